@@ -0,0 +1,700 @@
+// Betza "funny notation" for fairy pieces, see https://www.chessvariants.com/piececlopedia.dir/betza.html
+//
+// supported atoms: W F D N A H C Z G (plus any leaper written as (a,b)), K R B Q.
+// doubling an atom makes it a rider (NN), a number limits the range (R4, N2).
+// supported modifiers:
+//   f b l r v s  directions, relative to the team. combine them (fl, ff, fs, ffl) to narrow down.
+//   m c          move only / capture only.
+//   p            hop over a screen (cannon), compiles to LineJump.
+//   i            only on the piece's first move. i2 means the third move, see NthMoveRules.
+//   x            pierce (not real betza), x3R pierces through at most 3 points worth of pieces.
+
+use crate::error::{ChessError, Result};
+use crate::piece::PieceRef;
+use crate::piece_rules::{Direction, Distance, MoveRules, MoveVec, NthMoveRules};
+use crate::vec2::Vec2;
+
+/// move and kill rules compiled from a betza string, see PieceBuilder::betza.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BetzaRules {
+    move_rules: Vec<MoveRules>,
+    kill_rules: Vec<MoveRules>,
+    nth_move_rules: Vec<NthMoveRules>,
+    use_moves_for_kills: bool,
+    use_kill_for_moves: bool,
+}
+
+impl BetzaRules {
+    pub fn move_rules(&self) -> &Vec<MoveRules> {
+        &self.move_rules
+    }
+
+    pub fn kill_rules(&self) -> &Vec<MoveRules> {
+        &self.kill_rules
+    }
+
+    pub fn nth_move_rules(&self) -> &Vec<NthMoveRules> {
+        &self.nth_move_rules
+    }
+
+    pub fn use_moves_for_kills(&self) -> bool {
+        self.use_moves_for_kills
+    }
+
+    pub fn use_kill_for_moves(&self) -> bool {
+        self.use_kill_for_moves
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Mode {
+    Both,
+    Move,
+    Kill,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Kind {
+    Plain,
+    Hop,
+    Pierce(u16),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Range {
+    Leap,
+    Ride(Distance),
+}
+
+// W/F/K based atoms compile to Blunt rules, everything else to jumps.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum AtomClass {
+    Orthogonal,
+    Diagonal,
+    Royal,
+    Leaper(u32, u32),
+}
+
+impl AtomClass {
+    fn of(vec: Vec2) -> Self {
+        let a = vec.x().unsigned_abs().max(vec.y().unsigned_abs());
+        let b = vec.x().unsigned_abs().min(vec.y().unsigned_abs());
+        match (a, b) {
+            (1, 0) => AtomClass::Orthogonal,
+            (1, 1) => AtomClass::Diagonal,
+            _ => AtomClass::Leaper(a, b),
+        }
+    }
+
+    fn is_unit(&self) -> bool {
+        !matches!(self, AtomClass::Leaper(..))
+    }
+
+    fn vectors(&self) -> Vec<Vec2> {
+        match self {
+            AtomClass::Orthogonal => Vec2::symmetric(1, 0),
+            AtomClass::Diagonal => Vec2::symmetric(1, 1),
+            AtomClass::Royal => {
+                let mut vecs = Vec2::symmetric(1, 0);
+                vecs.extend(Vec2::symmetric(1, 1));
+                vecs
+            }
+            AtomClass::Leaper(a, b) => Vec2::symmetric(*a, *b),
+        }
+    }
+
+    fn name(&self, range: Range) -> String {
+        let (leap, ride) = match self {
+            AtomClass::Orthogonal => ("W", "R"),
+            AtomClass::Diagonal => ("F", "B"),
+            AtomClass::Royal => ("K", "Q"),
+            AtomClass::Leaper(a, b) => {
+                let name = leaper_name(*a, *b);
+                return match range {
+                    Range::Leap => name,
+                    Range::Ride(Distance::Infinite) => format!("{name}{name}"),
+                    Range::Ride(Distance::Finite { distance }) => format!("{name}{distance}"),
+                };
+            }
+        };
+
+        match range {
+            Range::Leap => leap.to_string(),
+            Range::Ride(Distance::Infinite) => ride.to_string(),
+            Range::Ride(Distance::Finite { distance }) => format!("{ride}{distance}"),
+        }
+    }
+}
+
+fn leaper_name(a: u32, b: u32) -> String {
+    let name = match (a, b) {
+        (1, 0) => "W",
+        (1, 1) => "F",
+        (2, 0) => "D",
+        (2, 1) => "N",
+        (2, 2) => "A",
+        (3, 0) => "H",
+        (3, 1) => "C",
+        (3, 2) => "Z",
+        (3, 3) => "G",
+        _ => return format!("({a},{b})"),
+    };
+    name.to_string()
+}
+
+fn notation_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+// direction letters. a doubled letter prefers the long leg in that direction (ffN = narrow forward knight moves).
+fn selects(selector: &str, vec: Vec2) -> bool {
+    let (x, y) = (vec.x(), vec.y());
+    let letter = |c: char| match c {
+        'f' => y > 0,
+        'b' => y < 0,
+        'l' => x < 0,
+        'r' => x > 0,
+        'v' => y.abs() > x.abs(),
+        's' => x.abs() > y.abs(),
+        _ => false,
+    };
+
+    let chars: Vec<char> = selector.chars().collect();
+    let doubled = chars.len() > 1 && chars[0] == chars[1];
+    let long_leg = match chars[0] {
+        'f' | 'b' => 'v',
+        _ => 's',
+    };
+
+    chars.iter().all(|c| letter(*c)) && (!doubled || letter(long_leg))
+}
+
+// splits a run of direction letters into selectors, ex. "ffrbl" -> ["ffr", "bl"].
+fn split_selectors(run: &str) -> Vec<String> {
+    let chars: Vec<char> = run.chars().collect();
+    let perpendicular = |a: char, b: char| match a {
+        'f' | 'b' => matches!(b, 'l' | 'r' | 's'),
+        'l' | 'r' => matches!(b, 'f' | 'b' | 'v'),
+        _ => false,
+    };
+
+    let mut selectors = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let first = chars[i];
+        let mut len = 1;
+        if let Some(&second) = chars.get(i + 1) {
+            if first == second && first != 'v' && first != 's' {
+                len = 2;
+                if let Some(&third) = chars.get(i + 2) {
+                    if third != 's' && third != 'v' && perpendicular(first, third) {
+                        len = 3;
+                    }
+                }
+            } else if perpendicular(first, second) {
+                len = 2;
+            }
+        }
+        selectors.push(chars[i..i + len].iter().collect());
+        i += len;
+    }
+    selectors
+}
+
+struct Leg {
+    initial: Option<u32>,
+    mode: Mode,
+    kind: Kind,
+    class: AtomClass,
+    range: Range,
+    vectors: Vec<Vec2>,
+}
+
+impl Leg {
+    fn compile(&self) -> Result<Vec<MoveRules>> {
+        let filtered = self.vectors.len() != self.class.vectors().len();
+
+        if self.class.is_unit() {
+            let distance = match self.range {
+                Range::Leap => Distance::finite(1),
+                Range::Ride(distance) => distance,
+            };
+            let move_info: Vec<MoveVec> = self
+                .vectors
+                .iter()
+                .map(|v| MoveVec::new(distance, Direction::from_vec(*v).unwrap()))
+                .collect();
+
+            return match self.kind {
+                Kind::Plain => {
+                    if self.class == AtomClass::Royal && !filtered && self.range == Range::Leap {
+                        Ok(vec![MoveRules::radius(1, false)])
+                    } else {
+                        Ok(vec![MoveRules::blunt(move_info)])
+                    }
+                }
+                _ if self.range == Range::Leap => Err(notation_error(
+                    "Hopping and piercing need a sliding atom (R, B, Q or a doubled W/F).".to_string(),
+                )),
+                Kind::Hop => Ok(vec![MoveRules::line_jump(move_info)]),
+                Kind::Pierce(max_points) => Ok(vec![MoveRules::pierce(move_info, max_points)]),
+            };
+        }
+
+        if self.kind != Kind::Plain {
+            return Err(notation_error(format!(
+                "Hopping and piercing are not supported for {}.",
+                self.class.name(self.range)
+            )));
+        }
+
+        match self.range {
+            Range::Leap if !filtered => match self.class {
+                AtomClass::Leaper(a, b) => Ok(vec![MoveRules::knight_jump(a, b)]),
+                _ => unreachable!(),
+            },
+            Range::Leap => Ok(self.vectors.iter().map(|v| MoveRules::jump(*v)).collect()),
+            Range::Ride(distance) => Ok(self
+                .vectors
+                .iter()
+                .map(|v| MoveRules::rider(*v, distance))
+                .collect()),
+        }
+    }
+}
+
+// reads digits starting at i, returns the number and the index after it.
+fn read_number(chars: &[char], mut i: usize) -> (Option<u32>, usize) {
+    let start = i;
+    while i < chars.len() && chars[i].is_ascii_digit() {
+        i += 1;
+    }
+    if start == i {
+        return (None, i);
+    }
+    let digits: String = chars[start..i].iter().collect();
+    (digits.parse().ok(), i)
+}
+
+// returns the atom, whether it rides by default, and the index after it.
+fn read_atom(chars: &[char], i: usize) -> Result<(AtomClass, bool, usize)> {
+    let atom = match chars[i] {
+        'W' => (AtomClass::Orthogonal, false),
+        'F' => (AtomClass::Diagonal, false),
+        'K' => (AtomClass::Royal, false),
+        'R' => (AtomClass::Orthogonal, true),
+        'B' => (AtomClass::Diagonal, true),
+        'Q' => (AtomClass::Royal, true),
+        'D' => (AtomClass::Leaper(2, 0), false),
+        'N' => (AtomClass::Leaper(2, 1), false),
+        'A' => (AtomClass::Leaper(2, 2), false),
+        'H' => (AtomClass::Leaper(3, 0), false),
+        'C' => (AtomClass::Leaper(3, 1), false),
+        'Z' => (AtomClass::Leaper(3, 2), false),
+        'G' => (AtomClass::Leaper(3, 3), false),
+        '(' => {
+            let close = chars[i..]
+                .iter()
+                .position(|c| *c == ')')
+                .ok_or_else(|| notation_error("Unclosed leaper, expected (a,b).".to_string()))?;
+            let inner: String = chars[i + 1..i + close].iter().collect();
+            let parts: Vec<&str> = inner.split(',').map(|s| s.trim()).collect();
+            let parsed: Vec<u32> = parts.iter().filter_map(|p| p.parse().ok()).collect();
+            if parts.len() != 2 || parsed.len() != 2 || (parsed[0] == 0 && parsed[1] == 0) {
+                return Err(notation_error(format!("Invalid leaper ({inner}).")));
+            }
+            let class = AtomClass::of(Vec2::new(parsed[0] as i32, parsed[1] as i32));
+            return Ok((class, false, i + close + 1));
+        }
+        c => return Err(notation_error(format!("Unknown atom '{c}'."))),
+    };
+    Ok((atom.0, atom.1, i + 1))
+}
+
+/// compiles betza notation (ex. "WfcF", "NN", "mRcpR") into piece rules.
+pub fn parse(notation: &str) -> Result<BetzaRules> {
+    let chars: Vec<char> = notation.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut legs = Vec::<Leg>::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let mut selectors = Vec::<String>::new();
+        let mut move_only = false;
+        let mut kill_only = false;
+        let mut kind = Kind::Plain;
+        let mut initial = None;
+
+        // modifiers
+        while i < chars.len() && chars[i].is_ascii_lowercase() {
+            match chars[i] {
+                'f' | 'b' | 'l' | 'r' | 'v' | 's' => {
+                    let start = i;
+                    while i < chars.len() && matches!(chars[i], 'f' | 'b' | 'l' | 'r' | 'v' | 's') {
+                        i += 1;
+                    }
+                    let run: String = chars[start..i].iter().collect();
+                    selectors.extend(split_selectors(&run));
+                    continue;
+                }
+                'm' => move_only = true,
+                'c' => kill_only = true,
+                'p' => kind = Kind::Hop,
+                'i' => {
+                    let (nth, next) = read_number(&chars, i + 1);
+                    initial = Some(nth.unwrap_or(0));
+                    i = next;
+                    continue;
+                }
+                'x' => {
+                    let (max_points, next) = read_number(&chars, i + 1);
+                    let max_points = match max_points {
+                        Some(n) => u16::try_from(n)
+                            .map_err(|_| notation_error(format!("Pierce limit {n} is too large.")))?,
+                        None => u16::MAX,
+                    };
+                    kind = Kind::Pierce(max_points);
+                    i = next;
+                    continue;
+                }
+                c => return Err(notation_error(format!("Unsupported modifier '{c}'."))),
+            }
+            i += 1;
+        }
+
+        if i >= chars.len() {
+            return Err(notation_error(format!(
+                "Modifiers at the end of \"{notation}\" are missing an atom."
+            )));
+        }
+
+        let (class, rides, next) = read_atom(&chars, i)?;
+        let atom = &chars[i..next];
+        i = next;
+
+        let mut range = if rides {
+            Range::Ride(Distance::infinite())
+        } else {
+            Range::Leap
+        };
+
+        // doubled atoms ride, numbers limit the range.
+        if !rides && chars[i..].starts_with(atom) {
+            range = Range::Ride(Distance::infinite());
+            i += atom.len();
+        }
+        let (limit, next) = read_number(&chars, i);
+        if let Some(limit) = limit {
+            if limit == 0 {
+                return Err(notation_error("Range can't be 0.".to_string()));
+            }
+            range = Range::Ride(Distance::finite(limit));
+            i = next;
+        }
+
+        let all = class.vectors();
+        let vectors: Vec<Vec2> = if selectors.is_empty() {
+            all
+        } else {
+            all.into_iter()
+                .filter(|v| selectors.iter().any(|s| selects(s, *v)))
+                .collect()
+        };
+        if vectors.is_empty() {
+            return Err(notation_error(format!(
+                "Directions {} select no moves of {}.",
+                selectors.concat(),
+                class.name(range)
+            )));
+        }
+
+        let mode = match (move_only, kill_only) {
+            (true, false) => Mode::Move,
+            (false, true) => Mode::Kill,
+            _ => Mode::Both,
+        };
+
+        legs.push(Leg {
+            initial,
+            mode,
+            kind,
+            class,
+            range,
+            vectors,
+        });
+    }
+
+    if legs.is_empty() {
+        return Err(notation_error("Empty notation.".to_string()));
+    }
+
+    assemble(legs)
+}
+
+fn assemble(legs: Vec<Leg>) -> Result<BetzaRules> {
+    let mut both = Vec::new();
+    let mut moves = Vec::new();
+    let mut kills = Vec::new();
+
+    for leg in legs.iter().filter(|l| l.initial.is_none()) {
+        let rules = leg.compile()?;
+        match leg.mode {
+            Mode::Both => both.extend(rules),
+            Mode::Move => moves.extend(rules),
+            Mode::Kill => kills.extend(rules),
+        }
+    }
+
+    // lay the rules out the same way piece::defaults does, flags instead of duplicates where possible.
+    let mut compiled = BetzaRules::default();
+    match (moves.is_empty(), kills.is_empty()) {
+        (true, _) => {
+            compiled.use_moves_for_kills = !both.is_empty();
+            compiled.move_rules = both;
+            compiled.kill_rules = kills;
+        }
+        (false, true) => {
+            compiled.use_kill_for_moves = !both.is_empty();
+            compiled.move_rules = moves;
+            compiled.kill_rules = both;
+        }
+        (false, false) => {
+            compiled.move_rules = both.clone();
+            compiled.move_rules.extend(moves);
+            compiled.kill_rules = both;
+            compiled.kill_rules.extend(kills);
+        }
+    }
+
+    // nth move rules can only kill through use_moves_for_kills.
+    for leg in legs.iter().filter(|l| l.initial.is_some()) {
+        let nth = leg.initial.unwrap();
+        let fits = match leg.mode {
+            Mode::Both => compiled.use_moves_for_kills,
+            Mode::Move => !compiled.use_moves_for_kills,
+            Mode::Kill => false,
+        };
+        if !fits {
+            return Err(notation_error(format!(
+                "Initial {} can't be expressed with this piece's other moves, nth move rules kill only if the regular moves do.",
+                leg.class.name(leg.range)
+            )));
+        }
+
+        let rules = leg.compile()?;
+        match compiled.nth_move_rules.iter_mut().find(|r| r.nth_move() == nth) {
+            Some(existing) => *existing = NthMoveRules::new([existing.move_rules().clone(), rules].concat(), nth, false),
+            None => compiled.nth_move_rules.push(NthMoveRules::new(rules, nth, false)),
+        }
+    }
+
+    Ok(compiled)
+}
+
+// one described move, ex. "imfR2".
+struct Group {
+    initial: Option<u32>,
+    mode: Mode,
+    kind: Kind,
+    class: AtomClass,
+    range: Range,
+    vectors: Vec<Vec2>,
+}
+
+impl Group {
+    fn same_move(&self, other: &Group) -> bool {
+        self.initial == other.initial
+            && self.mode == other.mode
+            && self.kind == other.kind
+            && self.range == other.range
+    }
+
+    fn is_full(&self) -> bool {
+        self.class.vectors().iter().all(|v| self.vectors.contains(v))
+    }
+
+    fn render(&self, out: &mut String) {
+        let mut prefix = String::new();
+        match self.initial {
+            Some(0) => prefix.push('i'),
+            Some(n) => prefix.push_str(&format!("i{n}")),
+            None => (),
+        }
+        match self.mode {
+            Mode::Move => prefix.push('m'),
+            Mode::Kill => prefix.push('c'),
+            Mode::Both => (),
+        }
+        match self.kind {
+            Kind::Hop => prefix.push('p'),
+            Kind::Pierce(u16::MAX) => prefix.push('x'),
+            Kind::Pierce(n) => prefix.push_str(&format!("x{n}")),
+            Kind::Plain => (),
+        }
+
+        let name = self.class.name(self.range);
+        for selector in cover(&self.class.vectors(), &self.vectors) {
+            out.push_str(&prefix);
+            out.push_str(&selector);
+            out.push_str(&name);
+        }
+    }
+}
+
+// picks direction selectors that together select exactly `target` out of `all`.
+fn cover(all: &[Vec2], target: &[Vec2]) -> Vec<String> {
+    if all.iter().all(|v| target.contains(v)) {
+        return vec![String::new()];
+    }
+
+    const CANDIDATES: [&str; 26] = [
+        "f", "b", "l", "r", "v", "s", "fl", "fr", "bl", "br", "ff", "bb", "ll", "rr", "fs", "bs",
+        "lv", "rv", "ffl", "ffr", "bbl", "bbr", "llf", "llb", "rrf", "rrb",
+    ];
+
+    let mut remaining: Vec<Vec2> = target.to_vec();
+    let mut selectors = Vec::new();
+    while !remaining.is_empty() {
+        // max_by_key keeps the last maximum, so walk backwards to prefer the shortest selector.
+        let best = CANDIDATES
+            .iter()
+            .rev()
+            .map(|c| (c, all.iter().filter(|v| selects(c, **v)).copied().collect::<Vec<_>>()))
+            .filter(|(_, picked)| !picked.is_empty() && picked.iter().all(|v| target.contains(v)))
+            .max_by_key(|(_, picked)| picked.iter().filter(|v| remaining.contains(v)).count());
+
+        // every single vector of every atom is reachable with a triple, so this always finds one.
+        let (selector, picked) = best.unwrap();
+        if !picked.iter().any(|v| remaining.contains(v)) {
+            break;
+        }
+        remaining.retain(|v| !picked.contains(v));
+        selectors.push(selector.to_string());
+    }
+    selectors
+}
+
+fn add_vector(groups: &mut Vec<Group>, template: Group) {
+    let existing = groups
+        .iter_mut()
+        .find(|g| g.same_move(&template) && g.class == template.class);
+    match existing {
+        Some(group) => {
+            for v in template.vectors {
+                if !group.vectors.contains(&v) {
+                    group.vectors.push(v);
+                }
+            }
+        }
+        None => groups.push(template),
+    }
+}
+
+fn add_rule(groups: &mut Vec<Group>, rule: &MoveRules, initial: Option<u32>, mode: Mode) {
+    let group = |kind, class, range, vectors| Group {
+        initial,
+        mode,
+        kind,
+        class,
+        range,
+        vectors,
+    };
+
+    let mut add_move_vecs = |move_info: &Vec<MoveVec>, kind: Kind| {
+        for move_vec in move_info {
+            let vec = move_vec.direction().as_vec();
+            let range = match move_vec.distance() {
+                Distance::Finite { distance: 1 } => Range::Leap,
+                distance => Range::Ride(distance),
+            };
+            add_vector(groups, group(kind, AtomClass::of(vec), range, vec![vec]));
+        }
+    };
+
+    match rule {
+        MoveRules::Blunt { move_info } => add_move_vecs(move_info, Kind::Plain),
+        MoveRules::LineJump { move_info } => add_move_vecs(move_info, Kind::Hop),
+        MoveRules::Pierce { move_info, max_points } => add_move_vecs(move_info, Kind::Pierce(*max_points)),
+        MoveRules::Radius { tiles } => {
+            add_vector(groups, group(Kind::Plain, AtomClass::Royal, Range::Leap, AtomClass::Royal.vectors()));
+            for a in 2..=*tiles {
+                for b in 0..=a {
+                    let class = AtomClass::Leaper(a, b);
+                    add_vector(groups, group(Kind::Plain, class, Range::Leap, class.vectors()));
+                }
+            }
+        }
+        MoveRules::KnightJump { radius, offset } => {
+            let class = AtomClass::of(Vec2::new(*radius as i32, *offset as i32));
+            add_vector(groups, group(Kind::Plain, class, Range::Leap, class.vectors()));
+        }
+        MoveRules::Jump { translation } => {
+            let class = AtomClass::of(*translation);
+            add_vector(groups, group(Kind::Plain, class, Range::Leap, vec![*translation]));
+        }
+        MoveRules::Rider { translation, distance } => {
+            let class = AtomClass::of(*translation);
+            add_vector(groups, group(Kind::Plain, class, Range::Ride(*distance), vec![*translation]));
+        }
+    }
+}
+
+fn royal_pair(groups: &[Group]) -> Option<(usize, usize)> {
+    groups.iter().enumerate().find_map(|(w, ortho)| {
+        if ortho.class != AtomClass::Orthogonal || !ortho.is_full() {
+            return None;
+        }
+        groups
+            .iter()
+            .position(|g| g.class == AtomClass::Diagonal && g.is_full() && g.same_move(ortho))
+            .map(|f| (w, f))
+    })
+}
+
+/// describes the piece's rules in betza notation. parse(describe(piece)) moves the same way as the piece.
+pub fn describe(piece: &PieceRef) -> String {
+    let mut groups = Vec::<Group>::new();
+
+    for rule in piece.move_rules() {
+        let mode = if piece.can_use_moves_for_kills() || piece.kill_rules().contains(rule) {
+            Mode::Both
+        } else {
+            Mode::Move
+        };
+        add_rule(&mut groups, rule, None, mode);
+    }
+    for rule in piece.kill_rules() {
+        if piece.move_rules().contains(rule) {
+            continue;
+        }
+        let mode = if piece.can_use_kill_for_moves() {
+            Mode::Both
+        } else {
+            Mode::Kill
+        };
+        add_rule(&mut groups, rule, None, mode);
+    }
+    for nth_move_rule in piece.nth_move_rules() {
+        let mode = if piece.can_use_moves_for_kills() {
+            Mode::Both
+        } else {
+            Mode::Move
+        };
+        for rule in nth_move_rule.move_rules() {
+            add_rule(&mut groups, rule, Some(nth_move_rule.nth_move()), mode);
+        }
+    }
+
+    // full W + full F of the same kind is a K (or Q).
+    while let Some((w, f)) = royal_pair(&groups) {
+        let diagonal = groups.remove(f);
+        let w = if f < w { w - 1 } else { w };
+        groups[w].class = AtomClass::Royal;
+        groups[w].vectors.extend(diagonal.vectors);
+    }
+
+    let mut out = String::new();
+    for group in &groups {
+        group.render(&mut out);
+    }
+    out
+}
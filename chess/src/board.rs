@@ -1,6 +1,6 @@
 // support up to four teams later
 
-use crate::error::Result;
+use crate::error::{ChessError, Result};
 use crate::{
     piece::Piece,
     piece_set::PieceSet,
@@ -23,8 +23,9 @@ impl Board {
 
         for _ in 0..h {
             let mut row = Vec::<Tile>::with_capacity(w as usize);
-            for _ in 0..w {
-                row.push(TileBuilder::new().build());
+            for x in 0..w {
+                // rows are stored top first.
+                row.push(TileBuilder::new(Coord::new(x, h - 1 - tiles.len() as u32)).build());
             }
             tiles.push(row.into_boxed_slice());
         }
@@ -46,20 +47,8 @@ impl Board {
 
         for (i, piece) in set.pieces().iter().enumerate() {
             let start = rel_starting_coords[i];
-            let start_info = team.start_info();
-            let abs_coord = self.rel_coord_to_absolute(start, start_info);
-            // add the translation vector (offset) from relative to absolute space.
-            let tile_opt = match start_info {
-                StartInfo::Bottom { offset } => self.tile_at(abs_coord.x() + offset, abs_coord.y()).unwrap(),
-                StartInfo::Top { offset } => self.tile_at(abs_coord.x() - offset, abs_coord.y()).unwrap(),
-                StartInfo::Left { offset } => self.tile_at(abs_coord.x(), abs_coord.y() - offset).unwrap(),
-                StartInfo::Right { offset } => self.tile_at(abs_coord.x(), abs_coord.y() + offset).unwrap(),
-            };
-            //
-
-            let mut tile_lock = tile_opt.write().unwrap();
-            tile_lock.set_piece(piece.clone())?;
-            drop(tile_lock);
+            let abs_coord = self.rel_coord_to_absolute(start, team.start_info());
+            self.tile(abs_coord)?.write()?.set_piece(piece.clone())?;
         }
         
         self.piece_sets.push(set);
@@ -67,6 +56,20 @@ impl Board {
         Ok(())
     }
 
+    // like add_piece_set, but pieces go wherever `positions` says (none = not on the board).
+    pub(crate) fn place_piece_set(&mut self, set: PieceSet, positions: &[Option<Coord>]) -> Result<()> {
+        for (piece, position) in set.pieces().iter().zip(positions) {
+            let Some(coord) = position else {
+                continue;
+            };
+            self.tile(*coord)?.write()?.set_piece(piece.clone())?;
+        }
+
+        self.piece_sets.push(set);
+
+        Ok(())
+    }
+
     /// should only be called once
     // pub fn init(&mut self) -> Result<()> {
     //     for set in &self.piece_sets {
@@ -109,33 +112,40 @@ impl Board {
         None
     }
 
-    pub fn rel_coord_to_absolute(&self, original: Coord, start_info: StartInfo) -> Coord {
-        let mut x = original.x();
-        let mut y = original.y();
-
-        let abs = match start_info {
-            StartInfo::Bottom { .. } => original,
-            StartInfo::Left { .. } => Coord::new(y, self.height - 1 - x),
-            StartInfo::Right { .. } => Coord::new(self.width - 1 - y, x),
-            StartInfo::Top { .. } => Coord::new(self.width - 1 - x, self.height - 1 - y),
-        };
+    /// like tile_at, but out of bounds is an error.
+    pub fn tile(&self, coord: Coord) -> Result<Tile> {
+        self.tile_at(coord.x(), coord.y()).ok_or(ChessError::OutOfBounds {
+            coord,
+            width: self.width,
+            height: self.height,
+        })
+    }
 
-        abs
+    /// the offset is applied here too, so this gives the actual tile a relative coordinate points to.
+    pub fn rel_coord_to_absolute(&self, original: Coord, start_info: StartInfo) -> Coord {
+        let x = original.x();
+        let y = original.y();
+
+        // wrapping so coordinates that fall off the board stay out of bounds instead of panicking.
+        match start_info {
+            StartInfo::Bottom { offset } => Coord::new(x.wrapping_add(offset), y),
+            StartInfo::Left { offset } => Coord::new(y, (self.height - 1).wrapping_sub(x).wrapping_sub(offset)),
+            StartInfo::Right { offset } => Coord::new((self.width - 1).wrapping_sub(y), x.wrapping_add(offset)),
+            StartInfo::Top { offset } => Coord::new((self.width - 1).wrapping_sub(x).wrapping_sub(offset), (self.height - 1).wrapping_sub(y)),
+        }
     }
 
+    /// inverse of rel_coord_to_absolute.
     pub fn absolute_coord_to_rel(&self, original: Coord, start_info: StartInfo) -> Coord {
-        todo!();
-        let mut x = original.x();
-        let mut y = original.y();
-
-        let rel = match start_info {
-            StartInfo::Bottom { .. } => original,
-            StartInfo::Left { .. } => Coord::new(y, self.height - 1 - x),
-            StartInfo::Right { .. } => Coord::new(self.width - 1 - y, x),
-            StartInfo::Top { .. } => Coord::new(self.width - 1 - x, self.height - 1 - y),
-        };
-
-        
+        let x = original.x();
+        let y = original.y();
+
+        match start_info {
+            StartInfo::Bottom { offset } => Coord::new(x.wrapping_sub(offset), y),
+            StartInfo::Left { offset } => Coord::new((self.height - 1).wrapping_sub(offset).wrapping_sub(y), x),
+            StartInfo::Right { offset } => Coord::new(y.wrapping_sub(offset), (self.width - 1).wrapping_sub(x)),
+            StartInfo::Top { offset } => Coord::new((self.width - 1).wrapping_sub(offset).wrapping_sub(x), (self.height - 1).wrapping_sub(y)),
+        }
     }
 
     // for debugging stuff
@@ -172,7 +182,7 @@ impl Board {
         return self.height;
     }
 
-    pub fn piece_sets(&self) -> Vec<PieceSet> {
-        self.piece_sets.clone()
+    pub fn piece_sets(&self) -> &Vec<PieceSet> {
+        &self.piece_sets
     }
 }
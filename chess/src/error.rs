@@ -1,21 +1,54 @@
+use std::sync::PoisonError;
+
 use thiserror::Error;
 
+use crate::explain::IllegalReason;
+use crate::r#move::{Coord, MoveKey};
+
 pub type Result<T> = std::result::Result<T, ChessError>;
 
 #[derive(Error, Debug)]
 pub enum ChessError {
-    #[error("Error with tile.")]
-    TileActionError { why: String },
-
-    #[error("Error creating piece.")]
+    #[error("Error creating piece: {why}")]
     PieceCreationError { why: String },
 
-    #[error("Error validating game.")]
+    #[error("Error validating game: {why}")]
     GameValidationError { why: String },
 
-    #[error("Error calculating move.")]
+    #[error("Error calculating move: {why}")]
     MoveCalculationError { why: String },
 
-    #[error("Error translating coordinate.")]
+    #[error("Error translating coordinate: {why}")]
     CoordTranslationError { why: String },
+
+    #[error("Error parsing notation: {why}")]
+    NotationError { why: String },
+
+    #[error("{coord} is outside the {width}x{height} board.")]
+    OutOfBounds { coord: Coord, width: u32, height: u32 },
+
+    #[error("{coord} is already occupied by a {piece}.")]
+    TileOccupied { coord: Coord, piece: String },
+
+    #[error("There is no piece on {coord}.")]
+    TileEmpty { coord: Coord },
+
+    #[error("{mv} is illegal: {reason}")]
+    IllegalMove { mv: MoveKey, reason: IllegalReason },
+
+    #[error("There is no team named {name}.")]
+    UnknownTeam { name: String },
+
+    #[error("It is {current}'s turn, not {team}'s.")]
+    NotYourTurn { team: String, current: String },
+
+    // a thread panicked while holding a piece or tile.
+    #[error("A lock was poisoned by a panicking thread.")]
+    LockPoisoned,
+}
+
+impl<T> From<PoisonError<T>> for ChessError {
+    fn from(_: PoisonError<T>) -> Self {
+        ChessError::LockPoisoned
+    }
 }
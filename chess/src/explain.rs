@@ -0,0 +1,401 @@
+// why a piece can't make a move, for players and for people designing pieces.
+// the piece's rules are traced towards the target on the current board, and the reason
+// from the rule that came closest to working is reported.
+
+use std::fmt;
+
+use crate::error::Result;
+use crate::game::Game;
+use crate::move_calculation::MoveGen;
+use crate::piece::PieceRef;
+use crate::piece_rules::{Distance, MoveRules};
+use crate::r#move::Coord;
+use crate::team::Team;
+use crate::vec2::Vec2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Immunity {
+    Jump,
+    Pierce,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IllegalReason {
+    OutOfBounds { coord: Coord },
+    NoPiece { coord: Coord },
+    NotYourPiece { owner: String },
+    NotYourTurn { current: String },
+    SameTile,
+    OwnPieceAtTarget { coord: Coord },
+    // no rule of the piece goes there, even on an empty board.
+    NoRuleReaches,
+    // only a rule for the piece's nth move goes there.
+    NthMoveExpired { nth_move: u32, move_num: u32 },
+    BlockedBy { coord: Coord },
+    // hopping needs exactly one piece in between.
+    NothingToJumpOver,
+    // the rules that go there can't kill the piece standing there.
+    CannotKill { coord: Coord },
+    // the rules that go there only kill, and the tile is empty.
+    NothingToKill,
+    TargetImmune { coord: Coord, immunity: Immunity },
+    PierceLimit { max_points: u16 },
+    LeavesRoyalInDanger { royal: Coord },
+    // the tiles are fine, the promotion asked for isn't.
+    InvalidPromotion,
+}
+
+impl fmt::Display for IllegalReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IllegalReason::OutOfBounds { coord } => write!(f, "{} is not on the board", coord),
+            IllegalReason::NoPiece { coord } => write!(f, "there is no piece on {}", coord),
+            IllegalReason::NotYourPiece { owner } => write!(f, "the piece belongs to {}", owner),
+            IllegalReason::NotYourTurn { current } => write!(f, "it is {}'s turn", current),
+            IllegalReason::SameTile => write!(f, "the piece is already there"),
+            IllegalReason::OwnPieceAtTarget { coord } => write!(f, "{} holds a piece of the same team", coord),
+            IllegalReason::NoRuleReaches => write!(f, "none of the piece's rules go there"),
+            IllegalReason::NthMoveExpired { nth_move, move_num } => write!(
+                f,
+                "that rule only applies to move {} of the piece, this would be move {}",
+                nth_move, move_num
+            ),
+            IllegalReason::BlockedBy { coord } => write!(f, "the way is blocked by the piece on {}", coord),
+            IllegalReason::NothingToJumpOver => write!(f, "there has to be exactly one piece to jump over"),
+            IllegalReason::CannotKill { coord } => write!(f, "the piece can't kill the piece on {} that way", coord),
+            IllegalReason::NothingToKill => write!(f, "the piece can only go there to kill"),
+            IllegalReason::TargetImmune { coord, immunity } => write!(
+                f,
+                "the piece on {} is immune to {}",
+                coord,
+                match immunity {
+                    Immunity::Jump => "jumping",
+                    Immunity::Pierce => "piercing",
+                }
+            ),
+            IllegalReason::PierceLimit { max_points } => {
+                write!(f, "that pierces through more than {} points", max_points)
+            }
+            IllegalReason::LeavesRoyalInDanger { royal } => write!(f, "the royal piece on {} would be in danger", royal),
+            IllegalReason::InvalidPromotion => write!(f, "the piece can't promote into that"),
+        }
+    }
+}
+
+impl IllegalReason {
+    // lower is closer to being legal, the closest reason is the one reported.
+    fn rank(&self) -> u8 {
+        match self {
+            IllegalReason::TargetImmune { .. } => 0,
+            IllegalReason::PierceLimit { .. } => 1,
+            IllegalReason::CannotKill { .. } => 2,
+            IllegalReason::NothingToKill => 3,
+            IllegalReason::BlockedBy { .. } => 4,
+            IllegalReason::NothingToJumpOver => 5,
+            IllegalReason::NthMoveExpired { .. } => 6,
+            _ => 7,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Occupant {
+    Empty,
+    Friend,
+    Enemy {
+        points: u16,
+        jump_immune: bool,
+        pierce_immune: bool,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum Ray {
+    Leap { jump: bool },
+    Slide,
+    Hop,
+    Pierce { max_points: u16 },
+}
+
+impl Game {
+    /// why the team to move can't move the piece on from to to, none when it can.
+    pub fn explain_illegal(&self, from: Coord, to: Coord) -> Result<Option<IllegalReason>> {
+        self.explain_illegal_for(self.current_team().name(), from, to)
+    }
+
+    /// like explain_illegal, for whoever controls team_name.
+    pub fn explain_illegal_for(&self, team_name: &str, from: Coord, to: Coord) -> Result<Option<IllegalReason>> {
+        let team = self.piece_set(team_name)?.team();
+        let board = self.board();
+
+        for coord in [from, to] {
+            if board.tile_at(coord.x(), coord.y()).is_none() {
+                return Ok(Some(IllegalReason::OutOfBounds { coord }));
+            }
+        }
+        let Some(piece) = board.tile(from)?.read()?.piece() else {
+            return Ok(Some(IllegalReason::NoPiece { coord: from }));
+        };
+        let owner = piece.read()?.team_unchecked();
+        if owner.name() != team.name() {
+            return Ok(Some(IllegalReason::NotYourPiece {
+                owner: owner.name().to_string(),
+            }));
+        }
+        let current = self.current_team();
+        if current.name() != team.name() {
+            return Ok(Some(IllegalReason::NotYourTurn {
+                current: current.name().to_string(),
+            }));
+        }
+        if from == to {
+            return Ok(Some(IllegalReason::SameTile));
+        }
+
+        if self.legal_moves()?.iter().any(|m| m.from() == from && m.to() == to) {
+            return Ok(None);
+        }
+
+        // possible, if it weren't for the royal pieces.
+        let mut moves = Vec::new();
+        MoveGen::new(board, self.en_passant(), false).piece_moves(&piece, &mut moves);
+        if let Some(m) = moves.into_iter().find(|m| m.to() == to) {
+            let log = m.execute()?;
+            let royal = self
+                .royal_coords(team.name())
+                .into_iter()
+                .find(|coord| self.is_attacked(*coord, team.name()));
+            log.reverse()?;
+
+            if let Some(royal) = royal {
+                return Ok(Some(IllegalReason::LeavesRoyalInDanger { royal }));
+            }
+        }
+
+        if let Occupant::Friend = self.occupant(&team, to)? {
+            return Ok(Some(IllegalReason::OwnPieceAtTarget { coord: to }));
+        }
+
+        let data = piece.read()?.clone();
+        let reason = self
+            .trace_rules(&data, &team, from, to)?
+            .into_iter()
+            .min_by_key(|reason| reason.rank())
+            .unwrap_or(IllegalReason::NoRuleReaches);
+
+        Ok(Some(reason))
+    }
+
+    // a reason for every rule that goes to the target.
+    fn trace_rules(&self, data: &PieceRef, team: &Team, from: Coord, to: Coord) -> Result<Vec<IllegalReason>> {
+        let mut reasons = Vec::new();
+
+        // (rules, can move, can kill, nth move)
+        let mut groups = vec![(data.move_rules(), true, data.can_use_moves_for_kills(), None)];
+        for nth_move_rule in data.nth_move_rules() {
+            groups.push((
+                nth_move_rule.move_rules(),
+                true,
+                data.can_use_moves_for_kills(),
+                Some(nth_move_rule.nth_move()),
+            ));
+        }
+        groups.push((data.kill_rules(), data.can_use_kill_for_moves(), true, None));
+
+        for (rules, can_move, can_kill, nth_move) in groups {
+            for rule in rules {
+                for (rel_step, max_steps, ray) in rays(rule) {
+                    let abs_step = rel_step.rel_to_absolute(team.start_info());
+                    let Some(steps) = steps_to(from, to, abs_step, max_steps) else {
+                        continue;
+                    };
+
+                    if let Some(nth_move) = nth_move.filter(|nth| *nth != data.move_num()) {
+                        reasons.push(IllegalReason::NthMoveExpired {
+                            nth_move,
+                            move_num: data.move_num(),
+                        });
+                        continue;
+                    }
+
+                    let mut between = Vec::new();
+                    for n in 1..steps {
+                        let coord = from.translate(&abs_step.mul(n as i32))?;
+                        between.push((coord, self.occupant(team, coord)?));
+                    }
+                    let target = self.occupant(team, to)?;
+
+                    if let Some(reason) = judge(ray, &between, to, target, can_move, can_kill) {
+                        reasons.push(reason);
+                    }
+                }
+            }
+        }
+
+        Ok(reasons)
+    }
+
+    fn occupant(&self, team: &Team, coord: Coord) -> Result<Occupant> {
+        let tile = self.board().tile(coord)?;
+        let tile_lock = tile.read()?;
+        let Some(piece) = tile_lock.piece() else {
+            return Ok(Occupant::Empty);
+        };
+        if tile_lock.team_on_tile_unchecked().name() == team.name() {
+            return Ok(Occupant::Friend);
+        }
+
+        let lock = piece.read()?;
+        Ok(Occupant::Enemy {
+            points: lock.points(),
+            jump_immune: lock.is_jump_immune(),
+            pierce_immune: lock.is_pierce_immune(),
+        })
+    }
+}
+
+// (relative step, how many times it repeats, how it treats what's in between)
+fn rays(rule: &MoveRules) -> Vec<(Vec2, u32, Ray)> {
+    let max_steps = |distance: Distance| match distance {
+        Distance::Finite { distance } => distance,
+        Distance::Infinite => u32::MAX,
+    };
+
+    match rule {
+        MoveRules::Jump { translation } => vec![(*translation, 1, Ray::Leap { jump: true })],
+        MoveRules::KnightJump { radius, offset } => Vec2::symmetric(*radius, *offset)
+            .into_iter()
+            .map(|v| (v, 1, Ray::Leap { jump: true }))
+            .collect(),
+        MoveRules::Radius { tiles } => {
+            let tiles = *tiles as i32;
+            (-tiles..=tiles)
+                .flat_map(|x| (-tiles..=tiles).map(move |y| Vec2::new(x, y)))
+                .filter(|v| v.x() != 0 || v.y() != 0)
+                .map(|v| (v, 1, Ray::Leap { jump: false }))
+                .collect()
+        }
+        MoveRules::Blunt { move_info } => move_info
+            .iter()
+            .map(|mv| (mv.direction().as_vec(), max_steps(mv.distance()), Ray::Slide))
+            .collect(),
+        MoveRules::Rider { translation, distance } => vec![(*translation, max_steps(*distance), Ray::Slide)],
+        MoveRules::LineJump { move_info } => move_info
+            .iter()
+            .map(|mv| (mv.direction().as_vec(), max_steps(mv.distance()), Ray::Hop))
+            .collect(),
+        MoveRules::Pierce { move_info, max_points } => move_info
+            .iter()
+            .map(|mv| {
+                (
+                    mv.direction().as_vec(),
+                    max_steps(mv.distance()),
+                    Ray::Pierce { max_points: *max_points },
+                )
+            })
+            .collect(),
+    }
+}
+
+// how many steps of abs_step lead from from to to, if any.
+fn steps_to(from: Coord, to: Coord, abs_step: Vec2, max_steps: u32) -> Option<u32> {
+    let dx = to.x() as i64 - from.x() as i64;
+    let dy = to.y() as i64 - from.y() as i64;
+    let (sx, sy) = (abs_step.x() as i64, abs_step.y() as i64);
+    // a zero translation never leaves its tile.
+    if sx == 0 && sy == 0 {
+        return None;
+    }
+
+    let steps = if sx != 0 { dx / sx } else { dy / sy };
+    if steps < 1 || steps > max_steps as i64 || sx * steps != dx || sy * steps != dy {
+        return None;
+    }
+    Some(steps as u32)
+}
+
+// none when the ray would allow the move.
+fn judge(
+    ray: Ray,
+    between: &[(Coord, Occupant)],
+    to: Coord,
+    target: Occupant,
+    can_move: bool,
+    can_kill: bool,
+) -> Option<IllegalReason> {
+    let mut pierced_points = 0_u32;
+    let mut pierced_any = false;
+
+    match ray {
+        Ray::Leap { .. } => {}
+        Ray::Slide => {
+            if let Some((coord, _)) = between.iter().find(|(_, o)| !matches!(o, Occupant::Empty)) {
+                return Some(IllegalReason::BlockedBy { coord: *coord });
+            }
+        }
+        Ray::Hop => {
+            let mut screens = between.iter().filter(|(_, o)| !matches!(o, Occupant::Empty));
+            if screens.next().is_none() {
+                return Some(IllegalReason::NothingToJumpOver);
+            }
+            if let Some((coord, _)) = screens.next() {
+                return Some(IllegalReason::BlockedBy { coord: *coord });
+            }
+        }
+        Ray::Pierce { max_points } => {
+            for (coord, occupant) in between {
+                match occupant {
+                    Occupant::Empty => {}
+                    Occupant::Friend => return Some(IllegalReason::BlockedBy { coord: *coord }),
+                    Occupant::Enemy { points, pierce_immune, .. } => {
+                        if !can_kill {
+                            return Some(IllegalReason::CannotKill { coord: *coord });
+                        }
+                        if *pierce_immune {
+                            return Some(IllegalReason::TargetImmune {
+                                coord: *coord,
+                                immunity: Immunity::Pierce,
+                            });
+                        }
+                        pierced_points += *points as u32;
+                        pierced_any = true;
+                        if pierced_points > max_points as u32 {
+                            return Some(IllegalReason::PierceLimit { max_points });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    match target {
+        Occupant::Empty => {
+            let allowed = if pierced_any { can_kill } else { can_move };
+            (!allowed).then_some(IllegalReason::NothingToKill)
+        }
+        Occupant::Friend => Some(IllegalReason::OwnPieceAtTarget { coord: to }),
+        Occupant::Enemy {
+            points,
+            jump_immune,
+            pierce_immune,
+        } => {
+            if !can_kill {
+                return Some(IllegalReason::CannotKill { coord: to });
+            }
+            match ray {
+                Ray::Leap { jump: true } | Ray::Hop if jump_immune => Some(IllegalReason::TargetImmune {
+                    coord: to,
+                    immunity: Immunity::Jump,
+                }),
+                Ray::Pierce { .. } if pierce_immune => Some(IllegalReason::TargetImmune {
+                    coord: to,
+                    immunity: Immunity::Pierce,
+                }),
+                Ray::Pierce { max_points } if pierced_points + points as u32 > max_points as u32 => {
+                    Some(IllegalReason::PierceLimit { max_points })
+                }
+                _ => None,
+            }
+        }
+    }
+}
@@ -0,0 +1,252 @@
+// forsyth-edwards notation, for two team games with white at the bottom and black at the top.
+// pieces are looked up by symbol in a catalog, uppercase for white and lowercase for black.
+// counts of empty tiles can take more than one digit, so boards bigger than 8x8 work too.
+
+use std::sync::Arc;
+
+use crate::board::Board;
+use crate::error::{ChessError, Result};
+use crate::game::{EnPassant, Game};
+use crate::notation::{parse_square, square_name};
+use crate::piece::{self, Piece, PieceBuilder};
+use crate::piece_set::PieceSet;
+use crate::r#move::Coord;
+use crate::team::{StartInfo, Team};
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// the six standard pieces.
+pub fn standard_catalog() -> Vec<Piece> {
+    vec![
+        piece::defaults::pawn(),
+        piece::defaults::knight(),
+        piece::defaults::bishop(),
+        piece::defaults::rook(),
+        piece::defaults::queen(),
+        piece::defaults::king(),
+    ]
+}
+
+fn fen_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+impl Game {
+    pub fn from_fen(fen: &str) -> Result<Self> {
+        Self::from_fen_with(fen, &standard_catalog())
+    }
+
+    /// the halfmove clock and fullmove number can be left out.
+    pub fn from_fen_with(fen: &str, catalog: &[Piece]) -> Result<Self> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(fen_error(format!("Expected at least 4 fields in {}.", fen)));
+        }
+
+        // (symbol, x) for every piece, rows from the top.
+        let mut rows = Vec::new();
+        for row_str in fields[0].split('/') {
+            let mut row = Vec::new();
+            let mut x = 0;
+            let mut empty = 0;
+            for c in row_str.chars() {
+                if let Some(digit) = c.to_digit(10) {
+                    empty = empty * 10 + digit;
+                    continue;
+                }
+                x += std::mem::take(&mut empty);
+                row.push((c, x));
+                x += 1;
+            }
+            rows.push((row, x + empty));
+        }
+
+        let width = rows[0].1;
+        let height = rows.len() as u32;
+        if width == 0 || rows.iter().any(|(_, w)| *w != width) {
+            return Err(fen_error(format!("Rows of {} don't have the same width.", fields[0])));
+        }
+
+        let mut board = Board::new(width, height);
+        let white = Arc::new(Team::new("White".to_string(), StartInfo::Bottom { offset: 0 }));
+        let black = Arc::new(Team::new("Black".to_string(), StartInfo::Top { offset: 0 }));
+        let mut white_set = PieceSet::new(white.clone(), Vec::new());
+        let mut black_set = PieceSet::new(black.clone(), Vec::new());
+
+        for (row_idx, (row, _)) in rows.iter().enumerate() {
+            let y = height - 1 - row_idx as u32;
+            for (symbol, x) in row {
+                let template = catalog
+                    .iter()
+                    .find(|p| p.read().unwrap().symbol() == symbol.to_ascii_uppercase())
+                    .ok_or(fen_error(format!("Unknown piece {}.", symbol)))?;
+                let piece = PieceBuilder::clone_piece(template);
+                // only what the castling rights and pawn rows allow counts as unmoved.
+                piece.write().unwrap().set_move_num(1);
+
+                let coord = Coord::new(*x, y);
+                if symbol.is_ascii_uppercase() {
+                    white_set.add_piece(piece, board.absolute_coord_to_rel(coord, white.start_info()))?;
+                } else {
+                    black_set.add_piece(piece, board.absolute_coord_to_rel(coord, black.start_info()))?;
+                }
+            }
+        }
+
+        board.add_piece_set(white_set)?;
+        board.add_piece_set(black_set)?;
+
+        for set in board.piece_sets() {
+            for piece in set.pieces() {
+                let mut lock = piece.write().unwrap();
+                let first_move_rules = lock.nth_move_rules().iter().any(|rules| rules.nth_move() == 0);
+                if first_move_rules && lock.rel_pos_unchecked().y() == 1 {
+                    lock.set_move_num(0);
+                }
+            }
+        }
+
+        if fields[2] != "-" {
+            for right in fields[2].chars() {
+                let set = &board.piece_sets()[if right.is_ascii_uppercase() { 0 } else { 1 }];
+                let kingside = match right.to_ascii_uppercase() {
+                    'K' => true,
+                    'Q' => false,
+                    _ => return Err(fen_error(format!("Unknown castling right {}.", right))),
+                };
+                grant_castling(&board, set, kingside)?;
+            }
+        }
+
+        let white_to_move = match fields[1] {
+            "w" => true,
+            "b" => false,
+            side => return Err(fen_error(format!("Unknown side to move {}.", side))),
+        };
+
+        let en_passant = match fields[3] {
+            "-" => None,
+            square => {
+                let square = parse_square(square)?;
+                // the team that just moved.
+                let (team, forward) = if white_to_move { (&black, -1) } else { (&white, 1) };
+                let victim = Coord::new(square.x(), square.y().wrapping_add_signed(forward));
+                let victim_is_there = board
+                    .tile_at(victim.x(), victim.y())
+                    .and_then(|tile| tile.read().unwrap().team_on_tile())
+                    .is_some_and(|t| t.name() == team.name());
+                victim_is_there.then(|| EnPassant::new(vec![square], victim, team.name().to_string()))
+            }
+        };
+
+        let number = |idx: usize, default: u32| -> Result<u32> {
+            match fields.get(idx) {
+                Some(field) => field.parse().map_err(|_| fen_error(format!("{} is not a number.", field))),
+                None => Ok(default),
+            }
+        };
+        let halfmove_clock = number(4, 0)?;
+        let fullmove = number(5, 1)?.max(1);
+        let current_move = (fullmove - 1) * 2 + if white_to_move { 0 } else { 1 };
+
+        Ok(Game::from_parts(board, current_move, Vec::new(), halfmove_clock, en_passant, Vec::new(), Vec::new()))
+    }
+
+    pub fn to_fen(&self) -> Result<String> {
+        let board = self.board();
+        let sets = board.piece_sets();
+        if sets.len() != 2 {
+            return Err(fen_error("Only two team games can be written as fen.".to_string()));
+        }
+
+        let mut rows = Vec::new();
+        for y in (0..board.height()).rev() {
+            let mut row = String::new();
+            let mut empty = 0;
+            for x in 0..board.width() {
+                let Some(piece) = board.tile_at(x, y).unwrap().read().unwrap().piece() else {
+                    empty += 1;
+                    continue;
+                };
+                if empty > 0 {
+                    row.push_str(&std::mem::take(&mut empty).to_string());
+                }
+                let lock = piece.read().unwrap();
+                if lock.team_unchecked().name() == sets[0].team().name() {
+                    row.push(lock.symbol());
+                } else {
+                    row.push(lock.symbol().to_ascii_lowercase());
+                }
+            }
+            if empty > 0 {
+                row.push_str(&empty.to_string());
+            }
+            rows.push(row);
+        }
+
+        let mut castling = String::new();
+        for (idx, set) in sets.iter().enumerate() {
+            for (kingside, symbol) in [(true, 'K'), (false, 'Q')] {
+                if castling_partner(board, set, kingside).is_some() {
+                    castling.push(if idx == 0 { symbol } else { symbol.to_ascii_lowercase() });
+                }
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.en_passant() {
+            Some(en_passant) => square_name(en_passant.squares()[0]),
+            None => "-".to_string(),
+        };
+
+        Ok(format!(
+            "{} {} {} {} {} {}",
+            rows.join("/"),
+            if self.current_move().is_multiple_of(2) { "w" } else { "b" },
+            castling,
+            en_passant,
+            self.halfmove_clock(),
+            self.current_move() / 2 + 1,
+        ))
+    }
+}
+
+fn absolute(board: &Board, piece: &Piece) -> Option<Coord> {
+    let lock = piece.read().unwrap();
+    if !lock.is_alive() {
+        return None;
+    }
+    Some(board.rel_coord_to_absolute(lock.rel_pos()?, lock.team()?.start_info()))
+}
+
+// the outermost castling partner on the side of the set's royal piece, any move number.
+fn outer_partner(board: &Board, set: &PieceSet, kingside: bool) -> Option<(Piece, Piece)> {
+    let royal = set.pieces().iter().find(|p| p.read().unwrap().is_royal())?;
+    let royal_coord = absolute(board, royal)?;
+
+    set.pieces()
+        .iter()
+        .filter(|p| p.read().unwrap().castles())
+        .filter_map(|p| Some((p, absolute(board, p)?)))
+        .filter(|(_, c)| c.y() == royal_coord.y() && (c.x() > royal_coord.x()) == kingside)
+        .max_by_key(|(_, c)| c.x().abs_diff(royal_coord.x()))
+        .map(|(p, _)| (royal.clone(), p.clone()))
+}
+
+fn castling_partner(board: &Board, set: &PieceSet, kingside: bool) -> Option<Piece> {
+    let (royal, partner) = outer_partner(board, set, kingside)?;
+    let unmoved = |p: &Piece| p.read().unwrap().move_num() == 0;
+    (unmoved(&royal) && unmoved(&partner)).then_some(partner)
+}
+
+fn grant_castling(board: &Board, set: &PieceSet, kingside: bool) -> Result<()> {
+    let (royal, partner) = outer_partner(board, set, kingside).ok_or(fen_error(format!(
+        "{} has no piece to castle with.",
+        set.team().name()
+    )))?;
+    royal.write().unwrap().set_move_num(0);
+    partner.write().unwrap().set_move_num(0);
+    Ok(())
+}
@@ -1,17 +1,36 @@
-use std::sync::{Arc, RwLockReadGuard};
+use std::sync::Arc;
 
 use crate::board::Board;
 use crate::error::{ChessError, Result};
-use crate::piece::{self, Piece, PieceBuilder, PieceRef};
-use crate::piece_rules::{MoveRules, Distance, Direction};
+use crate::explain::IllegalReason;
+use crate::move_calculation::MoveGen;
+use crate::piece::{self, Piece, PieceBuilder};
 use crate::piece_set::PieceSet;
-use crate::r#move::{Move, Coord};
+use crate::r#move::{Move, MoveKey, MoveLog, Coord};
 use crate::team::{StartInfo, Team};
 use crate::vec2::Vec2;
 
 pub struct Game {
     board: Board,
-    current_move: u32
+    // plies played, the team to move is current_move % teams.
+    current_move: u32,
+    history: Vec<MoveLog>,
+    // plies since the last kill or promotable piece move.
+    halfmove_clock: u32,
+    en_passant: Option<EnPassant>,
+    // the hash of every position since the game was created, the current one last.
+    hashes: Vec<u64>,
+    passes: Vec<Pass>,
+}
+
+// a turn that was passed, undone like a move.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Pass {
+    // how many moves were in the history when it happened.
+    history_len: usize,
+    prev_en_passant: Option<EnPassant>,
+    prev_halfmove_clock: u32,
 }
 
 impl Game {
@@ -52,12 +71,7 @@ impl Game {
         board.add_piece_set(white_set)?;
         board.add_piece_set(black_set)?;
 
-        Ok(
-            Self {
-                board, 
-                current_move: 0 
-            }
-        )
+        Ok(Self::from_parts(board, 0, Vec::new(), 0, None, Vec::new(), Vec::new()))
     }
 
     pub fn two_piece_test(piece: Piece) -> Result<Self> {
@@ -77,12 +91,43 @@ impl Game {
         board.add_piece_set(black_set)?;
         board.add_piece_set(green_set)?;
 
-        Ok(
-            Self {
-                board,
-                current_move: 0
-            }
-        )
+        Ok(Self::from_parts(board, 0, Vec::new(), 0, None, Vec::new(), Vec::new()))
+    }
+
+    // without hashes only the current position's is known.
+    pub(crate) fn from_parts(
+        board: Board,
+        current_move: u32,
+        history: Vec<MoveLog>,
+        halfmove_clock: u32,
+        en_passant: Option<EnPassant>,
+        hashes: Vec<u64>,
+        passes: Vec<Pass>,
+    ) -> Self {
+        let mut game = Self {
+            board,
+            current_move,
+            history,
+            halfmove_clock,
+            en_passant,
+            hashes,
+            passes,
+        };
+        if game.hashes.is_empty() {
+            game.hashes.push(game.compute_hash());
+        }
+        game
+    }
+
+    /// a game on a board that's already set up, the first piece set moves first.
+    pub fn from_board(board: Board) -> Self {
+        Self::from_parts(board, 0, Vec::new(), 0, None, Vec::new(), Vec::new())
+    }
+
+    /// a copy that shares no pieces or tiles with this one, so another thread can play on it
+    /// without waiting on this game's locks.
+    pub fn deep_clone(&self) -> Result<Self> {
+        self.snapshot()?.restore()
     }
 
     pub fn board(&self) -> &Board {
@@ -92,6 +137,29 @@ impl Game {
     pub fn current_move(&self) -> u32 {
         self.current_move
     }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn en_passant(&self) -> Option<&EnPassant> {
+        self.en_passant.as_ref()
+    }
+
+    /// every executed move, oldest first.
+    pub fn history(&self) -> &Vec<MoveLog> {
+        &self.history
+    }
+
+    /// the hash of every position so far, the current one last. positions from before the game
+    /// was loaded from fen aren't known.
+    pub fn hashes(&self) -> &Vec<u64> {
+        &self.hashes
+    }
+
+    pub(crate) fn passes(&self) -> &Vec<Pass> {
+        &self.passes
+    }
 }
 
 // game logic implementations
@@ -118,162 +186,343 @@ impl Game {
         Ok(())
     }
 
+    pub fn current_team(&self) -> Arc<Team> {
+        let sets = self.board.piece_sets();
+        sets[self.current_move as usize % sets.len()].team()
+    }
+
+    /// legal moves for whoever's turn it is.
+    pub fn legal_moves(&self) -> Result<Vec<Move>> {
+        self.calculate_moves_for(self.current_team().name().to_string())
+    }
+
+    /// legal moves for whoever's turn it is that pass the filter. faster than filtering
+    /// legal_moves, moves that don't pass never get checked for royal safety.
+    pub fn legal_moves_matching(&self, filter: impl Fn(&Move) -> bool) -> Result<Vec<Move>> {
+        self.moves_matching(self.current_team().name(), filter)
+    }
+
     // diabolical logic implementation
+    /// every legal move of the team, whether it's their turn or not.
     pub fn calculate_moves_for(&self, team_name: String) -> Result<Vec<Move>> {
-        let mut moves = Vec::<Move>::new();
+        self.moves_matching(&team_name, |_| true)
+    }
 
-        let board = &self.board;
-        let piece_sets = &board.piece_sets();
+    fn moves_matching(&self, team_name: &str, filter: impl Fn(&Move) -> bool) -> Result<Vec<Move>> {
+        let set = self.piece_set(team_name)?;
 
-        let set = piece_sets.iter().find(|set| set.team().name() == team_name);
+        let mut moves = Vec::<Move>::new();
+        let move_gen = MoveGen::new(&self.board, self.en_passant.as_ref(), false);
+        for piece in set.pieces() {
+            move_gen.piece_moves(piece, &mut moves);
+        }
+        self.castling_moves(set, &mut moves)?;
 
-        if set.is_none() {
-            return Err(ChessError::MoveCalculationError { why: format!("No team with name {}", team_name) });
+        let mut legal = Vec::with_capacity(moves.len());
+        for m in moves.into_iter().filter(|m| filter(m)) {
+            if self.keeps_royals_safe(&m, team_name)? {
+                legal.push(m);
+            }
         }
 
-        let set = set.unwrap();
-        let team = set.team();
+        Ok(legal)
+    }
 
-        for piece in set.pieces() {
-            let r_lock = piece.read().unwrap();
+    /// whether any royal piece of the team could be killed right now.
+    pub fn is_in_check(&self, team_name: &str) -> bool {
+        self.royal_coords(team_name)
+            .iter()
+            .any(|coord| self.is_attacked(*coord, team_name))
+    }
 
-            // handle regular movemenet rules
-            for rule in r_lock.move_rules() {
-                Game::process_move_rule(rule, piece.clone(), team.clone(), board, &r_lock, &mut moves);
-            }
+    // where the team's living royal pieces stand.
+    pub(crate) fn royal_coords(&self, team_name: &str) -> Vec<Coord> {
+        let Ok(set) = self.piece_set(team_name) else {
+            return Vec::new();
+        };
+
+        set.pieces()
+            .iter()
+            .filter_map(|piece| {
+                let lock = piece.read().unwrap();
+                if !lock.is_alive() || !lock.is_royal() {
+                    return None;
+                }
+                Some(self.board.rel_coord_to_absolute(lock.rel_pos()?, set.team().start_info()))
+            })
+            .collect()
+    }
 
-            for nth_move_rule in r_lock.nth_move_rules() {
-                // TODO! increment move number later. REFACTOR MOVE SYSTEM. game.executeMove(Move), game.undoPrevious()
-                if r_lock.move_num() == nth_move_rule.nth_move() {
-                    for rule in nth_move_rule.move_rules() {
-                        Game::process_move_rule(rule, piece.clone(), team.clone(), board, &r_lock, &mut moves);
-                    }
+    // can any other team kill whatever stands on the coord.
+    pub(crate) fn is_attacked(&self, coord: Coord, team_name: &str) -> bool {
+        let move_gen = MoveGen::new(&self.board, None, true);
+        let mut moves = Vec::new();
+
+        for set in self.board.piece_sets() {
+            if set.team().name() == team_name {
+                continue;
+            }
+            for piece in set.pieces() {
+                moves.clear();
+                move_gen.piece_moves(piece, &mut moves);
+                if moves.iter().any(|m| m.kills().contains(&coord)) {
+                    return true;
                 }
             }
         }
 
-        Ok(moves)
+        false
     }
 
-    fn process_move_rule(move_rule: &MoveRules, piece: Piece, team: Arc<Team>, board: &Board, piece_rlock: &RwLockReadGuard<PieceRef>, moves: &mut Vec<Move>) {
-        match move_rule {
-            MoveRules::Jump { translation } => {
-                todo!();
-            },
-            MoveRules::LineJump { move_info } => {
-                todo!();
+    // plays the move on the board and takes it back, the game's own state is left alone.
+    fn keeps_royals_safe(&self, m: &Move, team_name: &str) -> Result<bool> {
+        let log = m.clone().execute()?;
+        let safe = !self.is_in_check(team_name);
+        log.reverse()?;
+
+        Ok(safe)
+    }
+
+    // an unmoved royal piece moves two tiles towards an unmoved partner on its line,
+    // and the partner lands on the tile it passed. nothing may be in between,
+    // and the royal piece can't be in danger on its way.
+    fn castling_moves(&self, set: &PieceSet, moves: &mut Vec<Move>) -> Result<()> {
+        let team = set.team();
+        let start_info = team.start_info();
+        let unmoved = |piece: &Piece, royal: bool| {
+            let lock = piece.read().unwrap();
+            let matches = if royal { lock.is_royal() } else { lock.castles() };
+            if !matches || !lock.is_alive() || lock.move_num() != 0 {
+                return None;
             }
-            MoveRules::Pierce { move_info, max_points } => {
-                todo!();
-            },
-            MoveRules::Blunt { move_info } => {
-                'process_move_vecs: for move_vec in move_info {
-                    let start_info = team.start_info();
-                    let abs_move_vec = move_vec.rel_to_absolute(start_info);
-                    let rel_pos = piece_rlock.rel_pos_unchecked();
-                    
-                    let abs_pos = board.rel_coord_to_absolute(rel_pos, start_info);
-
-                    let offset_vec = abs_move_vec.direction().as_vec();
-                    
-                    match abs_move_vec.distance() {
-                        Distance::Finite { distance } => {
-                            for n in 1..=distance {
-                                let target_coord = abs_pos.translate(&(offset_vec.mul(n as i32)));
-                                // if this triggers, we are out of bounds on the negative direction.
-                                if target_coord.is_err() {
-                                    continue 'process_move_vecs;
-                                }
-
-                                let target_coord = target_coord.unwrap();
-
-                                // if tile is none, we are out of bounds on the positive direction.
-                                if let Some(t) = board.tile_at(target_coord.x(), target_coord.y()) {
-                                    // if there was a piece here stop and go to next direction or something (only if u cant use move for kills)
-                                    let tile_rlock = t.read().unwrap();
-                                    if tile_rlock.occupied() {
-
-                                        // dont kill your teammates idiot 
-                                        if tile_rlock.team_on_tile_unchecked().name() == team.name() {
-                                            continue;
-                                        }
-                                        // if it can use move for kills, add it once and just skip the rest
-                                        if piece_rlock.can_use_moves_for_kills() {
-                                            moves.push(
-                                                Move::new(piece.clone(), abs_pos, target_coord, Coord::new(0,0), 
-                                                board.tile_at(abs_pos.x(), abs_pos.y()).unwrap(), 
-                                                t.clone())
-                                            );
-                                        }
-                                        continue 'process_move_vecs;
-                                    }
-                                    // TODO! IMPLEMENT REL_TRANSLATION LATER IMPORTANT TODO!
-                                    moves.push(
-                                        Move::new(piece.clone(), abs_pos, target_coord, Coord::new(0,0), 
-                                        board.tile_at(abs_pos.x(), abs_pos.y()).unwrap(), 
-                                        t.clone())
-                                    );
-                                } else {
-                                    continue 'process_move_vecs;
-                                }
-                            }
-                        },
-                        Distance::Infinite => {
-                            let mut idx = 1_u32;
-                            let mut target_coord = abs_pos.translate(&offset_vec.mul(idx as i32));
-
-                            while target_coord.is_ok() {
-
-                                let target_coord_inner = target_coord.as_ref().unwrap();
-
-                                // if tile is none, we are out of bounds on the positive direction.
-                                if let Some(t) = board.tile_at(target_coord_inner.x(), target_coord_inner.y()) {
-                                    // if there was a piece here stop and go to next direction or something (only if u cant use move for kills)
-                                    let tile_rlock = t.read().unwrap();
-                                    if tile_rlock.occupied() {
-
-                                        // dont kill your teammates idiot 
-                                        if tile_rlock.team_on_tile_unchecked().name() == team.name() {
-                                            continue;
-                                        }
-                                        // if it can use move for kills, add it once and just skip the rest
-                                        if piece_rlock.can_use_moves_for_kills() {
-                                            moves.push(
-                                                Move::new(piece.clone(), abs_pos, target_coord_inner.clone(), Coord::new(0,0), 
-                                                board.tile_at(abs_pos.x(), abs_pos.y()).unwrap(), 
-                                                t.clone())
-                                            );
-                                        }
-                                        continue 'process_move_vecs;
-                                    }
-                                    // TODO! IMPLEMENT REL_TRANSLATION LATER IMPORTANT TODO!
-                                    moves.push(
-                                        Move::new(piece.clone(), abs_pos, target_coord_inner.clone(), Coord::new(0,0), 
-                                        board.tile_at(abs_pos.x(), abs_pos.y()).unwrap(), 
-                                        t.clone())
-                                    );
-                                } else {
-                                    continue 'process_move_vecs;
-                                }
-
-                                idx += 1;
-                                target_coord = abs_pos.translate(&offset_vec.mul(idx as i32));
-                            }
-                        },
-                    }
+            Some(self.board.rel_coord_to_absolute(lock.rel_pos()?, start_info))
+        };
+
+        for royal in set.pieces() {
+            let Some(from) = unmoved(royal, true) else {
+                continue;
+            };
+            let mut checked = None;
+
+            for partner in set.pieces() {
+                let Some(partner_from) = unmoved(partner, false) else {
+                    continue;
+                };
+
+                // castling happens along the team's own row.
+                let delta = Vec2::new(
+                    partner_from.x() as i32 - from.x() as i32,
+                    partner_from.y() as i32 - from.y() as i32,
+                );
+                let rel_delta = delta.absolute_to_rel(start_info);
+                if rel_delta.y() != 0 || rel_delta.x().abs() < 3 {
+                    continue;
+                }
+                let step = Vec2::new(delta.x().signum(), delta.y().signum());
+
+                let between_empty = (1..rel_delta.x().abs()).all(|n| {
+                    let coord = from.translate(&step.mul(n)).unwrap();
+                    self.board.tile_at(coord.x(), coord.y()).unwrap().read().unwrap().vacant()
+                });
+                if !between_empty {
+                    continue;
+                }
+
+                if *checked.get_or_insert_with(|| self.is_in_check(team.name())) {
+                    break;
+                }
+
+                let passed = from.translate(&step)?;
+                let landing = from.translate(&step.mul(2))?;
+                let tile = |coord: Coord| self.board.tile_at(coord.x(), coord.y()).unwrap();
+
+                let through = Move::new(royal.clone(), from, passed, step.absolute_to_rel(start_info), tile(from), tile(passed));
+                if !self.keeps_royals_safe(&through, team.name())? {
+                    continue;
                 }
-            },
-            MoveRules::Radius { tiles } => {
-                
-            },
-            MoveRules::KnightJump { radius, offset } => {
-                
-            },
+
+                let partner_move = Move::new(
+                    partner.clone(),
+                    partner_from,
+                    passed,
+                    Vec2::new(passed.x() as i32 - partner_from.x() as i32, passed.y() as i32 - partner_from.y() as i32)
+                        .absolute_to_rel(start_info),
+                    tile(partner_from),
+                    tile(passed),
+                );
+                moves.push(
+                    Move::new(royal.clone(), from, landing, step.mul(2).absolute_to_rel(start_info), tile(from), tile(landing))
+                        .with_castle(partner_move),
+                );
+            }
         }
+
+        Ok(())
+    }
+
+    pub(crate) fn piece_set(&self, team_name: &str) -> Result<&PieceSet> {
+        self.board
+            .piece_sets()
+            .iter()
+            .find(|set| set.team().name() == team_name)
+            .ok_or(ChessError::UnknownTeam { name: team_name.to_string() })
     }
 
-    pub fn execute_move(&mut self, move_to_execute: &Move) {
-        
+    /// plays the legal move with the key, for moves coming from players.
+    /// execute_move trusts the move instead.
+    pub fn play(&mut self, key: MoveKey) -> Result<()> {
+        let piece = self.board.tile(key.from)?.read()?.piece().ok_or(ChessError::TileEmpty { coord: key.from })?;
+        let team = piece.read()?.team_unchecked();
+        let current = self.current_team();
+        if team.name() != current.name() {
+            return Err(ChessError::NotYourTurn {
+                team: team.name().to_string(),
+                current: current.name().to_string(),
+            });
+        }
+
+        let m = self
+            .legal_moves()?
+            .into_iter()
+            .find(|m| m.key() == key);
+        let Some(m) = m else {
+            // from and to can be fine while the promotion isn't.
+            let reason = self.explain_illegal(key.from, key.to)?.unwrap_or(IllegalReason::InvalidPromotion);
+            return Err(ChessError::IllegalMove { mv: key, reason });
+        };
+
+        self.execute_move(&m)
     }
+
+    pub fn execute_move(&mut self, move_to_execute: &Move) -> Result<()> {
+        let lock = move_to_execute.piece();
+        let lock = lock.read().unwrap();
+        // kills and moves of promotable pieces can't be undone, like pawn moves in chess.
+        let resets_clock = move_to_execute.is_kill() || !lock.promotions().is_empty();
+        let en_passant = if lock.can_en_passant() && !move_to_execute.is_kill() && move_to_execute.castle().is_none() {
+            EnPassant::after(move_to_execute, lock.team_unchecked().name())
+        } else {
+            None
+        };
+        drop(lock);
+
+        let mut log = move_to_execute.clone().execute()?;
+        log.prev_en_passant = std::mem::replace(&mut self.en_passant, en_passant);
+        log.prev_halfmove_clock = self.halfmove_clock;
+        self.halfmove_clock = if resets_clock { 0 } else { self.halfmove_clock + 1 };
+
+        self.history.push(log);
+        self.current_move += 1;
+        self.hashes.push(self.compute_hash());
+
+        Ok(())
+    }
+
+    /// the team to move skips its turn, ex. in a search or when it's out of the game.
+    pub fn pass_turn(&mut self) {
+        self.passes.push(Pass {
+            history_len: self.history.len(),
+            prev_en_passant: self.en_passant.take(),
+            prev_halfmove_clock: self.halfmove_clock,
+        });
+        self.halfmove_clock += 1;
+        self.current_move += 1;
+        self.hashes.push(self.compute_hash());
+    }
+
+    /// reverses the last executed move or passed turn.
+    pub fn undo(&mut self) -> Result<()> {
+        if self.passes.last().is_some_and(|pass| pass.history_len == self.history.len()) {
+            let pass = self.passes.pop().unwrap();
+            self.en_passant = pass.prev_en_passant;
+            self.halfmove_clock = pass.prev_halfmove_clock;
+            self.current_move -= 1;
+            self.pop_hash();
+            return Ok(());
+        }
+
+        let mut log = self.history.pop().ok_or(ChessError::MoveCalculationError {
+            why: "There are no moves to undo.".to_string(),
+        })?;
+        self.en_passant = log.prev_en_passant.take();
+        self.halfmove_clock = log.prev_halfmove_clock;
+        log.reverse()?;
+        self.current_move -= 1;
+        self.pop_hash();
+
+        Ok(())
+    }
+
+    // games loaded from a snapshot only know the hashes from there on, positions further back
+    // are hashed again as they're undone into.
+    fn pop_hash(&mut self) {
+        self.hashes.pop();
+        if self.hashes.is_empty() {
+            self.hashes.push(self.compute_hash());
+        }
+    }
+
+    /// counts the leaf nodes of the legal move tree, used to check move generation.
+    pub fn perft(&mut self, depth: u32) -> Result<u64> {
+        if depth == 0 {
+            return Ok(1);
+        }
+
+        let moves = self.legal_moves()?;
+        if depth == 1 {
+            return Ok(moves.len() as u64);
+        }
+
+        let mut nodes = 0;
+        for m in &moves {
+            self.execute_move(m)?;
+            nodes += self.perft(depth - 1)?;
+            self.undo()?;
+        }
+
+        Ok(nodes)
+    }
+}
+
+/// tiles a piece just passed over, where it can still be killed en passant.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnPassant {
+    squares: Vec<Coord>,
+    victim: Coord,
+    team: String,
 }
 
+impl EnPassant {
+    pub fn new(squares: Vec<Coord>, victim: Coord, team: String) -> Self {
+        Self { squares, victim, team }
+    }
+
+    // a straight move over more than one tile.
+    fn after(m: &Move, team: &str) -> Option<Self> {
+        let delta = Vec2::new(m.to().x() as i32 - m.from().x() as i32, m.to().y() as i32 - m.from().y() as i32);
+        let steps = delta.x().abs().max(delta.y().abs());
+        let straight = delta.x() == 0 || delta.y() == 0 || delta.x().abs() == delta.y().abs();
+        if !straight || steps < 2 {
+            return None;
+        }
+
+        let step = Vec2::new(delta.x().signum(), delta.y().signum());
+        let squares = (1..steps).map(|n| m.from().translate(&step.mul(n)).unwrap()).collect();
+        Some(Self::new(squares, m.to(), team.to_string()))
+    }
+
+    pub fn squares(&self) -> &Vec<Coord> {
+        &self.squares
+    }
+
+    /// where the piece that can be killed stands.
+    pub fn victim(&self) -> Coord {
+        self.victim
+    }
+
+    /// the team that made the move.
+    pub fn team(&self) -> &str {
+        &self.team
+    }
+}
@@ -1,11 +1,18 @@
+pub mod betza;
 pub mod board;
 pub mod error;
+pub mod explain;
+pub mod fen;
 pub mod game;
 pub mod r#move;
+pub mod notation;
+pub mod pgn;
 pub mod piece;
 pub mod piece_rules;
 pub mod piece_set;
+pub mod snapshot;
 pub mod team;
 pub mod tile;
 pub mod vec2;
+pub mod zobrist;
 pub mod move_calculation;
\ No newline at end of file
@@ -1,9 +1,11 @@
 use crate::error::{Result, ChessError};
-use crate::team::StartInfo;
+use crate::game::EnPassant;
+use crate::piece::PieceRef;
 use crate::vec2::Vec2;
 use crate::{piece::Piece, tile::Tile};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coord {
     x: u32,
     y: u32,
@@ -37,6 +39,15 @@ impl Coord {
         }
     }
 
+    // relative positions of offset teams can dip under 0 while still on the board,
+    // board.rel_coord_to_absolute wraps them back.
+    pub fn wrapping_translate(&self, vec: &Vec2) -> Self {
+        Self {
+            x: self.x.wrapping_add_signed(vec.x()),
+            y: self.y.wrapping_add_signed(vec.y()),
+        }
+    }
+
     // TODO! handle overflows later.
     pub fn translate(&self, vec: &Vec2) -> Result<Self> {
         if self.x as i32 + vec.x() < 0 || self.y as i32 + vec.y() < 0 {
@@ -52,19 +63,66 @@ impl Coord {
     }
 }
 
+impl std::fmt::Display for Coord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// identifies a move within a position, cheap to copy and compare.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveKey {
+    pub from: Coord,
+    pub to: Coord,
+    // index into the moved piece's promotions.
+    pub promotion: Option<u8>,
+}
+
+#[derive(Clone)]
 pub struct Move {
     from: Coord,
     to: Coord,
     // to translate the piece's relative position when executing the move.
-    rel_translation: Coord,
+    rel_translation: Vec2,
 
     piece: Piece,
     from_tile: Tile,
     target_tile: Tile,
+
+    // tiles whose pieces get killed. the target tile is killed either way if occupied.
+    kills: Vec<(Coord, Tile)>,
+    // index into the piece's promotions.
+    promotion: Option<u8>,
+    // the castling partner's move, executed along with this one.
+    castle: Option<Box<Move>>,
+}
+
+impl std::fmt::Display for MoveKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} to {}", self.from, self.to)?;
+        if let Some(promotion) = self.promotion {
+            write!(f, " (promotion {})", promotion)?;
+        }
+        Ok(())
+    }
+}
+
+// pieces and tiles are behind locks, so only the coordinates are shown.
+impl std::fmt::Debug for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Move")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("kills", &self.kills())
+            .field("promotion", &self.promotion)
+            .field("castle", &self.castle)
+            .finish()
+    }
 }
 
 impl Move {
-    pub fn new(piece: Piece, from: Coord, to: Coord, rel_translation: Coord, from_tile: Tile, target_tile: Tile) -> Self {
+    pub fn new(piece: Piece, from: Coord, to: Coord, rel_translation: Vec2, from_tile: Tile, target_tile: Tile) -> Self {
         Self {
             from,
             to,
@@ -72,9 +130,27 @@ impl Move {
             piece,
             from_tile,
             target_tile,
+            kills: Vec::new(),
+            promotion: None,
+            castle: None,
         }
     }
 
+    pub fn with_kills(mut self, kills: Vec<(Coord, Tile)>) -> Self {
+        self.kills = kills;
+        self
+    }
+
+    pub fn with_promotion(mut self, promotion: u8) -> Self {
+        self.promotion = Some(promotion);
+        self
+    }
+
+    pub fn with_castle(mut self, partner: Move) -> Self {
+        self.castle = Some(Box::new(partner));
+        self
+    }
+
     // accessors
     pub fn from(&self) -> Coord {
         self.from
@@ -85,7 +161,7 @@ impl Move {
     
     }
 
-    pub fn rel_translation(&self) -> Coord {
+    pub fn rel_translation(&self) -> Vec2 {
         self.rel_translation
     }
 
@@ -101,21 +177,52 @@ impl Move {
         self.target_tile.clone()
     }
 
-    // actual stuff
-    pub fn execute(self) -> Result<MoveLog> {
-        let mut killed_piece = None;
-        let mut target_lock = self.target_tile.write().unwrap();
-        if target_lock.occupied() {
-            killed_piece = target_lock.piece();
+    /// coordinates of every piece the move kills.
+    pub fn kills(&self) -> Vec<Coord> {
+        self.kills.iter().map(|(coord, _)| *coord).collect()
+    }
 
-            let mut killed_lock = killed_piece.as_ref().unwrap().write().unwrap();
-            killed_lock.kill();
-            drop(killed_lock);
+    pub fn is_kill(&self) -> bool {
+        !self.kills.is_empty()
+    }
+
+    pub fn promotion(&self) -> Option<u8> {
+        self.promotion
+    }
+
+    pub fn castle(&self) -> Option<&Move> {
+        self.castle.as_deref()
+    }
 
-            target_lock.remove_piece()?;
+    pub fn key(&self) -> MoveKey {
+        MoveKey {
+            from: self.from,
+            to: self.to,
+            promotion: self.promotion,
         }
+    }
 
-        drop(target_lock);
+    // actual stuff
+    pub fn execute(self) -> Result<MoveLog> {
+        let mut killed = Vec::new();
+        // moves made by hand only name the target tile.
+        let target = (self.to, self.target_tile.clone());
+        for (coord, tile) in self.kills.iter().chain(std::iter::once(&target)) {
+            let mut tile_lock = tile.write().unwrap();
+            let Some(piece) = tile_lock.piece() else {
+                continue;
+            };
+
+            piece.write().unwrap().kill();
+            tile_lock.remove_piece()?;
+            killed.push((piece, *coord, tile.clone()));
+        }
+
+        // the partner moves first, castling only happens over empty tiles.
+        let castle = match self.castle {
+            Some(partner) => Some(Box::new(partner.execute()?)),
+            None => None,
+        };
 
         self.from_tile
             .write()
@@ -123,57 +230,114 @@ impl Move {
             .move_contained_piece(self.target_tile.clone())?;
 
         let mut write_lock = self.piece.write().unwrap();
-        write_lock.translate_rel_pos(self.rel_translation.x(), self.rel_translation.y());
+        write_lock.translate_rel_pos(self.rel_translation)?;
+        write_lock.increment_move();
+        let promoted_from = match self.promotion {
+            Some(idx) => Some(write_lock.promote(idx as usize)?),
+            None => None,
+        };
         drop(write_lock);
 
         Ok(MoveLog {
             from: self.from,
             to: self.to,
+            rel_translation: self.rel_translation,
 
             moved_piece: self.piece,
-            killed_piece: killed_piece,
-            // TODO: IMPLEMENT LATER!
-            promoted_to: None,
+            killed,
+            promoted_from,
+            castle,
 
             moved_from: self.from_tile,
             moved_to: self.target_tile,
+
+            prev_en_passant: None,
+            prev_halfmove_clock: 0,
         })
     }
 }
 
 pub struct MoveLog {
-    from: Coord,
-    to: Coord,
-
-    moved_piece: Piece,
-    killed_piece: Option<Piece>,
-    promoted_to: Option<Piece>,
-
-    moved_from: Tile,
-    moved_to: Tile,
+    pub(crate) from: Coord,
+    pub(crate) to: Coord,
+    pub(crate) rel_translation: Vec2,
+
+    pub(crate) moved_piece: Piece,
+    // in the order they were killed.
+    pub(crate) killed: Vec<(Piece, Coord, Tile)>,
+    // the piece as it was before promoting.
+    pub(crate) promoted_from: Option<PieceRef>,
+    pub(crate) castle: Option<Box<MoveLog>>,
+
+    pub(crate) moved_from: Tile,
+    pub(crate) moved_to: Tile,
+
+    // game state from before the move, filled in by the game.
+    pub(crate) prev_en_passant: Option<EnPassant>,
+    pub(crate) prev_halfmove_clock: u32,
 }
 
 impl MoveLog {
+    pub fn from(&self) -> Coord {
+        self.from
+    }
+
+    pub fn to(&self) -> Coord {
+        self.to
+    }
+
+    pub fn rel_translation(&self) -> Vec2 {
+        self.rel_translation
+    }
+
+    pub fn moved_piece(&self) -> Piece {
+        self.moved_piece.clone()
+    }
+
+    pub fn killed_pieces(&self) -> Vec<Piece> {
+        self.killed.iter().map(|(piece, _, _)| piece.clone()).collect()
+    }
+
+    /// the killed pieces and where they stood.
+    pub fn kills(&self) -> Vec<(Piece, Coord)> {
+        self.killed.iter().map(|(piece, coord, _)| (piece.clone(), *coord)).collect()
+    }
+
+    pub fn promoted_from(&self) -> Option<&PieceRef> {
+        self.promoted_from.as_ref()
+    }
+
+    pub fn castle(&self) -> Option<&MoveLog> {
+        self.castle.as_deref()
+    }
+
     /// undos the logged move.
     /// usually will panick at incorrect board state.
     /// MUST BE EXECUTED IN ORDER.
     pub fn reverse(self) -> Result<()> {
         // undo moved piece's move.
-        let mut lock = self.moved_to.write().unwrap();
-        lock.move_contained_piece(self.moved_from);
-        // if the piece was promoted, replace the new promtoed one with the original oen.
-        if self.promoted_to.is_some() {
-            lock.replace_piece(self.moved_piece);
+        self.moved_to
+            .write()
+            .unwrap()
+            .move_contained_piece(self.moved_from.clone())?;
+
+        let mut moved_lock = self.moved_piece.write().unwrap();
+        // if the piece was promoted, turn it back into the original.
+        if let Some(original) = self.promoted_from {
+            *moved_lock = original;
         }
+        moved_lock.translate_rel_pos(self.rel_translation.mul(-1))?;
+        moved_lock.decrement_move();
+        drop(moved_lock);
 
-        // if there was a piece killed, set killed piece back at its original spot. (also revive it)
-        if self.killed_piece.is_some() {
-            let piece_ref = self.killed_piece.clone().unwrap();
-            let mut kp_lock = piece_ref.write().unwrap();
-            kp_lock.revive();
-            drop(kp_lock);
+        if let Some(castle) = self.castle {
+            castle.reverse()?;
+        }
 
-            lock.set_piece(self.killed_piece.unwrap())?;
+        // set killed pieces back at their original spots. (also revive them)
+        for (piece, _, tile) in self.killed.into_iter().rev() {
+            piece.write().unwrap().revive();
+            tile.write().unwrap().set_piece(piece)?;
         }
 
         Ok(())
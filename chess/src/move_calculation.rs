@@ -1,3 +1,362 @@
-pub fn calc_blunt() {
-    
-}
\ No newline at end of file
+// pseudo legal move generation, every rule a piece has turned into moves.
+// whether a move leaves a royal piece in danger is checked by the game.
+
+use std::collections::HashSet;
+
+use crate::board::Board;
+use crate::game::EnPassant;
+use crate::piece::{Piece, PieceRef};
+use crate::piece_rules::{Distance, MoveRules};
+use crate::r#move::{Coord, Move};
+use crate::team::{StartInfo, Team};
+use crate::tile::Tile;
+use crate::vec2::Vec2;
+
+// which immunity protects a piece from a kill.
+#[derive(Clone, Copy)]
+enum KillKind {
+    Plain,
+    Jump,
+    Pierce,
+}
+
+enum Occupant {
+    Empty,
+    Friend,
+    Enemy {
+        points: u16,
+        jump_immune: bool,
+        pierce_immune: bool,
+    },
+}
+
+impl Occupant {
+    fn immune_to(&self, kind: KillKind) -> bool {
+        match (self, kind) {
+            (Occupant::Enemy { jump_immune, .. }, KillKind::Jump) => *jump_immune,
+            (Occupant::Enemy { pierce_immune, .. }, KillKind::Pierce) => *pierce_immune,
+            _ => false,
+        }
+    }
+}
+
+// the piece currently being processed.
+struct Mover<'a> {
+    piece: &'a Piece,
+    data: &'a PieceRef,
+    team: &'a Team,
+    from: Coord,
+    from_tile: Tile,
+}
+
+// what a rule is allowed to do with the tiles it reaches.
+#[derive(Clone, Copy)]
+struct Usage {
+    can_move: bool,
+    can_kill: bool,
+}
+
+pub(crate) struct MoveGen<'a> {
+    board: &'a Board,
+    en_passant: Option<&'a EnPassant>,
+    // only moves that kill something, used to find attacked pieces.
+    kills_only: bool,
+}
+
+impl<'a> MoveGen<'a> {
+    pub(crate) fn new(board: &'a Board, en_passant: Option<&'a EnPassant>, kills_only: bool) -> Self {
+        Self {
+            board,
+            en_passant,
+            kills_only,
+        }
+    }
+
+    /// pushes every move the piece can make, one per destination (and promotion).
+    pub(crate) fn piece_moves(&self, piece: &Piece, moves: &mut Vec<Move>) {
+        let data = piece.read().unwrap();
+        if !data.is_alive() {
+            return;
+        }
+        let (Some(team), Some(rel_pos)) = (data.team(), data.rel_pos()) else {
+            return;
+        };
+        let from = self.board.rel_coord_to_absolute(rel_pos, team.start_info());
+        let Some(from_tile) = self.board.tile_at(from.x(), from.y()) else {
+            return;
+        };
+
+        let mover = Mover {
+            piece,
+            data: &data,
+            team: &team,
+            from,
+            from_tile,
+        };
+        let start = moves.len();
+
+        let usage = Usage {
+            can_move: true,
+            can_kill: data.can_use_moves_for_kills(),
+        };
+        for rule in data.move_rules() {
+            self.process_rule(&mover, rule, usage, moves);
+        }
+        for nth_move_rule in data.nth_move_rules() {
+            if data.move_num() == nth_move_rule.nth_move() {
+                for rule in nth_move_rule.move_rules() {
+                    self.process_rule(&mover, rule, usage, moves);
+                }
+            }
+        }
+
+        let usage = Usage {
+            can_move: data.can_use_kill_for_moves(),
+            can_kill: true,
+        };
+        for rule in data.kill_rules() {
+            self.process_rule(&mover, rule, usage, moves);
+        }
+
+        // several rules can reach the same tile, the first one that kills there wins, or else the
+        // first one, so a quiet move can't hide en passant.
+        let killing: HashSet<Coord> = moves[start..].iter().filter(|m| m.is_kill()).map(|m| m.to()).collect();
+        let mut seen = HashSet::new();
+        let mut idx = 0;
+        moves.retain(|m| {
+            idx += 1;
+            idx <= start || ((m.is_kill() || !killing.contains(&m.to())) && seen.insert(m.to()))
+        });
+
+        if !data.promotions().is_empty() {
+            self.expand_promotions(&mover, start, moves);
+        }
+    }
+
+    fn process_rule(&self, mover: &Mover, rule: &MoveRules, usage: Usage, moves: &mut Vec<Move>) {
+        match rule {
+            MoveRules::Jump { translation } => {
+                self.leap(mover, *translation, usage, KillKind::Jump, moves);
+            }
+            MoveRules::KnightJump { radius, offset } => {
+                for translation in Vec2::symmetric(*radius, *offset) {
+                    self.leap(mover, translation, usage, KillKind::Jump, moves);
+                }
+            }
+            MoveRules::Radius { tiles } => {
+                let tiles = *tiles as i32;
+                for x in -tiles..=tiles {
+                    for y in -tiles..=tiles {
+                        if x != 0 || y != 0 {
+                            self.leap(mover, Vec2::new(x, y), usage, KillKind::Plain, moves);
+                        }
+                    }
+                }
+            }
+            MoveRules::Blunt { move_info } => {
+                for move_vec in move_info {
+                    self.slide(mover, move_vec.direction().as_vec(), move_vec.distance(), usage, moves);
+                }
+            }
+            MoveRules::Rider { translation, distance } => {
+                self.slide(mover, *translation, *distance, usage, moves);
+            }
+            MoveRules::LineJump { move_info } => {
+                for move_vec in move_info {
+                    self.hop(mover, move_vec.direction().as_vec(), move_vec.distance(), usage, moves);
+                }
+            }
+            MoveRules::Pierce { move_info, max_points } => {
+                for move_vec in move_info {
+                    self.pierce(mover, move_vec.direction().as_vec(), move_vec.distance(), *max_points, usage, moves);
+                }
+            }
+        }
+    }
+
+    // a single translation that ignores whatever is in between.
+    fn leap(&self, mover: &Mover, rel_vec: Vec2, usage: Usage, kind: KillKind, moves: &mut Vec<Move>) {
+        let Some((to, tile)) = self.target(mover, rel_vec, 1) else {
+            return;
+        };
+
+        match self.occupant(mover, &tile) {
+            Occupant::Empty => self.quiet(mover, to, tile, rel_vec, usage, moves),
+            Occupant::Friend => {}
+            occupant => {
+                if usage.can_kill && !occupant.immune_to(kind) {
+                    self.push(mover, to, tile.clone(), rel_vec, vec![(to, tile)], moves);
+                }
+            }
+        }
+    }
+
+    // repeats a translation until something is in the way.
+    fn slide(&self, mover: &Mover, rel_step: Vec2, distance: Distance, usage: Usage, moves: &mut Vec<Move>) {
+        for n in 1..=max_steps(distance) {
+            let Some((to, tile)) = self.target(mover, rel_step, n) else {
+                return;
+            };
+
+            match self.occupant(mover, &tile) {
+                Occupant::Empty => self.quiet(mover, to, tile, rel_step.mul(n as i32), usage, moves),
+                Occupant::Friend => return,
+                _ => {
+                    if usage.can_kill {
+                        self.push(mover, to, tile.clone(), rel_step.mul(n as i32), vec![(to, tile)], moves);
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    // needs exactly one piece (of any team) to jump over, then lands anywhere behind it.
+    fn hop(&self, mover: &Mover, rel_step: Vec2, distance: Distance, usage: Usage, moves: &mut Vec<Move>) {
+        let mut jumped = false;
+
+        for n in 1..=max_steps(distance) {
+            let Some((to, tile)) = self.target(mover, rel_step, n) else {
+                return;
+            };
+            let occupant = self.occupant(mover, &tile);
+
+            if !jumped {
+                jumped = !matches!(occupant, Occupant::Empty);
+                continue;
+            }
+
+            match occupant {
+                Occupant::Empty => {
+                    if usage.can_move {
+                        self.push(mover, to, tile, rel_step.mul(n as i32), Vec::new(), moves);
+                    }
+                }
+                Occupant::Friend => return,
+                occupant => {
+                    if usage.can_kill && !occupant.immune_to(KillKind::Jump) {
+                        self.push(mover, to, tile.clone(), rel_step.mul(n as i32), vec![(to, tile)], moves);
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    // kills every enemy it passes through until the points it pierced go over the limit.
+    fn pierce(&self, mover: &Mover, rel_step: Vec2, distance: Distance, max_points: u16, usage: Usage, moves: &mut Vec<Move>) {
+        let mut kills = Vec::new();
+        let mut points = 0_u32;
+
+        for n in 1..=max_steps(distance) {
+            let Some((to, tile)) = self.target(mover, rel_step, n) else {
+                return;
+            };
+
+            match self.occupant(mover, &tile) {
+                Occupant::Empty => {
+                    if (kills.is_empty() && usage.can_move) || (!kills.is_empty() && usage.can_kill) {
+                        self.push(mover, to, tile, rel_step.mul(n as i32), kills.clone(), moves);
+                    }
+                }
+                Occupant::Friend => return,
+                occupant => {
+                    let Occupant::Enemy { points: enemy_points, .. } = occupant else {
+                        unreachable!();
+                    };
+                    points += enemy_points as u32;
+                    if !usage.can_kill || occupant.immune_to(KillKind::Pierce) || points > max_points as u32 {
+                        return;
+                    }
+                    kills.push((to, tile.clone()));
+                    self.push(mover, to, tile, rel_step.mul(n as i32), kills.clone(), moves);
+                }
+            }
+        }
+    }
+
+    // an empty tile, which kill only rules can still use to kill en passant.
+    fn quiet(&self, mover: &Mover, to: Coord, tile: Tile, rel_translation: Vec2, usage: Usage, moves: &mut Vec<Move>) {
+        if usage.can_move {
+            self.push(mover, to, tile, rel_translation, Vec::new(), moves);
+            return;
+        }
+
+        let Some(en_passant) = self.en_passant else {
+            return;
+        };
+        if usage.can_kill
+            && mover.data.can_en_passant()
+            && en_passant.team() != mover.team.name()
+            && en_passant.squares().contains(&to)
+        {
+            let victim = en_passant.victim();
+            if let Some(victim_tile) = self.board.tile_at(victim.x(), victim.y()) {
+                self.push(mover, to, tile, rel_translation, vec![(victim, victim_tile)], moves);
+            }
+        }
+    }
+
+    fn push(&self, mover: &Mover, to: Coord, tile: Tile, rel_translation: Vec2, kills: Vec<(Coord, Tile)>, moves: &mut Vec<Move>) {
+        if self.kills_only && kills.is_empty() {
+            return;
+        }
+
+        moves.push(
+            Move::new(mover.piece.clone(), mover.from, to, rel_translation, mover.from_tile.clone(), tile)
+                .with_kills(kills),
+        );
+    }
+
+    // the tile n relative steps away, none when it's off the board.
+    fn target(&self, mover: &Mover, rel_step: Vec2, n: u32) -> Option<(Coord, Tile)> {
+        let abs_step = rel_step.rel_to_absolute(mover.team.start_info()).mul(n as i32);
+        let to = mover.from.translate(&abs_step).ok()?;
+        let tile = self.board.tile_at(to.x(), to.y())?;
+        Some((to, tile))
+    }
+
+    fn occupant(&self, mover: &Mover, tile: &Tile) -> Occupant {
+        let tile_lock = tile.read().unwrap();
+        let Some(piece) = tile_lock.piece() else {
+            return Occupant::Empty;
+        };
+        if tile_lock.team_on_tile_unchecked().name() == mover.team.name() {
+            return Occupant::Friend;
+        }
+
+        let piece_lock = piece.read().unwrap();
+        Occupant::Enemy {
+            points: piece_lock.points(),
+            jump_immune: piece_lock.is_jump_immune(),
+            pierce_immune: piece_lock.is_pierce_immune(),
+        }
+    }
+
+    // moves onto the last relative row turn into one move per promotion.
+    fn expand_promotions(&self, mover: &Mover, start: usize, moves: &mut Vec<Move>) {
+        let start_info = mover.team.start_info();
+        let last_row = match start_info {
+            StartInfo::Bottom { .. } | StartInfo::Top { .. } => self.board.height() - 1,
+            StartInfo::Left { .. } | StartInfo::Right { .. } => self.board.width() - 1,
+        };
+
+        let generated = moves.split_off(start);
+        for m in generated {
+            if self.board.absolute_coord_to_rel(m.to(), start_info).y() != last_row {
+                moves.push(m);
+                continue;
+            }
+            for idx in 0..mover.data.promotions().len() {
+                moves.push(m.clone().with_promotion(idx as u8));
+            }
+        }
+    }
+}
+
+fn max_steps(distance: Distance) -> u32 {
+    match distance {
+        Distance::Finite { distance } => distance,
+        Distance::Infinite => u32::MAX,
+    }
+}
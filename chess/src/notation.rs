@@ -0,0 +1,160 @@
+// square names and move notation (uci style coordinates and standard algebraic notation).
+// files are letters starting from a, ranks are numbers starting from 1, so boards wider than
+// 26 files can't be named. ranks past 9 just take more digits (ex. a10).
+
+use crate::error::{ChessError, Result};
+use crate::game::Game;
+use crate::r#move::{Coord, Move};
+
+pub fn square_name(coord: Coord) -> String {
+    format!("{}{}", (b'a' + coord.x() as u8) as char, coord.y() + 1)
+}
+
+pub fn parse_square(square: &str) -> Result<Coord> {
+    let mut chars = square.chars();
+    let file = chars.next().filter(|c| c.is_ascii_lowercase());
+    let rank = chars.as_str().parse::<u32>().ok().filter(|rank| *rank > 0);
+
+    match (file, rank) {
+        (Some(file), Some(rank)) => Ok(Coord::new(file as u32 - 'a' as u32, rank - 1)),
+        _ => Err(notation_error(format!("{} is not a square.", square))),
+    }
+}
+
+fn notation_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+impl Game {
+    /// coordinate notation, ex. e2e4 or e7e8q. castling is written as the royal piece's move.
+    pub fn move_to_uci(&self, m: &Move) -> String {
+        let mut uci = format!("{}{}", square_name(m.from()), square_name(m.to()));
+        if let Some(symbol) = promotion_symbol(m) {
+            uci.push(symbol.to_ascii_lowercase());
+        }
+        uci
+    }
+
+    pub fn parse_uci(&self, uci: &str) -> Result<Move> {
+        self.legal_moves()?
+            .into_iter()
+            .find(|m| self.move_to_uci(m) == uci)
+            .ok_or(notation_error(format!("{} is not a legal move.", uci)))
+    }
+
+    /// standard algebraic notation, ex. Nf3, exd5, e8=Q+ or O-O.
+    pub fn move_to_san(&self, m: &Move) -> Result<String> {
+        let team = m.piece().read().unwrap().team_unchecked();
+        let legal = self.calculate_moves_for(team.name().to_string())?;
+        let mut san = san_base(m, &legal);
+
+        // play the move to see what it does to everyone else.
+        let log = m.clone().execute()?;
+        let mut check = false;
+        let mut mate = false;
+        for set in self.board().piece_sets() {
+            let name = set.team().name().to_string();
+            if name == team.name() || !self.is_in_check(&name) {
+                continue;
+            }
+            check = true;
+            mate = mate || self.calculate_moves_for(name)?.is_empty();
+        }
+        log.reverse()?;
+
+        if mate {
+            san.push('#');
+        } else if check {
+            san.push('+');
+        }
+        Ok(san)
+    }
+
+    /// accepts check marks, annotations and promotions without '='.
+    pub fn parse_san(&self, san: &str) -> Result<Move> {
+        let wanted = normalize_san(san);
+        let legal = self.legal_moves()?;
+
+        legal
+            .iter()
+            .find(|m| normalize_san(&san_base(m, &legal)) == wanted)
+            .cloned()
+            .ok_or(notation_error(format!("{} is not a legal move.", san)))
+    }
+}
+
+// san without the check marks.
+fn san_base(m: &Move, legal: &[Move]) -> String {
+    if let Some(partner) = m.castle() {
+        return if partner.from().x() > m.from().x() { "O-O" } else { "O-O-O" }.to_string();
+    }
+
+    let symbol = m.piece().read().unwrap().symbol();
+    let mut san = String::new();
+
+    if symbol == 'P' {
+        if m.is_kill() {
+            san.push_str(&square_name(m.from())[..1]);
+        }
+    } else {
+        san.push(symbol);
+
+        // other pieces of the same kind that can go to the same tile.
+        let rivals: Vec<Coord> = legal
+            .iter()
+            .filter(|other| {
+                other.to() == m.to()
+                    && other.from() != m.from()
+                    && other.castle().is_none()
+                    && other.piece().read().unwrap().symbol() == symbol
+            })
+            .map(|other| other.from())
+            .collect();
+
+        if !rivals.is_empty() {
+            let from = square_name(m.from());
+            let (file, rank) = from.split_at(1);
+            if rivals.iter().all(|r| r.x() != m.from().x()) {
+                san.push_str(file);
+            } else if rivals.iter().all(|r| r.y() != m.from().y()) {
+                san.push_str(rank);
+            } else {
+                san.push_str(&from);
+            }
+        }
+    }
+
+    if m.is_kill() {
+        san.push('x');
+    }
+    san.push_str(&square_name(m.to()));
+
+    if let Some(symbol) = promotion_symbol(m) {
+        san.push('=');
+        san.push(symbol);
+    }
+
+    san
+}
+
+fn promotion_symbol(m: &Move) -> Option<char> {
+    let idx = m.promotion()? as usize;
+    let piece = m.piece();
+    let lock = piece.read().unwrap();
+    lock.promotions().get(idx).map(|p| p.symbol())
+}
+
+fn normalize_san(san: &str) -> String {
+    let san = san
+        .trim()
+        .trim_end_matches(['+', '#', '!', '?'])
+        .trim_end_matches("e.p.")
+        .trim()
+        .replace('=', "");
+
+    if san.starts_with("0-0") {
+        san.replace('0', "O")
+    } else {
+        san
+    }
+}
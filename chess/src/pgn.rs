@@ -0,0 +1,190 @@
+// portable game notation: tag pairs, then the moves in san.
+// comments, variations, annotation glyphs and move numbers are skipped, so only the main line is
+// kept. games that start somewhere else have their position in a FEN tag.
+// written pgn has the tags first, then the moves with their numbers wrapped at 80 columns.
+
+use crate::error::{ChessError, Result};
+use crate::fen::{standard_catalog, STARTING_FEN};
+use crate::game::Game;
+use crate::piece::Piece;
+
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+const LINE_WIDTH: usize = 80;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    // in san, as they were written.
+    pub moves: Vec<String>,
+    // 1-0, 0-1, 1/2-1/2 or * when it's unknown.
+    pub result: String,
+}
+
+fn pgn_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    /// the position before the first move, with the standard pieces.
+    pub fn start(&self) -> Result<Game> {
+        self.start_with(&standard_catalog())
+    }
+
+    pub fn start_with(&self, catalog: &[Piece]) -> Result<Game> {
+        Game::from_fen_with(self.tag("FEN").unwrap_or(STARTING_FEN), catalog)
+    }
+
+    /// the position after every move.
+    pub fn replay_with(&self, catalog: &[Piece]) -> Result<Game> {
+        let mut game = self.start_with(catalog)?;
+        for san in &self.moves {
+            let m = game.parse_san(san)?;
+            game.execute_move(&m)?;
+        }
+        Ok(game)
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for (name, value) in &self.tags {
+            pgn += &format!("[{} \"{}\"]\n", name, value.replace('"', "\\\""));
+        }
+        pgn.push('\n');
+
+        // the fen says who moves first and from which move number.
+        let fen_fields: Vec<&str> = self.tag("FEN").unwrap_or(STARTING_FEN).split_whitespace().collect();
+        let black_first = fen_fields.get(1) == Some(&"b");
+        let first_number = fen_fields.get(5).and_then(|n| n.parse::<usize>().ok()).unwrap_or(1);
+
+        let mut tokens = Vec::new();
+        for (idx, san) in self.moves.iter().enumerate() {
+            let ply = idx + black_first as usize;
+            let number = first_number + ply / 2;
+            if ply.is_multiple_of(2) {
+                tokens.push(format!("{}.", number));
+            } else if idx == 0 {
+                tokens.push(format!("{}...", number));
+            }
+            tokens.push(san.clone());
+        }
+        tokens.push(if self.result.is_empty() { "*".to_string() } else { self.result.clone() });
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                pgn += &line;
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += &token;
+        }
+        pgn += &line;
+        pgn.push('\n');
+        pgn
+    }
+}
+
+/// the result of a finished two team game and why it ended, ex. ("1-0", "White mates").
+/// none while it goes on.
+pub fn result_of(game: &Game) -> Option<(&'static str, &'static str)> {
+    let no_moves = game.legal_moves().map_or(true, |moves| moves.is_empty());
+    if no_moves {
+        let team = game.current_team();
+        return Some(if !game.is_in_check(team.name()) {
+            ("1/2-1/2", "Stalemate")
+        } else if game.current_move().is_multiple_of(2) {
+            ("0-1", "Black mates")
+        } else {
+            ("1-0", "White mates")
+        });
+    }
+    if game.halfmove_clock() >= 100 {
+        return Some(("1/2-1/2", "50 move rule"));
+    }
+    if game.repetitions() >= 2 {
+        return Some(("1/2-1/2", "3-fold repetition"));
+    }
+    None
+}
+
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>> {
+    let mut games = Vec::new();
+    let mut game = PgnGame::default();
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => {
+                // a game that had no result.
+                if !game.moves.is_empty() {
+                    game.result = "*".to_string();
+                    games.push(std::mem::take(&mut game));
+                }
+                let tag: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                let (name, value) = tag
+                    .trim()
+                    .split_once(char::is_whitespace)
+                    .ok_or(pgn_error(format!("Expected a name and a value in [{}].", tag)))?;
+                let value = value.trim().trim_matches('"').replace("\\\"", "\"");
+                game.tags.push((name.to_string(), value));
+            }
+            '{' => {
+                chars.by_ref().find(|c| *c == '}');
+            }
+            // a rest of line comment.
+            ';' => {
+                chars.by_ref().find(|c| *c == '\n');
+            }
+            '(' => {
+                let mut depth = 1;
+                while depth > 0 {
+                    match chars.next() {
+                        Some('(') => depth += 1,
+                        Some(')') => depth -= 1,
+                        Some('{') => {
+                            chars.by_ref().find(|c| *c == '}');
+                        }
+                        Some(_) => {}
+                        None => return Err(pgn_error("A variation was never closed.".to_string())),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"{}();[".contains(*c)) {
+                    token.push(c);
+                }
+                if RESULTS.contains(&token.as_str()) {
+                    game.result = token;
+                    games.push(std::mem::take(&mut game));
+                    continue;
+                }
+
+                // move numbers can be stuck to the move, ex. 1.e4 or 12...Nf6.
+                let number = token.len() - token.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                let san = match token[number..].strip_prefix('.') {
+                    Some(rest) => rest.trim_start_matches('.'),
+                    None => &token,
+                };
+                if !san.is_empty() && !san.starts_with('$') {
+                    game.moves.push(san.to_string());
+                }
+            }
+        }
+    }
+
+    // a last game without a result.
+    if !game.tags.is_empty() || !game.moves.is_empty() {
+        game.result = game.tag("Result").unwrap_or("*").to_string();
+        games.push(game);
+    }
+    Ok(games)
+}
@@ -2,19 +2,26 @@ use std::sync::{Arc, RwLock};
 
 use crate::error::Result;
 use crate::{
+    betza,
     error::ChessError,
     piece_rules::{MoveRules, NthMoveRules},
     r#move::Coord,
     team::Team,
+    vec2::Vec2,
 };
 
 /// custom type to reduce boilerplate
 /// piece uses arc internally
 pub type Piece = Arc<RwLock<PieceRef>>;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PieceRef {
     name: String,
+    // letter used by fen and san, defaults to the first letter of the name.
+    symbol: Option<char>,
+    // restored by whoever owns the piece (see snapshot).
+    #[cfg_attr(feature = "serde", serde(skip))]
     team: Option<Arc<Team>>,
     points: u16,
     move_rules: Vec<MoveRules>,
@@ -26,6 +33,16 @@ pub struct PieceRef {
     jump_immune: bool,
     pierce_immune: bool,
 
+    // the team loses when all of its royal pieces can be taken.
+    royal: bool,
+    // an unmoved royal piece can castle with an unmoved castling partner on its line.
+    castles: bool,
+    // moving more than one tile in a straight line can be punished en passant,
+    // and the piece can itself kill en passant.
+    en_passant: bool,
+    // what the piece may turn into on the last relative row.
+    promotions: Vec<PieceRef>,
+
     // allows kill move rules to be used for moving
     use_kill_for_moves: bool,
     // allows default move rules (AND nth move rules) to be used for killing
@@ -45,6 +62,12 @@ impl PieceRef {
         &self.name
     }
 
+    pub fn symbol(&self) -> char {
+        self.symbol
+            .unwrap_or_else(|| self.name.chars().next().unwrap_or('?'))
+            .to_ascii_uppercase()
+    }
+
     pub fn team(&self) -> Option<Arc<Team>> {
         self.team.clone()
     }
@@ -81,6 +104,22 @@ impl PieceRef {
         self.pierce_immune
     }
 
+    pub fn is_royal(&self) -> bool {
+        self.royal
+    }
+
+    pub fn castles(&self) -> bool {
+        self.castles
+    }
+
+    pub fn can_en_passant(&self) -> bool {
+        self.en_passant
+    }
+
+    pub fn promotions(&self) -> &Vec<PieceRef> {
+        &self.promotions
+    }
+
     pub fn can_use_kill_for_moves(&self) -> bool {
         self.use_kill_for_moves
     }
@@ -117,25 +156,60 @@ impl PieceRef {
         self.rel_pos.unwrap()
     }
 
-    pub fn translate_rel_pos(&mut self, x: u32, y: u32) {
-        self.rel_pos.unwrap().set_x(self.rel_pos.unwrap().x() + x);
-        self.rel_pos.unwrap().set_y(self.rel_pos.unwrap().y() + y);
+    pub fn translate_rel_pos(&mut self, translation: Vec2) -> Result<()> {
+        if let Some(pos) = self.rel_pos {
+            self.rel_pos = Some(pos.wrapping_translate(&translation));
+        }
+        Ok(())
     }
 
     pub fn increment_move(&mut self) {
         self.move_number += 1;
     }
 
+    pub fn decrement_move(&mut self) {
+        self.move_number -= 1;
+    }
+
     // current move number
     pub fn move_num(&self) -> u32 {
         self.move_number
     }
+
+    pub fn set_move_num(&mut self, move_number: u32) {
+        self.move_number = move_number;
+    }
+
+    /// pieces are copied like in PieceBuilder::promotions.
+    pub fn set_promotions(&mut self, promotions: Vec<Piece>) {
+        self.promotions = promotions.iter().map(|p| p.read().unwrap().clone()).collect();
+    }
+
+    /// turns the piece into its promotion at idx, keeping its team, position and move number.
+    /// returns the piece as it was before, so the promotion can be undone.
+    pub fn promote(&mut self, idx: usize) -> Result<PieceRef> {
+        let mut promoted = self.promotions.get(idx).cloned().ok_or(ChessError::MoveCalculationError {
+            why: format!("{} has no promotion {}.", self.name, idx),
+        })?;
+        promoted.team = self.team.clone();
+        promoted.rel_pos = self.rel_pos;
+        promoted.move_number = self.move_number;
+        promoted.alive = self.alive;
+
+        Ok(std::mem::replace(self, promoted))
+    }
+
+    /// the piece's rules in betza notation.
+    pub fn betza(&self) -> String {
+        betza::describe(self)
+    }
 }
 
 
 #[derive(Default)]
 pub struct PieceBuilder {
     name: String,
+    symbol: Option<char>,
     team: Option<Arc<Team>>,
     points: u16,
     move_rules: Vec<MoveRules>,
@@ -146,6 +220,11 @@ pub struct PieceBuilder {
     jump_immune: bool,
     pierce_immune: bool,
 
+    royal: bool,
+    castles: bool,
+    en_passant: bool,
+    promotions: Vec<PieceRef>,
+
     // allows kill move rules to be used for moving
     use_kill_for_moves: bool,
     // allows default move rules (AND nth move rules) to be used for killing
@@ -162,6 +241,11 @@ impl PieceBuilder {
         self
     }
 
+    pub fn symbol(mut self, symbol: char) -> Self {
+        self.symbol = Some(symbol);
+        self
+    }
+
     pub fn points(mut self, points: u16) -> Self {
         self.points = points;
         self
@@ -192,6 +276,27 @@ impl PieceBuilder {
         self
     }
 
+    pub fn royal(mut self, royal: bool) -> Self {
+        self.royal = royal;
+        self
+    }
+
+    pub fn castles(mut self, castles: bool) -> Self {
+        self.castles = castles;
+        self
+    }
+
+    pub fn en_passant(mut self, en_passant: bool) -> Self {
+        self.en_passant = en_passant;
+        self
+    }
+
+    /// pieces are copied, the promoted piece keeps nothing but its rules.
+    pub fn promotions(mut self, promotions: Vec<Piece>) -> Self {
+        self.promotions = promotions.iter().map(|p| p.read().unwrap().clone()).collect();
+        self
+    }
+
     pub fn use_kill_for_moves(mut self, use_kill_for_moves: bool) -> Self {
         self.use_kill_for_moves = use_kill_for_moves;
         self
@@ -207,12 +312,36 @@ impl PieceBuilder {
         self
     }
 
-    pub fn build(mut self) -> Result<Piece> {
+    /// replaces the move, kill and nth move rules with ones compiled from betza notation (ex. "WfcF").
+    pub fn betza(mut self, notation: &str) -> Result<Self> {
+        let rules = betza::parse(notation)?;
+        self.move_rules = rules.move_rules().clone();
+        self.kill_rules = rules.kill_rules().clone();
+        self.nth_move_rules = rules.nth_move_rules().clone();
+        self.use_moves_for_kills = rules.use_moves_for_kills();
+        self.use_kill_for_moves = rules.use_kill_for_moves();
+        Ok(self)
+    }
+
+    pub fn build(self) -> Result<Piece> {
         // if self.team.is_none() {
         //     return Err(ChessError::PieceCreationError { why: "Piece needs a reference to a team.".to_string() });
         // }
-        Ok(Arc::new(RwLock::new(PieceRef {
+        // symbols are written in fen, where digits count empty tiles.
+        if let Some(symbol) = self.symbol.filter(|c| !c.is_ascii_alphabetic()) {
+            return Err(ChessError::PieceCreationError {
+                why: format!("{} is not a letter, it can't be used as a symbol.", symbol),
+            });
+        }
+
+        Ok(self.assemble())
+    }
+
+    // for pieces that are known to be valid.
+    fn assemble(self) -> Piece {
+        Arc::new(RwLock::new(PieceRef {
             name: self.name,
+            symbol: self.symbol,
             team: self.team,
             points: self.points,
             move_rules: self.move_rules,
@@ -221,11 +350,15 @@ impl PieceBuilder {
             alive: true,
             jump_immune: self.jump_immune,
             pierce_immune: self.pierce_immune,
+            royal: self.royal,
+            castles: self.castles,
+            en_passant: self.en_passant,
+            promotions: self.promotions,
             use_kill_for_moves: self.use_kill_for_moves,
             use_moves_for_kills: self.use_moves_for_kills,
             rel_pos: None,
             move_number: 0
-        })))
+        }))
     }
 
     /// creates a new copy of the underlying data.
@@ -268,11 +401,12 @@ pub mod defaults {
         PieceBuilder::new()
             .name("Pawn".to_string())
             .points(1)
+            .en_passant(true)
+            .promotions(vec![queen(), rook(), bishop(), knight()])
             .move_rules(move_rules)
             .kill_rules(kill_rules)
             .nth_move_rules(nth_move_rules)
-            .build()
-            .unwrap()
+            .assemble()
     }
 
     pub fn rook() -> Piece {
@@ -289,13 +423,13 @@ pub mod defaults {
 
         PieceBuilder::new()
             .name("Rook".to_string())
-            .points(1)
+            .points(5)
+            .castles(true)
             .move_rules(move_rules)
             .kill_rules(kill_rules)
             .nth_move_rules(nth_move_rules)
             .use_moves_for_kills(true)
-            .build()
-            .unwrap()
+            .assemble()
     }
 
     pub fn bishop() -> Piece {
@@ -312,13 +446,12 @@ pub mod defaults {
 
         PieceBuilder::new()
             .name("Bishop".to_string())
-            .points(1)
+            .points(3)
             .move_rules(move_rules)
             .kill_rules(kill_rules)
             .nth_move_rules(nth_move_rules)
             .use_moves_for_kills(true)
-            .build()
-            .unwrap()
+            .assemble()
     }
 
     pub fn knight() -> Piece {
@@ -330,13 +463,13 @@ pub mod defaults {
 
         PieceBuilder::new()
             .name("Knight".to_string())
-            .points(1)
+            .symbol('N')
+            .points(3)
             .move_rules(move_rules)
             .kill_rules(kill_rules)
             .nth_move_rules(nth_move_rules)
             .use_moves_for_kills(true)
-            .build()
-            .unwrap()
+            .assemble()
     }
 
     pub fn queen() -> Piece {
@@ -357,13 +490,12 @@ pub mod defaults {
 
         PieceBuilder::new()
             .name("Queen".to_string())
-            .points(1)
+            .points(9)
             .move_rules(move_rules)
             .kill_rules(kill_rules)
             .nth_move_rules(nth_move_rules)
             .use_moves_for_kills(true)
-            .build()
-            .unwrap()
+            .assemble()
     }
 
     pub fn king() -> Piece {
@@ -375,12 +507,13 @@ pub mod defaults {
 
         PieceBuilder::new()
             .name("King".to_string())
-            .points(1)
+            // royal pieces are never traded, so they aren't worth material.
+            .points(0)
+            .royal(true)
             .move_rules(move_rules)
             .kill_rules(kill_rules)
             .nth_move_rules(nth_move_rules)
             .use_moves_for_kills(true)
-            .build()
-            .unwrap()
+            .assemble()
     }
 }
\ No newline at end of file
@@ -1,7 +1,8 @@
-use crate::{team::StartInfo, r#move::Coord, vec2::Vec2};
+use crate::{team::StartInfo, vec2::Vec2};
 
 // will be handled differently depending on team
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     Left,
     Right,
@@ -68,9 +69,25 @@ impl Direction {
             Direction::RightDown => Vec2::new(1, -1),
         }
     }
+
+    // inverse of as_vec, only unit vectors map to a direction.
+    pub fn from_vec(vec: Vec2) -> Option<Self> {
+        match (vec.x(), vec.y()) {
+            (-1, 0) => Some(Direction::Left),
+            (1, 0) => Some(Direction::Right),
+            (0, 1) => Some(Direction::Up),
+            (0, -1) => Some(Direction::Down),
+            (-1, 1) => Some(Direction::LeftUp),
+            (-1, -1) => Some(Direction::LeftDown),
+            (1, 1) => Some(Direction::RightUp),
+            (1, -1) => Some(Direction::RightDown),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Distance {
     Finite { distance: u32 },
     Infinite,
@@ -86,7 +103,8 @@ impl Distance {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MoveVec {
     distance: Distance,
     direction: Direction,
@@ -116,14 +134,17 @@ impl MoveVec {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MoveRules {
     // a singular jump rule.
+    // translation is relative to the team, like MoveVec directions.
     Jump {
-        translation: Coord
+        translation: Vec2
     },
 
     // In a direction, piece can jump over every piece/choose one to eat.
+    // (betza calls this hopping, ex. the cannon captures with pR)
     LineJump {
         move_info: Vec<MoveVec>,
     },
@@ -154,10 +175,16 @@ pub enum MoveRules {
         // distance offset from the ending position, after traveling the radius. (in both directions perpendicular)
         offset: u32,
     },
+
+    // repeats a jump in a straight line until it hits something (ex. nightrider).
+    Rider {
+        translation: Vec2,
+        distance: Distance,
+    },
 }
 
 impl MoveRules {
-    pub fn jump(translation: Coord) -> MoveRules {
+    pub fn jump(translation: Vec2) -> MoveRules {
         MoveRules::Jump {
             translation,
         }
@@ -189,9 +216,17 @@ impl MoveRules {
     pub fn knight_jump(radius: u32, offset: u32) -> MoveRules {
         MoveRules::KnightJump { radius, offset }
     }
+
+    pub fn rider(translation: Vec2, distance: Distance) -> MoveRules {
+        MoveRules::Rider {
+            translation,
+            distance,
+        }
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NthMoveRules {
     move_rules: Vec<MoveRules>,
     // first move is 0 btw
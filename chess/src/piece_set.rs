@@ -22,12 +22,21 @@ impl PieceSet {
         }
     }
 
+    // pieces must already have their team and relative position set.
+    pub(crate) fn from_parts(team: Arc<Team>, pieces: Vec<Piece>, starting_coords: Vec<Coord>) -> Self {
+        Self {
+            team,
+            pieces,
+            starting_coords,
+        }
+    }
+
     pub fn team(&self) -> Arc<Team> {
         self.team.clone()
     }
 
-    pub fn pieces(&self) -> Vec<Piece> {
-        self.pieces.clone()
+    pub fn pieces(&self) -> &Vec<Piece> {
+        &self.pieces
     }
 
     pub fn starting_coords(&self) -> Vec<Coord> {
@@ -0,0 +1,320 @@
+// plain data copies of boards and games.
+// the board shares pieces and teams through Arc<RwLock>, which can't be serialized directly,
+// so everything is flattened here and pieces are referred to by (piece set index, piece index).
+
+use std::sync::{Arc, RwLock};
+
+use crate::board::Board;
+use crate::error::{ChessError, Result};
+use crate::game::{EnPassant, Game, Pass};
+use crate::piece::{Piece, PieceRef};
+use crate::piece_set::PieceSet;
+use crate::r#move::{Coord, MoveLog};
+use crate::team::Team;
+use crate::vec2::Vec2;
+
+/// index of a piece set on the board, and of the piece inside it.
+pub type PieceId = (usize, usize);
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PieceSnapshot {
+    piece: PieceRef,
+    starting_coord: Coord,
+    // the tile it stands on, none once it has been killed.
+    position: Option<Coord>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PieceSetSnapshot {
+    team: Team,
+    pieces: Vec<PieceSnapshot>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardSnapshot {
+    width: u32,
+    height: u32,
+    piece_sets: Vec<PieceSetSnapshot>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveLogSnapshot {
+    from: Coord,
+    to: Coord,
+    rel_translation: Vec2,
+    moved_piece: PieceId,
+    killed: Vec<(PieceId, Coord)>,
+    promoted_from: Option<PieceRef>,
+    castle: Option<Box<MoveLogSnapshot>>,
+    prev_en_passant: Option<EnPassant>,
+    prev_halfmove_clock: u32,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameSnapshot {
+    board: BoardSnapshot,
+    current_move: u32,
+    history: Vec<MoveLogSnapshot>,
+    halfmove_clock: u32,
+    en_passant: Option<EnPassant>,
+    // so the restored game can still count repetitions and undo passed turns. saves without
+    // them still load, earlier hashes are then recomputed on undo.
+    #[cfg_attr(feature = "serde", serde(default))]
+    hashes: Vec<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    passes: Vec<Pass>,
+}
+
+fn piece_id(board: &Board, piece: &Piece) -> Result<PieceId> {
+    for (set_idx, set) in board.piece_sets().iter().enumerate() {
+        if let Some(idx) = set.pieces().iter().position(|p| Arc::ptr_eq(p, piece)) {
+            return Ok((set_idx, idx));
+        }
+    }
+
+    Err(ChessError::GameValidationError {
+        why: format!("Piece {} is not in any piece set.", piece.read().unwrap().name()),
+    })
+}
+
+fn piece_by_id(board: &Board, id: PieceId) -> Result<Piece> {
+    board
+        .piece_sets()
+        .get(id.0)
+        .and_then(|set| set.pieces().get(id.1).cloned())
+        .ok_or(ChessError::GameValidationError {
+            why: format!("No piece with id {:?}.", id),
+        })
+}
+
+
+impl BoardSnapshot {
+    pub fn new(board: &Board) -> Self {
+        let sets = board.piece_sets();
+        let mut piece_sets: Vec<PieceSetSnapshot> = sets
+            .iter()
+            .map(|set| PieceSetSnapshot {
+                team: (*set.team()).clone(),
+                pieces: set
+                    .pieces()
+                    .iter()
+                    .zip(set.starting_coords())
+                    .map(|(piece, starting_coord)| PieceSnapshot {
+                        piece: piece.read().unwrap().clone(),
+                        starting_coord,
+                        position: None,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let tile = board.tile_at(x, y).unwrap();
+                let Some(piece) = tile.read().unwrap().piece() else {
+                    continue;
+                };
+                // pieces that aren't part of a piece set have nothing to be restored into.
+                if let Ok((set_idx, idx)) = piece_id(board, &piece) {
+                    piece_sets[set_idx].pieces[idx].position = Some(Coord::new(x, y));
+                }
+            }
+        }
+
+        Self {
+            width: board.width(),
+            height: board.height(),
+            piece_sets,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// builds a new board with its own teams, pieces and tiles.
+    pub fn restore(&self) -> Result<Board> {
+        let mut board = Board::new(self.width, self.height);
+
+        for set in &self.piece_sets {
+            let team = Arc::new(set.team.clone());
+            let pieces: Vec<Piece> = set
+                .pieces
+                .iter()
+                .map(|p| {
+                    let mut piece = p.piece.clone();
+                    piece.set_team(team.clone());
+                    Arc::new(RwLock::new(piece))
+                })
+                .collect();
+            let starting_coords = set.pieces.iter().map(|p| p.starting_coord).collect();
+            let positions: Vec<Option<Coord>> = set.pieces.iter().map(|p| p.position).collect();
+
+            board.place_piece_set(PieceSet::from_parts(team, pieces, starting_coords), &positions)?;
+        }
+
+        Ok(board)
+    }
+}
+
+impl MoveLogSnapshot {
+    fn new(board: &Board, log: &MoveLog) -> Result<Self> {
+        Ok(Self {
+            from: log.from,
+            to: log.to,
+            rel_translation: log.rel_translation,
+            moved_piece: piece_id(board, &log.moved_piece)?,
+            killed: log
+                .killed
+                .iter()
+                .map(|(piece, coord, _)| Ok((piece_id(board, piece)?, *coord)))
+                .collect::<Result<_>>()?,
+            promoted_from: log.promoted_from.clone(),
+            castle: match &log.castle {
+                Some(castle) => Some(Box::new(Self::new(board, castle)?)),
+                None => None,
+            },
+            prev_en_passant: log.prev_en_passant.clone(),
+            prev_halfmove_clock: log.prev_halfmove_clock,
+        })
+    }
+
+    fn restore(&self, board: &Board) -> Result<MoveLog> {
+        let moved_piece = piece_by_id(board, self.moved_piece)?;
+        let promoted_from = self.promoted_from.clone().map(|mut p| {
+            p.set_team(moved_piece.read().unwrap().team_unchecked());
+            p
+        });
+
+        Ok(MoveLog {
+            from: self.from,
+            to: self.to,
+            rel_translation: self.rel_translation,
+            moved_piece,
+            killed: self
+                .killed
+                .iter()
+                .map(|(id, coord)| Ok((piece_by_id(board, *id)?, *coord, board.tile(*coord)?)))
+                .collect::<Result<_>>()?,
+            promoted_from,
+            castle: match &self.castle {
+                Some(castle) => Some(Box::new(castle.restore(board)?)),
+                None => None,
+            },
+            moved_from: board.tile(self.from)?,
+            moved_to: board.tile(self.to)?,
+            prev_en_passant: self.prev_en_passant.clone(),
+            prev_halfmove_clock: self.prev_halfmove_clock,
+        })
+    }
+}
+
+impl GameSnapshot {
+    pub fn new(game: &Game) -> Result<Self> {
+        let board = game.board();
+        let history = game
+            .history()
+            .iter()
+            .map(|log| MoveLogSnapshot::new(board, log))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            board: BoardSnapshot::new(board),
+            current_move: game.current_move(),
+            history,
+            halfmove_clock: game.halfmove_clock(),
+            en_passant: game.en_passant().cloned(),
+            hashes: game.hashes().clone(),
+            passes: game.passes().clone(),
+        })
+    }
+
+    pub fn board(&self) -> &BoardSnapshot {
+        &self.board
+    }
+
+    pub fn current_move(&self) -> u32 {
+        self.current_move
+    }
+
+    /// builds an independent copy of the game, moves can be undone past the snapshot.
+    pub fn restore(&self) -> Result<Game> {
+        let board = self.board.restore()?;
+        let history = self
+            .history
+            .iter()
+            .map(|log| log.restore(&board))
+            .collect::<Result<_>>()?;
+
+        Ok(Game::from_parts(
+            board,
+            self.current_move,
+            history,
+            self.halfmove_clock,
+            self.en_passant.clone(),
+            self.hashes.clone(),
+            self.passes.clone(),
+        ))
+    }
+}
+
+impl Board {
+    pub fn snapshot(&self) -> BoardSnapshot {
+        BoardSnapshot::new(self)
+    }
+}
+
+impl Game {
+    pub fn snapshot(&self) -> Result<GameSnapshot> {
+        GameSnapshot::new(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{BoardSnapshot, GameSnapshot};
+    use crate::board::Board;
+    use crate::game::Game;
+
+    impl Serialize for Board {
+        fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+            self.snapshot().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Board {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+            BoardSnapshot::deserialize(deserializer)?
+                .restore()
+                .map_err(D::Error::custom)
+        }
+    }
+
+    impl Serialize for Game {
+        fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+            use serde::ser::Error;
+            self.snapshot()
+                .map_err(S::Error::custom)?
+                .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Game {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+            GameSnapshot::deserialize(deserializer)?
+                .restore()
+                .map_err(D::Error::custom)
+        }
+    }
+}
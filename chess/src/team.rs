@@ -3,7 +3,8 @@ use std::sync::{Arc, RwLock};
 use crate::error::Result;
 use crate::piece::Piece;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // ALL OFFSETS ARE TO THE LEFT, RELATIVE TO THE ORIENTATION OF THE BOARD.
 pub enum StartInfo {
     // ALL OFFSETS ARE TO THE LEFT, RELATIVE TO THE ORIENTATION OF THE BOARD.
@@ -13,6 +14,8 @@ pub enum StartInfo {
     Right { offset: u32 },
 }
 
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Team {
     name: String,
     start_info: StartInfo,
@@ -28,8 +31,8 @@ impl Team {
         }
     }
 
-    pub fn name(&self) -> String {
-        self.name.clone()
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
     pub fn start_info(&self) -> StartInfo {
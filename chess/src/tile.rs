@@ -7,17 +7,23 @@ use crate::error::{ChessError, Result};
 use crate::piece;
 use crate::piece::Piece;
 use crate::piece::PieceRef;
+use crate::r#move::Coord;
 use crate::team::Team;
 
 pub type Tile = Arc<RwLock<TileRef>>;
 
 #[derive(Clone)]
 pub struct TileRef {
+    coord: Coord,
     piece: Option<Piece>,
     team_on_tile: Option<Arc<Team>>
 }
 
 impl TileRef {
+    pub fn coord(&self) -> Coord {
+        self.coord
+    }
+
     pub fn piece(&self) -> Option<Piece> {
         self.piece.clone()
     }
@@ -31,30 +37,26 @@ impl TileRef {
     }
 
     pub fn set_piece(&mut self, piece: Piece) -> Result<()> {
-        if self.piece.is_some() {
-            let read_lock = self.piece.as_ref().unwrap().read().unwrap();
-            return Err(ChessError::TileActionError {
-                why: format!(
-                    "Could not replace tile's piece, there is already a {} here.",
-                    read_lock.name()
-                ),
+        if let Some(existing) = &self.piece {
+            return Err(ChessError::TileOccupied {
+                coord: self.coord,
+                piece: existing.read()?.name().to_string(),
             });
         }
-        self.piece = Some(piece.clone());
-        self.team_on_tile = piece.read().unwrap().team();
+        self.team_on_tile = piece.read()?.team();
+        self.piece = Some(piece);
         Ok(())
     }
 
-    pub fn replace_piece(&mut self, piece: Piece) {
-        self.piece = Some(piece.clone());
-        self.team_on_tile = piece.read().unwrap().team();
+    pub fn replace_piece(&mut self, piece: Piece) -> Result<()> {
+        self.team_on_tile = piece.read()?.team();
+        self.piece = Some(piece);
+        Ok(())
     }
 
     pub fn remove_piece(&mut self) -> Result<()> {
         if self.piece.is_none() {
-            return Err(ChessError::TileActionError {
-                why: format!("Could not remove tile's piece, it is already vacant."),
-            });
+            return Err(ChessError::TileEmpty { coord: self.coord });
         }
         self.piece = None;
         self.team_on_tile = None;
@@ -62,17 +64,12 @@ impl TileRef {
     }
 
     pub fn move_contained_piece(&mut self, target_tile: Tile) -> Result<()> {
-        if self.piece.is_none() {
-            return Err(ChessError::TileActionError {
-                why: "Could not move contained piece, there is no piece to move.".to_string(),
-            });
-        }
-
-        let mut lock = target_tile.write().unwrap();
-        lock.set_piece(self.piece.clone().unwrap())?;
-        drop(lock);
+        let Some(piece) = self.piece.clone() else {
+            return Err(ChessError::TileEmpty { coord: self.coord });
+        };
 
-        self.remove_piece();
+        target_tile.write()?.set_piece(piece)?;
+        self.remove_piece()?;
 
         Ok(())
     }
@@ -86,17 +83,16 @@ impl TileRef {
     }
 }
 
-#[derive(Default)]
 pub struct TileBuilder {
-    
+    coord: Coord,
 }
 
 impl TileBuilder {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(coord: Coord) -> Self {
+        Self { coord }
     }
 
     pub fn build(self) -> Tile {
-        Arc::new(RwLock::new(TileRef {piece: None, team_on_tile: None}))
+        Arc::new(RwLock::new(TileRef {coord: self.coord, piece: None, team_on_tile: None}))
     }
 }
@@ -1,4 +1,7 @@
-#[derive(Clone, Copy, Debug)]
+use crate::team::StartInfo;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec2 {
     x: i32,
     y: i32,
@@ -38,4 +41,38 @@ impl Vec2 {
             y: self.y * n
         }
     }
-}
\ No newline at end of file
+
+    // every distinct (±a, ±b) and (±b, ±a), ex. the 8 knight moves for (2, 1).
+    pub fn symmetric(a: u32, b: u32) -> Vec<Self> {
+        let (a, b) = (a as i32, b as i32);
+        let mut vecs = Vec::new();
+        for (x, y) in [(a, b), (b, a)] {
+            for (sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+                let v = Self::new(x * sx, y * sy);
+                if !vecs.contains(&v) {
+                    vecs.push(v);
+                }
+            }
+        }
+        vecs
+    }
+
+    // same rotation as Direction::rel_to_absolute, for arbitrary vectors.
+    pub fn rel_to_absolute(&self, start_info: StartInfo) -> Self {
+        match start_info {
+            StartInfo::Bottom { .. } => *self,
+            StartInfo::Top { .. } => Self::new(-self.x, -self.y),
+            StartInfo::Left { .. } => Self::new(self.y, -self.x),
+            StartInfo::Right { .. } => Self::new(-self.y, self.x),
+        }
+    }
+
+    pub fn absolute_to_rel(&self, start_info: StartInfo) -> Self {
+        match start_info {
+            StartInfo::Bottom { .. } => *self,
+            StartInfo::Top { .. } => Self::new(-self.x, -self.y),
+            StartInfo::Left { .. } => Self::new(-self.y, self.x),
+            StartInfo::Right { .. } => Self::new(self.y, -self.x),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// zobrist style position hashes. pieces are user defined, so instead of a table of random keys
+// every (team, piece, tile) key is mixed out of the piece's name and symbol when it's needed.
+// two positions with the same hash can be treated as the same position: same pieces on the same
+// tiles, same team to move, same move numbers where rules depend on them, and the same en passant
+// tiles when something can kill on them.
+
+use crate::game::Game;
+use crate::move_calculation::MoveGen;
+use crate::piece::PieceRef;
+
+// splitmix64's finalizer.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+fn combine(seed: u64, value: u64) -> u64 {
+    mix(seed ^ value.wrapping_add(0x9e3779b97f4a7c15))
+}
+
+// fnv-1a, stable between runs unlike the std hasher.
+fn hash_str(s: &str) -> u64 {
+    s.bytes()
+        .fold(0xcbf29ce484222325, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+// the part of a piece's move number its rules can tell apart.
+fn move_state(data: &PieceRef) -> u64 {
+    let last_nth = data.nth_move_rules().iter().map(|rules| rules.nth_move() + 1).max();
+    match last_nth {
+        Some(last_nth) => data.move_num().min(last_nth) as u64,
+        None if data.castles() || data.is_royal() => (data.move_num() == 0) as u64,
+        None => 0,
+    }
+}
+
+impl Game {
+    /// the hash of the current position.
+    pub fn hash(&self) -> u64 {
+        self.hashes().last().copied().unwrap_or_else(|| self.compute_hash())
+    }
+
+    /// how many times the current position happened before, only counting back to the last
+    /// kill or promotable piece move, since positions before those can't come back.
+    /// positions from before the game was loaded from fen aren't known.
+    pub fn repetitions(&self) -> usize {
+        let hashes = self.hashes();
+        let Some((current, previous)) = hashes.split_last() else {
+            return 0;
+        };
+        let window = (self.halfmove_clock() as usize).min(previous.len());
+
+        previous[previous.len() - window..]
+            .iter()
+            .rev()
+            // the same team has to be to move, so only every nth position can match.
+            .skip(self.board().piece_sets().len() - 1)
+            .step_by(self.board().piece_sets().len())
+            .filter(|hash| *hash == current)
+            .count()
+    }
+
+    // recomputes the hash from every piece on the board.
+    pub(crate) fn compute_hash(&self) -> u64 {
+        let board = self.board();
+        let sets = board.piece_sets();
+        let mut hash = combine(0, (self.current_move() as usize % sets.len().max(1)) as u64);
+
+        for (set_idx, set) in sets.iter().enumerate() {
+            let start_info = set.team().start_info();
+            for piece in set.pieces() {
+                let data = piece.read().unwrap();
+                let Some(rel_pos) = data.rel_pos().filter(|_| data.is_alive()) else {
+                    continue;
+                };
+                let coord = board.rel_coord_to_absolute(rel_pos, start_info);
+
+                let mut key = combine(set_idx as u64, hash_str(data.name()));
+                key = combine(key, data.symbol() as u64);
+                key = combine(key, ((coord.x() as u64) << 32) | coord.y() as u64);
+                key = combine(key, move_state(&data));
+                // xor, so the order the pieces are visited in doesn't matter.
+                hash ^= key;
+            }
+        }
+
+        if let Some(en_passant) = self.en_passant().filter(|_| self.en_passant_possible()) {
+            let mut key = hash_str(en_passant.team());
+            for square in en_passant.squares() {
+                key = combine(key, ((square.x() as u64) << 32) | square.y() as u64);
+            }
+            hash ^= mix(key);
+        }
+
+        hash
+    }
+
+    // whether another team has a piece that could kill en passant, ignoring royal safety.
+    fn en_passant_possible(&self) -> bool {
+        let Some(en_passant) = self.en_passant() else {
+            return false;
+        };
+        let move_gen = MoveGen::new(self.board(), Some(en_passant), true);
+        let mut moves = Vec::new();
+
+        self.board()
+            .piece_sets()
+            .iter()
+            .filter(|set| set.team().name() != en_passant.team())
+            .flat_map(|set| set.pieces())
+            .filter(|piece| piece.read().unwrap().can_en_passant())
+            .any(|piece| {
+                moves.clear();
+                move_gen.piece_moves(piece, &mut moves);
+                moves.iter().any(|m| en_passant.squares().contains(&m.to()))
+            })
+    }
+}
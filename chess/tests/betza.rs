@@ -0,0 +1,95 @@
+use chess::{
+    betza,
+    game::Game,
+    piece::{self, PieceBuilder},
+    piece_rules::{Direction, Distance, MoveRules, MoveVec},
+    vec2::Vec2,
+};
+
+#[test]
+pub fn parse_cannon() {
+    let rules = betza::parse("mRcpR").unwrap();
+    let orthogonal = |distance| {
+        vec![
+            MoveVec::new(distance, Direction::Right),
+            MoveVec::new(distance, Direction::Left),
+            MoveVec::new(distance, Direction::Up),
+            MoveVec::new(distance, Direction::Down),
+        ]
+    };
+
+    assert_eq!(rules.move_rules(), &vec![MoveRules::blunt(orthogonal(Distance::infinite()))]);
+    assert_eq!(rules.kill_rules(), &vec![MoveRules::line_jump(orthogonal(Distance::infinite()))]);
+    assert!(!rules.use_moves_for_kills());
+    assert!(!rules.use_kill_for_moves());
+}
+
+#[test]
+pub fn parse_riders_and_leapers() {
+    let nightrider = betza::parse("NN").unwrap();
+    assert_eq!(nightrider.move_rules().len(), 8);
+    assert!(nightrider.use_moves_for_kills());
+    assert!(nightrider
+        .move_rules()
+        .contains(&MoveRules::rider(Vec2::new(1, 2), Distance::infinite())));
+
+    let knight = betza::parse("N").unwrap();
+    assert_eq!(knight.move_rules(), &vec![MoveRules::knight_jump(2, 1)]);
+
+    let narrow = betza::parse("ffN").unwrap();
+    assert_eq!(
+        narrow.move_rules(),
+        &vec![MoveRules::jump(Vec2::new(1, 2)), MoveRules::jump(Vec2::new(-1, 2))]
+    );
+
+    // wazir that can also capture diagonally forward.
+    let rules = betza::parse("WfcF").unwrap();
+    assert!(rules.use_moves_for_kills());
+    assert_eq!(rules.kill_rules().len(), 1);
+}
+
+#[test]
+pub fn parse_errors() {
+    assert!(betza::parse("").is_err());
+    assert!(betza::parse("fm").is_err());
+    assert!(betza::parse("nN").is_err());
+    assert!(betza::parse("pN").is_err());
+    assert!(betza::parse("flW").is_err());
+}
+
+#[test]
+pub fn describe_defaults() {
+    let describe = |p: piece::Piece| p.read().unwrap().betza();
+
+    assert_eq!(describe(piece::defaults::rook()), "R");
+    assert_eq!(describe(piece::defaults::bishop()), "B");
+    assert_eq!(describe(piece::defaults::queen()), "Q");
+    assert_eq!(describe(piece::defaults::king()), "K");
+    assert_eq!(describe(piece::defaults::knight()), "N");
+    assert_eq!(describe(piece::defaults::pawn()), "mfWcfFimfR2");
+}
+
+#[test]
+pub fn round_trip() {
+    for notation in ["WfcF", "NN", "mRcpR", "fmWfcFifmR2", "KDNA", "x3Q", "vRsW", "ffNbbN", "(3,4)", "R4cN"] {
+        let piece = PieceBuilder::new().betza(notation).unwrap().build().unwrap();
+        let described = piece.read().unwrap().betza();
+        let again = PieceBuilder::new().betza(&described).unwrap().build().unwrap();
+
+        assert_eq!(described, again.read().unwrap().betza(), "{notation} -> {described}");
+        assert_eq!(
+            betza::parse(notation).unwrap(),
+            betza::parse(&described).unwrap(),
+            "{notation} -> {described}"
+        );
+    }
+}
+
+#[test]
+pub fn nightrider_moves() {
+    let nightrider = PieceBuilder::new().betza("NN").unwrap().build().unwrap();
+    let game = Game::two_piece_test(nightrider).unwrap();
+
+    // from (2, 3) on the empty 10x10 board, stopping at the edges.
+    assert_eq!(game.calculate_moves_for("White".to_string()).unwrap().len(), 15);
+}
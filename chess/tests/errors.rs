@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use chess::board::Board;
+use chess::error::ChessError;
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+use chess::piece::{self, PieceBuilder};
+use chess::piece_set::PieceSet;
+use chess::r#move::{Coord, MoveKey};
+use chess::team::{StartInfo, Team};
+
+fn key(from: (u32, u32), to: (u32, u32)) -> MoveKey {
+    MoveKey {
+        from: Coord::new(from.0, from.1),
+        to: Coord::new(to.0, to.1),
+        promotion: None,
+    }
+}
+
+#[test]
+pub fn board_errors() {
+    let team = Arc::new(Team::new("White".to_string(), StartInfo::Bottom { offset: 0 }));
+    let mut set = PieceSet::new(team, Vec::new());
+    set.add_piece(piece::defaults::rook(), Coord::new(9, 0)).unwrap();
+
+    let err = Board::new(8, 8).add_piece_set(set).unwrap_err();
+    assert!(matches!(err, ChessError::OutOfBounds { coord, width: 8, height: 8 } if coord == Coord::new(9, 0)));
+    assert_eq!(err.to_string(), "(9, 0) is outside the 8x8 board.");
+
+    let board = Board::new(8, 8);
+    let tile = board.tile(Coord::new(2, 5)).unwrap();
+    tile.write().unwrap().set_piece(piece::defaults::queen()).unwrap();
+    let err = tile.write().unwrap().set_piece(piece::defaults::king()).unwrap_err();
+    assert!(matches!(&err, ChessError::TileOccupied { coord, piece } if *coord == Coord::new(2, 5) && piece == "Queen"));
+    assert_eq!(err.to_string(), "(2, 5) is already occupied by a Queen.");
+
+    let err = board.tile(Coord::new(0, 0)).unwrap().write().unwrap().remove_piece().unwrap_err();
+    assert!(matches!(err, ChessError::TileEmpty { .. }));
+}
+
+#[test]
+pub fn move_errors() {
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+
+    let err = game.play(key((4, 6), (4, 4))).unwrap_err();
+    assert!(matches!(&err, ChessError::NotYourTurn { team, current } if team == "Black" && current == "White"));
+    assert_eq!(err.to_string(), "It is White's turn, not Black's.");
+
+    let err = game.play(key((4, 1), (4, 4))).unwrap_err();
+    assert!(matches!(err, ChessError::IllegalMove { mv, .. } if mv == key((4, 1), (4, 4))));
+    assert!(matches!(game.play(key((4, 3), (4, 4))), Err(ChessError::TileEmpty { .. })));
+
+    game.play(key((4, 1), (4, 3))).unwrap();
+    assert_eq!(game.current_move(), 1);
+
+    let err = game.calculate_moves_for("Green".to_string()).unwrap_err();
+    assert!(matches!(&err, ChessError::UnknownTeam { name } if name == "Green"));
+}
+
+#[test]
+pub fn piece_errors() {
+    let err = PieceBuilder::new().symbol('3').build().unwrap_err();
+    assert!(matches!(err, ChessError::PieceCreationError { .. }));
+    assert_eq!(err.to_string(), "Error creating piece: 3 is not a letter, it can't be used as a symbol.");
+    assert!(PieceBuilder::new().name("Archbishop".to_string()).symbol('A').build().is_ok());
+}
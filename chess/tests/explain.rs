@@ -0,0 +1,127 @@
+use chess::error::ChessError;
+use chess::explain::{IllegalReason, Immunity};
+use chess::fen::{standard_catalog, STARTING_FEN};
+use chess::game::Game;
+use chess::notation::parse_square;
+use chess::piece::PieceBuilder;
+use chess::piece_rules::{Direction, Distance, MoveRules, MoveVec};
+use chess::vec2::Vec2;
+
+fn explain(game: &Game, from: &str, to: &str) -> Option<IllegalReason> {
+    game.explain_illegal(parse_square(from).unwrap(), parse_square(to).unwrap())
+        .unwrap()
+}
+
+#[test]
+pub fn ownership() {
+    let game = Game::from_fen(STARTING_FEN).unwrap();
+    assert_eq!(explain(&game, "e2", "e4"), None);
+    assert_eq!(explain(&game, "e4", "e5"), Some(IllegalReason::NoPiece { coord: parse_square("e4").unwrap() }));
+    assert_eq!(
+        explain(&game, "e7", "e5"),
+        Some(IllegalReason::NotYourPiece { owner: "Black".to_string() })
+    );
+    assert_eq!(
+        game.explain_illegal_for("Black", parse_square("e7").unwrap(), parse_square("e5").unwrap())
+            .unwrap(),
+        Some(IllegalReason::NotYourTurn { current: "White".to_string() })
+    );
+    assert_eq!(
+        explain(&game, "d1", "d2"),
+        Some(IllegalReason::OwnPieceAtTarget { coord: parse_square("d2").unwrap() })
+    );
+    assert!(matches!(
+        game.explain_illegal_for("Green", parse_square("e2").unwrap(), parse_square("e4").unwrap()),
+        Err(ChessError::UnknownTeam { .. })
+    ));
+}
+
+#[test]
+pub fn rules() {
+    let game = Game::from_fen("4k3/8/8/4p3/4P3/2P5/8/R3K3 w - - 0 1").unwrap();
+    assert_eq!(explain(&game, "e1", "e3"), Some(IllegalReason::NoRuleReaches));
+    assert_eq!(
+        explain(&game, "c3", "c5"),
+        Some(IllegalReason::NthMoveExpired { nth_move: 0, move_num: 1 })
+    );
+    assert_eq!(
+        explain(&game, "e4", "e5"),
+        Some(IllegalReason::CannotKill { coord: parse_square("e5").unwrap() })
+    );
+    assert_eq!(explain(&game, "c3", "d4"), Some(IllegalReason::NothingToKill));
+
+    let game = Game::from_fen("4k3/8/8/8/8/8/P7/R3K3 w - - 0 1").unwrap();
+    assert_eq!(
+        explain(&game, "a1", "a5"),
+        Some(IllegalReason::BlockedBy { coord: parse_square("a2").unwrap() })
+    );
+}
+
+#[test]
+pub fn royal_danger() {
+    // the bishop on d2 is pinned, and the king can't step next to the rook.
+    let game = Game::from_fen("4k3/8/8/q7/8/8/3B3r/4K3 w - - 0 1").unwrap();
+    let reason = IllegalReason::LeavesRoyalInDanger { royal: parse_square("e1").unwrap() };
+    assert_eq!(explain(&game, "d2", "e3"), Some(reason));
+    assert_eq!(
+        explain(&game, "e1", "f2"),
+        Some(IllegalReason::LeavesRoyalInDanger { royal: parse_square("f2").unwrap() })
+    );
+    assert_eq!(explain(&game, "d2", "c3"), None);
+}
+
+#[test]
+pub fn fairy_pieces() {
+    let mut catalog = standard_catalog();
+    catalog.push(
+        PieceBuilder::new()
+            .name("Wall".to_string())
+            .symbol('W')
+            .jump_immune(true)
+            .build()
+            .unwrap(),
+    );
+    catalog.push(
+        PieceBuilder::new()
+            .name("Cannon".to_string())
+            .symbol('C')
+            .move_rules(vec![MoveRules::line_jump(vec![MoveVec::new(Distance::infinite(), Direction::Up)])])
+            .use_moves_for_kills(true)
+            .build()
+            .unwrap(),
+    );
+
+    let game = Game::from_fen_with("4k3/8/4w3/8/3N4/8/8/2C1K3 w - - 0 1", &catalog).unwrap();
+    assert_eq!(
+        explain(&game, "d4", "e6"),
+        Some(IllegalReason::TargetImmune {
+            coord: parse_square("e6").unwrap(),
+            immunity: Immunity::Jump,
+        })
+    );
+    assert_eq!(explain(&game, "c1", "c5"), Some(IllegalReason::NothingToJumpOver));
+
+    // a vertical leaper jumping down the board.
+    catalog.push(PieceBuilder::new().name("Dabbaba".to_string()).betza("D").unwrap().build().unwrap());
+    let game = Game::from_fen_with("k7/8/4D3/8/4w3/8/8/K7 w - - 0 1", &catalog).unwrap();
+    assert_eq!(
+        explain(&game, "e6", "e4"),
+        Some(IllegalReason::TargetImmune {
+            coord: parse_square("e4").unwrap(),
+            immunity: Immunity::Jump,
+        })
+    );
+
+    // a zero translation next to a real jump.
+    catalog.push(
+        PieceBuilder::new()
+            .name("Stay".to_string())
+            .symbol('S')
+            .move_rules(vec![MoveRules::jump(Vec2::new(0, 0)), MoveRules::jump(Vec2::new(1, 2))])
+            .build()
+            .unwrap(),
+    );
+    let game = Game::from_fen_with("k7/8/8/8/3S4/8/8/K7 w - - 0 1", &catalog).unwrap();
+    assert_eq!(explain(&game, "d4", "h8"), Some(IllegalReason::NoRuleReaches));
+    assert_eq!(explain(&game, "d4", "e6"), None);
+}
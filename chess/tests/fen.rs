@@ -0,0 +1,39 @@
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+
+#[test]
+pub fn round_trip() {
+    for fen in [
+        STARTING_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "4k3/8/8/8/8/8/8/4K2R b K - 12 40",
+    ] {
+        assert_eq!(Game::from_fen(fen).unwrap().to_fen().unwrap(), fen);
+    }
+}
+
+#[test]
+pub fn state_after_moves() {
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    for san in ["e4", "Nf6", "e5", "d5"] {
+        let m = game.parse_san(san).unwrap();
+        game.execute_move(&m).unwrap();
+    }
+    assert_eq!(game.to_fen().unwrap(), "rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3");
+
+    let m = game.parse_san("exd6").unwrap();
+    game.execute_move(&m).unwrap();
+    let m = game.parse_san("Rg8").unwrap();
+    game.execute_move(&m).unwrap();
+    assert_eq!(game.to_fen().unwrap(), "rnbqkbr1/ppp1pppp/3P1n2/8/8/8/PPPP1PPP/RNBQKBNR w KQq - 1 4");
+}
+
+#[test]
+pub fn wide_boards() {
+    let fen = "r3k4r/10/10/10/10/10/10/10/10/R3K4R w KQkq - 0 1";
+    let game = Game::from_fen(fen).unwrap();
+    assert_eq!(game.board().width(), 10);
+    assert_eq!(game.board().height(), 10);
+    assert!(Game::from_fen("rnbqkbnr/ppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err());
+}
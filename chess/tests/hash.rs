@@ -0,0 +1,116 @@
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+
+fn play(game: &mut Game, moves: &[&str]) {
+    for uci in moves {
+        let m = game.parse_uci(uci).unwrap();
+        game.execute_move(&m).unwrap();
+    }
+}
+
+#[test]
+pub fn transpositions() {
+    let mut a = Game::from_fen(STARTING_FEN).unwrap();
+    let mut b = Game::from_fen(STARTING_FEN).unwrap();
+    play(&mut a, &["g1f3", "b8c6", "b1c3"]);
+    play(&mut b, &["b1c3", "b8c6", "g1f3"]);
+    assert_eq!(a.hash(), b.hash());
+
+    // the same tiles with the other team to move.
+    let white = Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let black = Game::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+    assert_ne!(white.hash(), black.hash());
+
+    // castling rights and en passant tiles are part of the position.
+    let rights = Game::from_fen("r3k3/8/8/8/8/8/8/4K3 b q - 0 1").unwrap();
+    let no_rights = Game::from_fen("r3k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+    assert_ne!(rights.hash(), no_rights.hash());
+    let en_passant = Game::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+    let no_en_passant = Game::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+    assert_ne!(en_passant.hash(), no_en_passant.hash());
+}
+
+#[test]
+pub fn undo_and_repetitions() {
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    let start = game.hash();
+    play(&mut game, &["e2e4"]);
+    game.undo().unwrap();
+    assert_eq!(game.hash(), start);
+
+    play(&mut game, &["g1f3", "g8f6", "f3g1"]);
+    assert_eq!(game.repetitions(), 0);
+    play(&mut game, &["f6g8"]);
+    assert_eq!(game.hash(), start);
+    assert_eq!(game.repetitions(), 1);
+    play(&mut game, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+    assert_eq!(game.repetitions(), 2);
+
+    // nothing before a pawn move can come back.
+    play(&mut game, &["e2e4", "e7e5", "g1f3", "g8f6", "f3g1", "f6g8"]);
+    assert_eq!(game.repetitions(), 1);
+}
+
+#[test]
+pub fn undo_past_a_restore() {
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    let start = game.hash();
+    play(&mut game, &["e2e4"]);
+    let after_e4 = game.hash();
+    play(&mut game, &["e7e5"]);
+
+    let mut restored = game.snapshot().unwrap().restore().unwrap();
+    assert_eq!(restored.hashes(), game.hashes());
+    play(&mut restored, &["g1f3"]);
+    restored.undo().unwrap();
+    assert_eq!(restored.hash(), game.hash());
+
+    // the positions from before the restore still hash the same.
+    restored.undo().unwrap();
+    assert_eq!(restored.hash(), after_e4);
+    restored.undo().unwrap();
+    assert_eq!(restored.hash(), start);
+    assert_eq!(restored.hashes().len(), 1);
+}
+
+#[test]
+pub fn passing() {
+    let mut game = Game::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+    let start = game.hash();
+    game.pass_turn();
+    assert_eq!(game.current_move(), 2);
+    assert!(game.en_passant().is_none());
+    assert_ne!(game.hash(), start);
+
+    // a pass undoes like a move, and moves made after it come off first.
+    play(&mut game, &["e1d1"]);
+    game.undo().unwrap();
+    game.undo().unwrap();
+    assert_eq!(game.hash(), start);
+    assert_eq!(game.current_move(), 1);
+    assert!(game.en_passant().is_some());
+}
+
+#[test]
+pub fn deep_clone() {
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    play(&mut game, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+    let mut copy = game.deep_clone().unwrap();
+    assert_eq!(copy.hash(), game.hash());
+    assert_eq!(copy.repetitions(), 1);
+
+    // moves on the copy don't touch the original's pieces.
+    play(&mut copy, &["e2e4"]);
+    assert!(game.board().tile(chess::notation::parse_square("e2").unwrap()).unwrap().read().unwrap().piece().is_some());
+    copy.undo().unwrap();
+    assert_eq!(copy.hash(), game.hash());
+
+    // and it can be sent to another thread.
+    let hash = std::thread::spawn(move || {
+        play(&mut copy, &["e2e4"]);
+        copy.hash()
+    })
+    .join()
+    .unwrap();
+    assert_ne!(hash, game.hash());
+}
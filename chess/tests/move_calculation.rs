@@ -1,8 +1,38 @@
+use chess::fen::standard_catalog;
 use chess::game::Game;
+use chess::notation::parse_square;
+use chess::piece::PieceBuilder;
 
 #[test]
 pub fn calc_default() {
     let game = Game::original().unwrap();
 
-    assert!(game.calculate_moves_for("White".to_string()).unwrap().len() + game.calculate_moves_for("Black".to_string()).unwrap().len() == 40);
-}
\ No newline at end of file
+    // 16 pawn moves, 6 knight moves and 2 rook moves (the outer files are empty on a 10x10 board) each.
+    assert!(game.calculate_moves_for("White".to_string()).unwrap().len() + game.calculate_moves_for("Black".to_string()).unwrap().len() == 48);
+}
+
+#[test]
+pub fn overlapping_rules() {
+    // moves and kills one step diagonally forward, so both rules reach the en passant tile.
+    let mut catalog = standard_catalog();
+    catalog.push(
+        PieceBuilder::new()
+            .name("Ferz Pawn".to_string())
+            .symbol('X')
+            .betza("mfFcfF")
+            .unwrap()
+            .en_passant(true)
+            .build()
+            .unwrap(),
+    );
+    let mut game = Game::from_fen_with("4k3/8/8/3pX3/8/8/8/4K3 w - d6 0 1", &catalog).unwrap();
+    let from = parse_square("e5").unwrap();
+    let moves: Vec<_> = game.legal_moves().unwrap().into_iter().filter(|m| m.from() == from).collect();
+    assert_eq!(moves.len(), 2);
+    let en_passant = moves.iter().find(|m| m.to() == parse_square("d6").unwrap()).unwrap();
+    assert_eq!(en_passant.kills(), vec![parse_square("d5").unwrap()]);
+
+    let m = game.parse_uci("e5d6").unwrap();
+    game.execute_move(&m).unwrap();
+    assert!(game.board().tile(parse_square("d5").unwrap()).unwrap().read().unwrap().vacant());
+}
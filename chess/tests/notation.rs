@@ -0,0 +1,36 @@
+use chess::game::Game;
+use chess::notation::{parse_square, square_name};
+use chess::r#move::Coord;
+
+#[test]
+pub fn squares() {
+    assert_eq!(square_name(Coord::new(4, 3)), "e4");
+    assert_eq!(square_name(Coord::new(0, 9)), "a10");
+    assert_eq!(parse_square("j10").unwrap(), Coord::new(9, 9));
+    assert!(parse_square("e0").is_err());
+}
+
+#[test]
+pub fn san_round_trip() {
+    let game = Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    for m in game.legal_moves().unwrap() {
+        let san = game.move_to_san(&m).unwrap();
+        assert_eq!(game.parse_san(&san).unwrap().key(), m.key());
+        let uci = game.move_to_uci(&m);
+        assert_eq!(game.parse_uci(&uci).unwrap().key(), m.key());
+    }
+}
+
+#[test]
+pub fn san_details() {
+    // two knights can reach d2, and b7 promotes with check.
+    let game = Game::from_fen("4k3/1P6/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+    let san = |uci: &str| game.move_to_san(&game.parse_uci(uci).unwrap()).unwrap();
+    assert_eq!(san("b1d2"), "Nbd2");
+    assert_eq!(san("b7b8q"), "b8=Q+");
+    assert_eq!(game.parse_san("b8Q").unwrap().key(), game.parse_uci("b7b8q").unwrap().key());
+
+    let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    assert_eq!(game.move_to_san(&game.parse_uci("a1a8").unwrap()).unwrap(), "Ra8#");
+    assert_eq!(game.move_to_san(&game.parse_uci("e1c1").unwrap()).unwrap(), "O-O-O");
+}
@@ -0,0 +1,41 @@
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+
+fn perft(fen: &str, depth: u32) -> u64 {
+    Game::from_fen(fen).unwrap().perft(depth).unwrap()
+}
+
+#[test]
+pub fn perft_start() {
+    assert_eq!(perft(STARTING_FEN, 1), 20);
+    assert_eq!(perft(STARTING_FEN, 3), 8902);
+}
+
+// castling, en passant and promotions.
+#[test]
+pub fn perft_kiwipete() {
+    assert_eq!(perft("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 2), 2039);
+}
+
+// en passant that would expose the king.
+#[test]
+pub fn perft_pinned_en_passant() {
+    assert_eq!(perft("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 3), 2812);
+}
+
+#[test]
+pub fn perft_promotions() {
+    assert_eq!(perft("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 2), 264);
+}
+
+#[test]
+pub fn undo_restores_position() {
+    let mut game = Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let fen = game.to_fen().unwrap();
+
+    for m in game.legal_moves().unwrap() {
+        game.execute_move(&m).unwrap();
+        game.undo().unwrap();
+        assert_eq!(game.to_fen().unwrap(), fen);
+    }
+}
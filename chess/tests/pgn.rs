@@ -0,0 +1,73 @@
+use chess::fen::standard_catalog;
+use chess::game::Game;
+use chess::pgn::{parse_pgn, result_of, PgnGame};
+
+const GAMES: &str = r#"[Event "Casual"]
+[White "A"]
+[Black "B"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 {the usual} Nc6 (2... d6 3. d4) 3.Bb5 a6 $1 4. Ba4 Nf6 5. 0-0 Be7 1-0
+
+[Event "Short"]
+[FEN "4k3/8/8/8/8/8/8/R3K3 w Q - 0 1"]
+[SetUp "1"]
+
+1. O-O-O Ke7 ; resigns
+2. Rd7+ *
+"#;
+
+#[test]
+pub fn parsing() {
+    let games = parse_pgn(GAMES).unwrap();
+    assert_eq!(games.len(), 2);
+
+    assert_eq!(games[0].tag("White"), Some("A"));
+    assert_eq!(games[0].tag("Round"), None);
+    assert_eq!(games[0].result, "1-0");
+    assert_eq!(games[0].moves, ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "0-0", "Be7"]);
+    let game = games[0].replay_with(&standard_catalog()).unwrap();
+    assert_eq!(game.to_fen().unwrap(), "r1bqk2r/1pppbppp/p1n2n2/4p3/B3P3/5N2/PPPP1PPP/RNBQ1RK1 w kq - 4 6");
+
+    assert_eq!(games[1].result, "*");
+    assert_eq!(games[1].moves, ["O-O-O", "Ke7", "Rd7+"]);
+    assert_eq!(games[1].start().unwrap().to_fen().unwrap(), "4k3/8/8/8/8/8/8/R3K3 w Q - 0 1");
+    let game = games[1].replay_with(&standard_catalog()).unwrap();
+    assert!(game.is_in_check(game.current_team().name()));
+
+    // a game without a result still counts, a bad move shows up when it's replayed.
+    let games = parse_pgn("1. e4 e5 2. Ke3").unwrap();
+    assert_eq!(games.len(), 1);
+    assert!(games[0].replay_with(&standard_catalog()).is_err());
+    assert!(parse_pgn("1. e4 (1. d4").is_err());
+}
+
+#[test]
+pub fn writing() {
+    let games = parse_pgn(GAMES).unwrap();
+    for game in &games {
+        assert_eq!(parse_pgn(&game.to_pgn()).unwrap(), std::slice::from_ref(game));
+    }
+    assert!(games[0].to_pgn().starts_with("[Event \"Casual\"]\n[White \"A\"]\n"));
+    assert!(games[0].to_pgn().ends_with("\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. 0-0 Be7 1-0\n"));
+
+    // black moving first, and long games wrapped.
+    let game = PgnGame {
+        tags: vec![("FEN".to_string(), "4k3/8/8/8/8/8/8/4K3 b - - 0 30".to_string())],
+        moves: ["Kd7", "Kd2"].repeat(20).into_iter().map(String::from).collect(),
+        result: "1/2-1/2".to_string(),
+    };
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("\n\n30... Kd7 31. Kd2 Kd7"));
+    assert!(pgn.lines().all(|line| line.len() <= 80));
+    assert_eq!(parse_pgn(&pgn).unwrap(), [game]);
+}
+
+#[test]
+pub fn results() {
+    let result = |fen: &str| result_of(&Game::from_fen(fen).unwrap());
+    assert_eq!(result("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), None);
+    assert_eq!(result("R3k3/8/4K3/8/8/8/8/8 b - - 0 1"), Some(("1-0", "White mates")));
+    assert_eq!(result("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"), Some(("1/2-1/2", "Stalemate")));
+    assert_eq!(result("4k3/8/8/8/8/8/8/4K3 w - - 100 80"), Some(("1/2-1/2", "50 move rule")));
+}
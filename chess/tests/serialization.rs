@@ -0,0 +1,109 @@
+#![cfg(feature = "serde")]
+
+use chess::{
+    fen::STARTING_FEN,
+    game::Game,
+    piece_rules::MoveRules,
+};
+
+fn first_move(game: &Game, team: &str) -> chess::r#move::Move {
+    game.calculate_moves_for(team.to_string())
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap()
+}
+
+#[test]
+pub fn game_round_trip() {
+    let mut game = Game::original().unwrap();
+    let white_move = first_move(&game, "White");
+    game.execute_move(&white_move).unwrap();
+    let black_move = first_move(&game, "Black");
+    game.execute_move(&black_move).unwrap();
+
+    let json = serde_json::to_string(&game).unwrap();
+    let mut restored: Game = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(json, serde_json::to_string(&restored).unwrap());
+    assert_eq!(restored.current_move(), 2);
+    assert_eq!(restored.history().len(), 2);
+    restored.validate_game().unwrap();
+
+    // history survives, so the restored game can be undone back to the start.
+    restored.undo().unwrap();
+    restored.undo().unwrap();
+    let fresh = serde_json::to_string(&Game::original().unwrap()).unwrap();
+    assert_eq!(fresh, serde_json::to_string(&restored).unwrap());
+}
+
+#[test]
+pub fn restored_game_is_independent() {
+    let game = Game::original().unwrap();
+    let mut restored: Game = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+
+    let m = first_move(&restored, "White");
+    restored.execute_move(&m).unwrap();
+
+    let from = m.from();
+    assert!(restored.board().tile_at(from.x(), from.y()).unwrap().read().unwrap().vacant());
+    assert!(game.board().tile_at(from.x(), from.y()).unwrap().read().unwrap().occupied());
+}
+
+#[test]
+pub fn rules_round_trip() {
+    let rules = chess::betza::parse("mRcpRNNx3B").unwrap();
+    let json = serde_json::to_string(rules.move_rules()).unwrap();
+    let restored: Vec<MoveRules> = serde_json::from_str(&json).unwrap();
+    assert_eq!(&restored, rules.move_rules());
+}
+
+#[test]
+pub fn bincode_round_trip() {
+    let mut game = Game::original().unwrap();
+    let white_move = first_move(&game, "White");
+    game.execute_move(&white_move).unwrap();
+
+    let bytes = bincode::serialize(&game).unwrap();
+    let mut restored: Game = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(bytes, bincode::serialize(&restored).unwrap());
+    restored.validate_game().unwrap();
+
+    restored.undo().unwrap();
+    assert!(restored.undo().is_err());
+    let fresh = bincode::serialize(&Game::original().unwrap()).unwrap();
+    assert_eq!(fresh, bincode::serialize(&restored).unwrap());
+}
+
+#[test]
+pub fn hashes_and_passes_round_trip() {
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    let play = |game: &mut Game, uci: &str| {
+        let m = game.parse_uci(uci).unwrap();
+        game.execute_move(&m).unwrap();
+    };
+    play(&mut game, "e2e4");
+    play(&mut game, "e7e5");
+    let before_pass = game.hash();
+    game.pass_turn();
+    play(&mut game, "b8c6");
+
+    let bytes = bincode::serialize(&game).unwrap();
+    let mut restored: Game = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(restored.hashes(), game.hashes());
+    assert_eq!(restored.to_fen().unwrap(), game.to_fen().unwrap());
+
+    // play on, then undo back through the restore point, the pass included.
+    play(&mut restored, "g1f3");
+    restored.undo().unwrap();
+    assert_eq!(restored.hash(), game.hash());
+    restored.undo().unwrap();
+    restored.undo().unwrap();
+    assert_eq!(restored.hash(), before_pass);
+    assert_eq!(restored.current_move(), 2);
+    restored.undo().unwrap();
+    restored.undo().unwrap();
+    assert_eq!(restored.to_fen().unwrap(), STARTING_FEN);
+    assert_eq!(restored.hash(), Game::from_fen(STARTING_FEN).unwrap().hash());
+    assert!(restored.undo().is_err());
+}
@@ -0,0 +1,79 @@
+// node counts of fixed depth searches with and without move ordering.
+// usage: bench [--depth n]
+
+use std::process::ExitCode;
+use std::time::Instant;
+
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+use chess_engine::search::{SearchLimits, Searcher};
+
+const POSITIONS: [(&str, &str); 5] = [
+    ("start", STARTING_FEN),
+    ("italian", "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3"),
+    ("middlegame", "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 8"),
+    ("endgame", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"),
+    ("10x10", "r3k4r/pppppppppp/10/10/10/10/10/10/PPPPPPPPPP/R3K4R w KQkq - 0 1"),
+];
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let depth = match args.as_slice() {
+        [] => 3,
+        [flag, depth] if flag == "--depth" => match depth.parse() {
+            Ok(depth) => depth,
+            Err(_) => return usage(),
+        },
+        _ => return usage(),
+    };
+
+    let mut totals = [0_u64; 2];
+    for (name, fen) in POSITIONS {
+        let mut counts = [0_u64; 2];
+        for (idx, order_moves) in [false, true].into_iter().enumerate() {
+            let mut game = match Game::from_fen(fen) {
+                Ok(game) => game,
+                Err(e) => {
+                    eprintln!("could not load {}: {:?}", name, e);
+                    return ExitCode::FAILURE;
+                }
+            };
+
+            let start = Instant::now();
+            let mut searcher = Searcher::new(SearchLimits::new().depth(depth)).with_move_ordering(order_moves);
+            match searcher.search(&mut game) {
+                Ok(result) => {
+                    counts[idx] = result.nodes;
+                    println!(
+                        "{:<10} {:<11} {:>10} nodes in {:.2?}",
+                        name,
+                        if order_moves { "ordered" } else { "unordered" },
+                        result.nodes,
+                        start.elapsed()
+                    );
+                }
+                Err(e) => {
+                    eprintln!("search failed on {}: {:?}", name, e);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        totals[0] += counts[0];
+        totals[1] += counts[1];
+    }
+
+    println!(
+        "total at depth {}: {} unordered, {} ordered, {:.1}% of the nodes",
+        depth,
+        totals[0],
+        totals[1],
+        totals[1] as f64 * 100.0 / totals[0].max(1) as f64
+    );
+
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: bench [--depth n]");
+    ExitCode::FAILURE
+}
@@ -0,0 +1,83 @@
+// builds an opening book out of a pgn collection.
+// usage: book <games.pgn> <book.bin> [--plies n] [--variant name]
+// standard chess makes a polyglot book, other variants a variant book keyed by our own hashes.
+
+use std::process::ExitCode;
+
+use chess::pgn::parse_pgn;
+use chess_engine::book::{BookBuilder, BookFormat, DEFAULT_BOOK_PLIES};
+use chess_engine::variant::builtin_variants;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut paths = Vec::new();
+    let mut plies = DEFAULT_BOOK_PLIES;
+    let mut variant_name = "chess".to_string();
+
+    let mut idx = 0;
+    while idx < args.len() {
+        match args[idx].as_str() {
+            "--plies" => {
+                idx += 1;
+                match args.get(idx).and_then(|p| p.parse().ok()) {
+                    Some(p) => plies = p,
+                    None => return usage(),
+                }
+            }
+            "--variant" => {
+                idx += 1;
+                match args.get(idx) {
+                    Some(name) => variant_name = name.clone(),
+                    None => return usage(),
+                }
+            }
+            arg => paths.push(arg.to_string()),
+        }
+        idx += 1;
+    }
+    let [pgn_path, book_path] = paths.as_slice() else {
+        return usage();
+    };
+
+    let Some(variant) = builtin_variants().into_iter().find(|v| v.name() == variant_name) else {
+        eprintln!("unknown variant {}", variant_name);
+        return ExitCode::FAILURE;
+    };
+    let format = if variant.name() == "chess" { BookFormat::Polyglot } else { BookFormat::Variant };
+
+    let contents = match std::fs::read_to_string(pgn_path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("could not read {}: {}", pgn_path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let games = match parse_pgn(&contents) {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("could not parse {}: {:?}", pgn_path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut builder = BookBuilder::new(format, variant.catalog().clone()).with_plies(plies);
+    for (idx, game) in games.iter().enumerate() {
+        // a bad game only loses its moves from the first bad one on.
+        if let Err(e) = builder.add_game(game) {
+            eprintln!("game {}: {}", idx + 1, e);
+        }
+    }
+
+    let book = builder.build();
+    if let Err(e) = std::fs::write(book_path, book.to_bytes()) {
+        eprintln!("could not write {}: {}", book_path, e);
+        return ExitCode::FAILURE;
+    }
+    println!("{} games, {} entries written to {}", games.len(), book.len(), book_path);
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: book <games.pgn> <book.bin> [--plies n] [--variant name]");
+    ExitCode::FAILURE
+}
@@ -0,0 +1,10 @@
+// the engine over the xboard protocol on stdin and stdout.
+// usage: cecp
+
+use std::io;
+
+use chess_engine::cecp::Cecp;
+
+fn main() {
+    Cecp::new(io::stdout()).run(io::stdin().lock());
+}
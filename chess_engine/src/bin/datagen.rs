@@ -0,0 +1,121 @@
+// plays the engine against itself and writes positions for training a network.
+// usage: datagen <samples.txt> [--games n] [--nodes n | --depth n] [--random-plies n] [--seed n] [--variant name] [--network net.nnue]
+// samples are appended, so runs with different seeds can go in one file. train makes a network out of them.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Instant;
+
+use chess_engine::datagen::{DataGen, DEFAULT_NODES, DEFAULT_RANDOM_PLIES};
+use chess_engine::nnue::Network;
+use chess_engine::search::SearchLimits;
+use chess_engine::variant::builtin_variants;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut paths = Vec::new();
+    let mut games = 100;
+    let mut limits = SearchLimits::new().nodes(DEFAULT_NODES);
+    let mut random_plies = DEFAULT_RANDOM_PLIES;
+    let mut seed = 1;
+    let mut variant_name = "chess".to_string();
+    let mut network_path = None;
+
+    let mut idx = 0;
+    while idx < args.len() {
+        match args[idx].as_str() {
+            flag @ ("--games" | "--nodes" | "--depth" | "--random-plies" | "--seed") => {
+                let Some(n) = args.get(idx + 1).and_then(|n| n.parse::<u64>().ok()) else {
+                    return usage();
+                };
+                match flag {
+                    "--games" => games = n as usize,
+                    "--nodes" => limits = SearchLimits::new().nodes(n),
+                    "--depth" => limits = SearchLimits::new().depth(n as u32),
+                    "--random-plies" => random_plies = n as usize,
+                    _ => seed = n,
+                }
+                idx += 1;
+            }
+            "--variant" | "--network" => {
+                let Some(value) = args.get(idx + 1) else {
+                    return usage();
+                };
+                match args[idx].as_str() {
+                    "--variant" => variant_name = value.clone(),
+                    _ => network_path = Some(value.clone()),
+                }
+                idx += 1;
+            }
+            arg => paths.push(arg.to_string()),
+        }
+        idx += 1;
+    }
+    let [samples_path] = paths.as_slice() else {
+        return usage();
+    };
+
+    let Some(variant) = builtin_variants().into_iter().find(|v| v.name() == variant_name) else {
+        eprintln!("unknown variant {}", variant_name);
+        return ExitCode::FAILURE;
+    };
+    let network = match network_path {
+        Some(path) => match std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| Network::from_bytes(&bytes).map_err(|e| e.to_string()))
+        {
+            Ok(network) => Some(Arc::new(network)),
+            Err(e) => {
+                eprintln!("could not read {}: {}", path, e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+
+    let mut out = match OpenOptions::new().create(true).append(true).open(samples_path) {
+        Ok(out) => out,
+        Err(e) => {
+            eprintln!("could not open {}: {}", samples_path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let start = Instant::now();
+    let datagen = DataGen::new(variant)
+        .with_games(games)
+        .with_limits(limits)
+        .with_random_plies(random_plies)
+        .with_seed(seed)
+        .with_network(network);
+    let mut written = 0;
+    let mut write_error = None;
+    let result = datagen.run(|round, samples| {
+        let lines: String = samples.iter().map(|sample| sample.to_line() + "\n").collect();
+        if let Err(e) = out.write_all(lines.as_bytes()) {
+            write_error = Some(e);
+            return false;
+        }
+        written += samples.len();
+        println!("game {}/{}: {} samples, {} in total", round + 1, games, samples.len(), written);
+        true
+    });
+
+    if let Some(e) = write_error {
+        eprintln!("could not write {}: {}", samples_path, e);
+        return ExitCode::FAILURE;
+    }
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+    println!("{} samples written to {} in {:.2?}", written, samples_path, start.elapsed());
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: datagen <samples.txt> [--games n] [--nodes n | --depth n] [--random-plies n] [--seed n] [--variant name] [--network net.nnue]");
+    ExitCode::FAILURE
+}
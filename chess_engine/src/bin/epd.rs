@@ -0,0 +1,129 @@
+// runs an epd test suite through the search and reports how many positions were solved.
+// usage: epd <file> [--depth n] [--movetime ms]
+// with only a move time the search goes as deep as it gets in that time, without either it's depth 3.
+
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use chess_engine::epd::parse_epd;
+use chess_engine::search::{search, SearchLimits};
+
+const DEFAULT_DEPTH: u32 = 3;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut path = None;
+    let mut depth = None;
+    let mut movetime = None;
+
+    let mut idx = 0;
+    while idx < args.len() {
+        match args[idx].as_str() {
+            "--depth" => {
+                idx += 1;
+                match args.get(idx).and_then(|d| d.parse().ok()) {
+                    Some(d) => depth = Some(d),
+                    None => return usage(),
+                }
+            }
+            "--movetime" => {
+                idx += 1;
+                match args.get(idx).and_then(|ms| ms.parse().ok()) {
+                    Some(ms) => movetime = Some(Duration::from_millis(ms)),
+                    None => return usage(),
+                }
+            }
+            arg if path.is_none() => path = Some(arg.to_string()),
+            _ => return usage(),
+        }
+        idx += 1;
+    }
+    let Some(path) = path else {
+        return usage();
+    };
+    let limits = SearchLimits {
+        depth: depth.or(movetime.is_none().then_some(DEFAULT_DEPTH)),
+        movetime,
+        ..SearchLimits::new()
+    };
+
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("could not read {}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let positions = match parse_epd(&contents) {
+        Ok(positions) => positions,
+        Err(e) => {
+            eprintln!("could not parse {}: {:?}", path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let suite_start = Instant::now();
+    let mut solved = 0;
+
+    for (idx, position) in positions.iter().enumerate() {
+        let id = position.id().map_or(format!("#{}", idx + 1), |id| id.to_string());
+        let start = Instant::now();
+
+        let outcome = position.game().and_then(|mut game| {
+            let result = search(&mut game, limits)?;
+            let Some(best_move) = result.best_move else {
+                return Ok(("(none)".to_string(), false, result.nodes));
+            };
+            let san = game.move_to_san(&best_move)?;
+            let ok = position.is_solved_by(&game, &best_move)?;
+            Ok((san, ok, result.nodes))
+        });
+
+        match outcome {
+            Ok((san, ok, nodes)) => {
+                if ok {
+                    solved += 1;
+                }
+                let mut expected = Vec::new();
+                if !position.best_moves().is_empty() {
+                    expected.push(format!("bm {}", position.best_moves().join(" ")));
+                }
+                if !position.avoid_moves().is_empty() {
+                    expected.push(format!("am {}", position.avoid_moves().join(" ")));
+                }
+                println!(
+                    "{:<4} {} played {} ({}) {} nodes in {:.2?}",
+                    if ok { "ok" } else { "FAIL" },
+                    id,
+                    san,
+                    expected.join(", "),
+                    nodes,
+                    start.elapsed(),
+                );
+            }
+            Err(e) => println!("ERR  {} {:?}", id, e),
+        }
+    }
+
+    let mut limited = Vec::new();
+    if let Some(depth) = limits.depth {
+        limited.push(format!("depth {}", depth));
+    }
+    if let Some(movetime) = limits.movetime {
+        limited.push(format!("{:?} a move", movetime));
+    }
+    println!(
+        "solved {}/{} at {} in {:.2?}",
+        solved,
+        positions.len(),
+        limited.join(", "),
+        suite_start.elapsed()
+    );
+
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: epd <file> [--depth n] [--movetime ms]");
+    ExitCode::FAILURE
+}
@@ -0,0 +1,210 @@
+// plays two uci engines against each other and reports how much better the first one is.
+// usage: selfplay --engine <cmd> [name=x] [option.Name=value...] --engine <cmd> [...]
+//        [--games n] [--concurrency n] [--openings file] [--variant name]
+//        [--depth n | --nodes n | --movetime ms | --tc seconds+increment]
+//        [--no-adjudication] [--sprt elo0 elo1] [--pgn file]
+
+use std::fs::File;
+use std::io::Write;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use chess_engine::selfplay::{parse_openings, run_match, EngineConfig, MatchConfig, MatchLimit, Sprt, SprtVerdict};
+use chess_engine::variant::builtin_variants;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut engines: Vec<EngineConfig> = Vec::new();
+    let mut games = 2;
+    let mut concurrency = 1;
+    let mut openings_path = None;
+    let mut variant_name = "chess".to_string();
+    let mut limit = MatchLimit::Depth(4);
+    let mut adjudicate = true;
+    let mut sprt = None;
+    let mut pgn_path = None;
+
+    let mut idx = 0;
+    while idx < args.len() {
+        let value = |at: usize| args.get(at).map(String::as_str);
+        match args[idx].as_str() {
+            "--engine" => {
+                let Some(command) = value(idx + 1) else {
+                    return usage();
+                };
+                let mut engine = EngineConfig::new(command);
+                idx += 1;
+                // name=x and option.Name=value until the next flag.
+                while let Some((key, setting)) = value(idx + 1)
+                    .filter(|arg| !arg.starts_with("--"))
+                    .and_then(|arg| arg.split_once('='))
+                {
+                    engine = match key.strip_prefix("option.") {
+                        Some(option) => engine.with_option(option, setting),
+                        None if key == "name" => engine.with_name(setting),
+                        None => return usage(),
+                    };
+                    idx += 1;
+                }
+                engines.push(engine);
+            }
+            "--games" | "--concurrency" | "--depth" | "--nodes" | "--movetime" => {
+                let Some(n) = value(idx + 1).and_then(|n| n.parse::<u64>().ok()) else {
+                    return usage();
+                };
+                match args[idx].as_str() {
+                    "--games" => games = n as usize,
+                    "--concurrency" => concurrency = n as usize,
+                    "--depth" => limit = MatchLimit::Depth(n as u32),
+                    "--nodes" => limit = MatchLimit::Nodes(n),
+                    _ => limit = MatchLimit::MoveTime(Duration::from_millis(n)),
+                }
+                idx += 1;
+            }
+            "--tc" => {
+                let clock = value(idx + 1).and_then(|tc| {
+                    let (base, increment) = tc.split_once('+').unwrap_or((tc, "0"));
+                    Some(MatchLimit::Clock {
+                        base: Duration::from_secs_f64(base.parse().ok()?),
+                        increment: Duration::from_secs_f64(increment.parse().ok()?),
+                    })
+                });
+                match clock {
+                    Some(clock) => limit = clock,
+                    None => return usage(),
+                }
+                idx += 1;
+            }
+            "--openings" | "--variant" | "--pgn" => {
+                let Some(path) = value(idx + 1) else {
+                    return usage();
+                };
+                match args[idx].as_str() {
+                    "--openings" => openings_path = Some(path.to_string()),
+                    "--variant" => variant_name = path.to_string(),
+                    _ => pgn_path = Some(path.to_string()),
+                }
+                idx += 1;
+            }
+            "--sprt" => {
+                match (value(idx + 1).and_then(|e| e.parse().ok()), value(idx + 2).and_then(|e| e.parse().ok())) {
+                    (Some(elo0), Some(elo1)) => sprt = Some(Sprt::new(elo0, elo1)),
+                    _ => return usage(),
+                }
+                idx += 2;
+            }
+            "--no-adjudication" => adjudicate = false,
+            _ => return usage(),
+        }
+        idx += 1;
+    }
+    let [first, second] = engines.as_slice() else {
+        return usage();
+    };
+
+    let Some(variant) = builtin_variants().into_iter().find(|v| v.name() == variant_name) else {
+        eprintln!("unknown variant {}", variant_name);
+        return ExitCode::FAILURE;
+    };
+    let mut openings = Vec::new();
+    if let Some(path) = openings_path {
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| parse_openings(&contents, &variant).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(parsed) => openings = parsed,
+            Err(e) => {
+                eprintln!("could not read openings from {}: {}", path, e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let mut pgn_file = match pgn_path.as_ref().map(File::create).transpose() {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("could not create {}: {}", pgn_path.unwrap(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut config = MatchConfig::new(first.clone(), second.clone())
+        .with_variant(variant)
+        .with_openings(openings)
+        .with_games(games)
+        .with_concurrency(concurrency)
+        .with_limit(limit);
+    if !adjudicate {
+        config = config.with_adjudication(None);
+    }
+
+    let start = Instant::now();
+    let outcome = run_match(&config, |game, stats| {
+        let (elo, margin) = stats.elo();
+        let mut line = format!(
+            "game {}/{}: {} vs {} {} ({}) | +{} -{} ={} | elo {:.1} +/- {:.1}",
+            stats.games(),
+            games,
+            game.pgn.tag("White").unwrap_or("?"),
+            game.pgn.tag("Black").unwrap_or("?"),
+            game.pgn.result,
+            game.reason,
+            stats.wins,
+            stats.losses,
+            stats.draws,
+            elo,
+            margin
+        );
+        if let Some(sprt) = &sprt {
+            let (lower, upper) = sprt.bounds();
+            line += &format!(" | llr {:.2} ({:.2}, {:.2})", sprt.llr(stats), lower, upper);
+        }
+        println!("{}", line);
+
+        if let Some(file) = pgn_file.as_mut() {
+            if let Err(e) = writeln!(file, "{}", game.pgn.to_pgn()) {
+                eprintln!("could not write the game: {}", e);
+            }
+        }
+        sprt.is_none_or(|sprt| sprt.verdict(stats).is_none())
+    });
+
+    let stats = match outcome {
+        Ok(stats) => stats,
+        Err(e) => {
+            eprintln!("the match stopped: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let (elo, margin) = stats.elo();
+    println!(
+        "{} vs {}: +{} -{} ={} in {:.2?}, score {:.1}%, elo {:.1} +/- {:.1}",
+        first.name(),
+        second.name(),
+        stats.wins,
+        stats.losses,
+        stats.draws,
+        start.elapsed(),
+        stats.score() * 100.0,
+        elo,
+        margin
+    );
+    if let Some(sprt) = sprt {
+        let verdict = match sprt.verdict(&stats) {
+            Some(SprtVerdict::H0) => format!("H0 accepted, {} is not {} elo better", first.name(), sprt.elo1),
+            Some(SprtVerdict::H1) => format!("H1 accepted, {} is {} elo better", first.name(), sprt.elo1),
+            None => "inconclusive".to_string(),
+        };
+        println!("sprt [{}, {}] llr {:.2}: {}", sprt.elo0, sprt.elo1, sprt.llr(&stats), verdict);
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!(
+        "usage: selfplay --engine <cmd> [name=x] [option.Name=value...] --engine <cmd> [...] [--games n] \
+         [--concurrency n] [--openings file] [--variant name] [--depth n | --nodes n | --movetime ms | \
+         --tc seconds+increment] [--no-adjudication] [--sprt elo0 elo1] [--pgn file]"
+    );
+    ExitCode::FAILURE
+}
@@ -0,0 +1,65 @@
+// generates the endgame tables for some material on a board size, and every smaller one it needs.
+// usage: tablebase <material> <width>x<height> <out> [--variant name]
+// ex. tablebase KQvK 8x8 kqk.tb, or tablebase KAvK 10x8 kak.tb --variant capablanca
+
+use std::process::ExitCode;
+use std::time::Instant;
+
+use chess_engine::tablebase::Tablebases;
+use chess_engine::variant::builtin_variants;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut positional = Vec::new();
+    let mut variant_name = "chess".to_string();
+
+    let mut idx = 0;
+    while idx < args.len() {
+        match args[idx].as_str() {
+            "--variant" => {
+                idx += 1;
+                match args.get(idx) {
+                    Some(name) => variant_name = name.clone(),
+                    None => return usage(),
+                }
+            }
+            arg => positional.push(arg.to_string()),
+        }
+        idx += 1;
+    }
+    let [material, size, path] = positional.as_slice() else {
+        return usage();
+    };
+    let Some((width, height)) = size
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?)))
+    else {
+        return usage();
+    };
+    let Some(variant) = builtin_variants().into_iter().find(|v| v.name() == variant_name) else {
+        eprintln!("unknown variant {}", variant_name);
+        return ExitCode::FAILURE;
+    };
+
+    let start = Instant::now();
+    let mut tablebases = Tablebases::new();
+    if let Err(e) = tablebases.generate(material, width, height, variant.catalog()) {
+        eprintln!("could not generate {}: {}", material, e);
+        return ExitCode::FAILURE;
+    }
+    for table in tablebases.tables() {
+        println!("{} on {}x{}: {} positions", table.material(), table.width(), table.height(), table.len());
+    }
+
+    if let Err(e) = std::fs::write(path, tablebases.to_bytes()) {
+        eprintln!("could not write {}: {}", path, e);
+        return ExitCode::FAILURE;
+    }
+    println!("written to {} in {:.2?}", path, start.elapsed());
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: tablebase <material> <width>x<height> <out> [--variant name]");
+    ExitCode::FAILURE
+}
@@ -0,0 +1,111 @@
+// trains a network on samples from datagen.
+// usage: train <samples.txt> <net.nnue> [--epochs n] [--hidden n] [--lambda f] [--seed n] [--variant name]
+// the network it writes is loaded with the uci NNUEFile option.
+
+use std::process::ExitCode;
+use std::time::Instant;
+
+use chess_engine::datagen::parse_samples;
+use chess_engine::nnue::{Trainer, DEFAULT_HIDDEN, DEFAULT_LAMBDA};
+use chess_engine::variant::builtin_variants;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut paths = Vec::new();
+    let mut epochs = 20;
+    let mut hidden = DEFAULT_HIDDEN;
+    let mut lambda = DEFAULT_LAMBDA;
+    let mut seed = None;
+    let mut variant_name = "chess".to_string();
+
+    let mut idx = 0;
+    while idx < args.len() {
+        match args[idx].as_str() {
+            flag @ ("--epochs" | "--hidden" | "--seed") => {
+                let Some(n) = args.get(idx + 1).and_then(|n| n.parse::<u64>().ok()) else {
+                    return usage();
+                };
+                match flag {
+                    "--epochs" => epochs = n as usize,
+                    "--hidden" => hidden = n as usize,
+                    _ => seed = Some(n),
+                }
+                idx += 1;
+            }
+            "--lambda" => {
+                idx += 1;
+                match args.get(idx).and_then(|f| f.parse().ok()) {
+                    Some(f) => lambda = f,
+                    None => return usage(),
+                }
+            }
+            "--variant" => {
+                idx += 1;
+                match args.get(idx) {
+                    Some(name) => variant_name = name.clone(),
+                    None => return usage(),
+                }
+            }
+            arg => paths.push(arg.to_string()),
+        }
+        idx += 1;
+    }
+    let [samples_path, network_path] = paths.as_slice() else {
+        return usage();
+    };
+
+    let Some(variant) = builtin_variants().into_iter().find(|v| v.name() == variant_name) else {
+        eprintln!("unknown variant {}", variant_name);
+        return ExitCode::FAILURE;
+    };
+    let mut trainer = match Trainer::new(&variant, hidden) {
+        Ok(trainer) => trainer.with_lambda(lambda),
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(seed) = seed {
+        trainer = trainer.with_seed(seed);
+    }
+
+    let samples = match std::fs::read_to_string(samples_path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| parse_samples(&text).map_err(|e| e.to_string()))
+    {
+        Ok(samples) => samples,
+        Err(e) => {
+            eprintln!("could not read {}: {}", samples_path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    for (idx, sample) in samples.iter().enumerate() {
+        if let Err(e) = trainer.add_sample(sample) {
+            eprintln!("sample {}: {}", idx + 1, e);
+        }
+    }
+    if trainer.is_empty() {
+        eprintln!("no samples in {}", samples_path);
+        return ExitCode::FAILURE;
+    }
+
+    let start = Instant::now();
+    println!("{} samples, error {:.6}", trainer.len(), trainer.error());
+    for epoch in 0..epochs {
+        let error = trainer.train(1);
+        println!("epoch {}: error {:.6}", epoch + 1, error);
+    }
+    println!("trained in {:.2?}", start.elapsed());
+
+    if let Err(e) = std::fs::write(network_path, trainer.network().to_bytes()) {
+        eprintln!("could not write {}: {}", network_path, e);
+        return ExitCode::FAILURE;
+    }
+    println!("written to {}", network_path);
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: train <samples.txt> <net.nnue> [--epochs n] [--hidden n] [--lambda f] [--seed n] [--variant name]");
+    ExitCode::FAILURE
+}
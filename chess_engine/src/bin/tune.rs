@@ -0,0 +1,116 @@
+// tunes the evaluation weights to the results of a pgn collection, ex. from selfplay.
+// usage: tune <games.pgn> <params.txt> [--iterations n] [--start params.txt] [--skip plies] [--variant name]
+// the parameters it writes are loaded with the uci EvalFile option.
+
+use std::process::ExitCode;
+use std::time::Instant;
+
+use chess::pgn::parse_pgn;
+use chess_engine::eval::EvalParams;
+use chess_engine::tune::{Tuner, DEFAULT_SKIP_PLIES};
+use chess_engine::variant::builtin_variants;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut paths = Vec::new();
+    let mut iterations = 1000;
+    let mut start_path = None;
+    let mut skip_plies = DEFAULT_SKIP_PLIES;
+    let mut variant_name = "chess".to_string();
+
+    let mut idx = 0;
+    while idx < args.len() {
+        match args[idx].as_str() {
+            "--iterations" | "--skip" => {
+                let Some(n) = args.get(idx + 1).and_then(|n| n.parse().ok()) else {
+                    return usage();
+                };
+                match args[idx].as_str() {
+                    "--iterations" => iterations = n,
+                    _ => skip_plies = n,
+                }
+                idx += 1;
+            }
+            "--start" => {
+                idx += 1;
+                match args.get(idx) {
+                    Some(path) => start_path = Some(path.clone()),
+                    None => return usage(),
+                }
+            }
+            "--variant" => {
+                idx += 1;
+                match args.get(idx) {
+                    Some(name) => variant_name = name.clone(),
+                    None => return usage(),
+                }
+            }
+            arg => paths.push(arg.to_string()),
+        }
+        idx += 1;
+    }
+    let [pgn_path, params_path] = paths.as_slice() else {
+        return usage();
+    };
+
+    let Some(variant) = builtin_variants().into_iter().find(|v| v.name() == variant_name) else {
+        eprintln!("unknown variant {}", variant_name);
+        return ExitCode::FAILURE;
+    };
+    let mut tuner = Tuner::new(variant.catalog().clone()).with_skip_plies(skip_plies);
+    if let Some(path) = start_path {
+        let params = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| EvalParams::parse(&text).map_err(|e| e.to_string()));
+        match params {
+            Ok(params) => tuner = tuner.with_params(&params),
+            Err(e) => {
+                eprintln!("could not read {}: {}", path, e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let contents = match std::fs::read_to_string(pgn_path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("could not read {}: {}", pgn_path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let games = match parse_pgn(&contents) {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("could not parse {}: {:?}", pgn_path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    for (idx, game) in games.iter().enumerate() {
+        // positions before a bad move are kept.
+        if let Err(e) = tuner.add_game(game) {
+            eprintln!("game {}: {}", idx + 1, e);
+        }
+    }
+    if tuner.is_empty() {
+        eprintln!("no quiet positions from finished games in {}", pgn_path);
+        return ExitCode::FAILURE;
+    }
+
+    let start = Instant::now();
+    let scale = tuner.fit_scale();
+    println!("{} positions from {} games, scale {:.3}, error {:.6}", tuner.len(), games.len(), scale, tuner.error());
+    let error = tuner.tune(iterations);
+    println!("error {:.6} after {} iterations in {:.2?}", error, iterations, start.elapsed());
+
+    if let Err(e) = std::fs::write(params_path, tuner.params().to_text()) {
+        eprintln!("could not write {}: {}", params_path, e);
+        return ExitCode::FAILURE;
+    }
+    println!("written to {}", params_path);
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: tune <games.pgn> <params.txt> [--iterations n] [--start params.txt] [--skip plies] [--variant name]");
+    ExitCode::FAILURE
+}
@@ -0,0 +1,10 @@
+// the engine over uci on stdin and stdout.
+// usage: uci
+
+use std::io;
+
+use chess_engine::uci::Uci;
+
+fn main() {
+    Uci::new(io::stdout()).run(io::stdin().lock());
+}
@@ -0,0 +1,300 @@
+// opening books: moves to play in known positions, each with a weight for how often to pick it.
+// polyglot books are the standard .bin files for standard chess, 16 bytes per entry: the
+// polyglot key, the move, the weight and a learn value, big endian and sorted by key.
+// variant books are laid out the same way for any board, keyed by our own position hash and with
+// room for bigger boards in the move: 18 bytes per entry, the move taking 4.
+
+use std::collections::HashMap;
+
+use chess::error::{ChessError, Result};
+use chess::game::Game;
+use chess::pgn::PgnGame;
+use chess::piece::Piece;
+use chess::r#move::Move;
+
+use crate::polyglot::polyglot_key;
+
+// the moves of a game that go into a book.
+pub const DEFAULT_BOOK_PLIES: usize = 20;
+// polyglot's promotion pieces, a move without one is 0.
+const POLYGLOT_PROMOTIONS: &str = " nbrq";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BookFormat {
+    Polyglot,
+    Variant,
+}
+
+impl BookFormat {
+    fn entry_size(&self) -> usize {
+        match self {
+            BookFormat::Polyglot => 16,
+            BookFormat::Variant => 18,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    // polyglot moves only use the lower 16 bits.
+    pub mv: u32,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Book {
+    format: BookFormat,
+    // sorted by key.
+    entries: Vec<BookEntry>,
+}
+
+fn book_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+impl Book {
+    pub fn new(format: BookFormat) -> Self {
+        Self {
+            format,
+            entries: Vec::new(),
+        }
+    }
+
+    /// reads a book file's contents.
+    pub fn from_bytes(bytes: &[u8], format: BookFormat) -> Result<Self> {
+        let size = format.entry_size();
+        if !bytes.len().is_multiple_of(size) {
+            return Err(book_error(format!(
+                "A book is made of {} byte entries, {} bytes doesn't fit.",
+                size,
+                bytes.len()
+            )));
+        }
+
+        let mut entries: Vec<BookEntry> = bytes
+            .chunks_exact(size)
+            .map(|chunk| {
+                let key = u64::from_be_bytes(chunk[0..8].try_into().unwrap());
+                match format {
+                    BookFormat::Polyglot => BookEntry {
+                        key,
+                        mv: u16::from_be_bytes(chunk[8..10].try_into().unwrap()) as u32,
+                        weight: u16::from_be_bytes(chunk[10..12].try_into().unwrap()),
+                        learn: u32::from_be_bytes(chunk[12..16].try_into().unwrap()),
+                    },
+                    BookFormat::Variant => BookEntry {
+                        key,
+                        mv: u32::from_be_bytes(chunk[8..12].try_into().unwrap()),
+                        weight: u16::from_be_bytes(chunk[12..14].try_into().unwrap()),
+                        learn: u32::from_be_bytes(chunk[14..18].try_into().unwrap()),
+                    },
+                }
+            })
+            .collect();
+        // should be already, but a badly sorted book would miss moves.
+        entries.sort_by_key(|entry| entry.key);
+
+        Ok(Self { format, entries })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.entries.len() * self.format.entry_size());
+        for entry in &self.entries {
+            bytes.extend(entry.key.to_be_bytes());
+            match self.format {
+                BookFormat::Polyglot => bytes.extend((entry.mv as u16).to_be_bytes()),
+                BookFormat::Variant => bytes.extend(entry.mv.to_be_bytes()),
+            }
+            bytes.extend(entry.weight.to_be_bytes());
+            bytes.extend(entry.learn.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn format(&self) -> BookFormat {
+        self.format
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// the key the position is stored under.
+    pub fn key(&self, game: &Game) -> Result<u64> {
+        match self.format {
+            BookFormat::Polyglot => polyglot_key(game),
+            BookFormat::Variant => Ok(game.hash()),
+        }
+    }
+
+    pub fn entries(&self, game: &Game) -> Result<&[BookEntry]> {
+        let key = self.key(game)?;
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+        Ok(&self.entries[start..end])
+    }
+
+    /// the legal moves the book has for the position, with their weights.
+    /// entries for moves that aren't legal are left out.
+    pub fn moves(&self, game: &Game) -> Result<Vec<(Move, u16)>> {
+        let entries = self.entries(game)?;
+        if entries.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let legal = game.legal_moves()?;
+        Ok(entries
+            .iter()
+            .filter_map(|entry| {
+                let m = legal.iter().find(|m| self.encode(game, m) == entry.mv)?;
+                Some((m.clone(), entry.weight))
+            })
+            .collect())
+    }
+
+    /// picks a move at random, weighted by how good the book thinks they are.
+    /// random is any number, ex. from the clock, the same one picks the same move.
+    pub fn pick(&self, game: &Game, random: u64) -> Result<Option<Move>> {
+        let moves = self.moves(game)?;
+        let total: u64 = moves.iter().map(|(_, weight)| *weight as u64).sum();
+        if total == 0 {
+            return Ok(None);
+        }
+
+        let mut left = random % total;
+        for (m, weight) in moves {
+            if left < weight as u64 {
+                return Ok(Some(m));
+            }
+            left -= weight as u64;
+        }
+        unreachable!()
+    }
+
+    /// adds weight to a move, or puts it in the book.
+    pub fn add(&mut self, game: &Game, m: &Move, weight: u16) -> Result<()> {
+        let key = self.key(game)?;
+        let mv = self.encode(game, m);
+
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+        match self.entries[start..end].iter_mut().find(|entry| entry.mv == mv) {
+            Some(entry) => entry.weight = entry.weight.saturating_add(weight),
+            None => self.entries.insert(
+                end,
+                BookEntry {
+                    key,
+                    mv,
+                    weight,
+                    learn: 0,
+                },
+            ),
+        }
+        Ok(())
+    }
+
+    /// the move as the book stores it.
+    /// polyglot castles by moving the king onto its rook, variant books keep our own move.
+    pub fn encode(&self, game: &Game, m: &Move) -> u32 {
+        let promotion = m.promotion().map_or(0, |idx| idx as u32 + 1);
+        match self.format {
+            BookFormat::Polyglot => {
+                let to = m.castle().map_or(m.to(), |partner| partner.from());
+                let promotion = m
+                    .promotion()
+                    .and_then(|idx| {
+                        let piece = m.piece();
+                        let lock = piece.read().unwrap();
+                        let symbol = lock.promotions().get(idx as usize)?.symbol().to_ascii_lowercase();
+                        POLYGLOT_PROMOTIONS.find(symbol)
+                    })
+                    .unwrap_or(0) as u32;
+                to.x() | to.y() << 3 | m.from().x() << 6 | m.from().y() << 9 | promotion << 12
+            }
+            BookFormat::Variant => {
+                let width = game.board().width();
+                let index = |x: u32, y: u32| (y * width + x) & 0xfff;
+                index(m.to().x(), m.to().y()) | index(m.from().x(), m.from().y()) << 12 | promotion << 24
+            }
+        }
+    }
+}
+
+/// builds a book out of games, counting the moves of the first plies of each.
+/// a move gets 2 for every win and 1 for every draw of the team that played it, and moves that
+/// only ever lost are left out.
+pub struct BookBuilder {
+    format: BookFormat,
+    catalog: Vec<Piece>,
+    plies: usize,
+    // (key, move) -> weight, before it's scaled to fit.
+    weights: HashMap<(u64, u32), u64>,
+}
+
+impl BookBuilder {
+    pub fn new(format: BookFormat, catalog: Vec<Piece>) -> Self {
+        Self {
+            format,
+            catalog,
+            plies: DEFAULT_BOOK_PLIES,
+            weights: HashMap::new(),
+        }
+    }
+
+    pub fn with_plies(mut self, plies: usize) -> Self {
+        self.plies = plies;
+        self
+    }
+
+    /// stops at the first move that isn't legal, keeping what came before.
+    pub fn add_game(&mut self, pgn: &PgnGame) -> Result<()> {
+        let book = Book::new(self.format);
+        let mut game = pgn.start_with(&self.catalog)?;
+        let first_team = game.current_move() as usize % 2;
+
+        for (ply, san) in pgn.moves.iter().take(self.plies).enumerate() {
+            let m = game.parse_san(san)?;
+            let team = (first_team + ply) % 2;
+            let points = match (pgn.result.as_str(), team) {
+                ("1-0", 0) | ("0-1", 1) => 2,
+                ("1/2-1/2", _) => 1,
+                _ => 0,
+            };
+
+            let key = book.key(&game)?;
+            *self.weights.entry((key, book.encode(&game, &m))).or_default() += points;
+            game.execute_move(&m)?;
+        }
+        Ok(())
+    }
+
+    /// weights are scaled down to fit if they have to.
+    pub fn build(&self) -> Book {
+        let max = self.weights.values().copied().max().unwrap_or(0);
+        let scale = (max as f64 / u16::MAX as f64).max(1.0);
+
+        let mut entries: Vec<BookEntry> = self
+            .weights
+            .iter()
+            .filter(|(_, weight)| **weight > 0)
+            .map(|((key, mv), weight)| BookEntry {
+                key: *key,
+                mv: *mv,
+                weight: ((*weight as f64 / scale) as u16).max(1),
+                learn: 0,
+            })
+            .collect();
+        entries.sort_by_key(|entry| (entry.key, std::cmp::Reverse(entry.weight), entry.mv));
+
+        Book {
+            format: self.format,
+            entries,
+        }
+    }
+}
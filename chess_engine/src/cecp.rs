@@ -0,0 +1,389 @@
+// the xboard protocol (cecp), for winboard compatible guis.
+// like uci, searches run in the background (see controller.rs), so ? gets answered while the
+// engine thinks. ping waits for the engine's move, so the pong comes after it. the engine's own
+// moves get played on the game once the search is done.
+// moves are in coordinate notation, the same as uci's.
+
+use std::io::{BufRead, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chess::game::Game;
+use chess::pgn::result_of;
+use chess::r#move::MoveKey;
+
+use crate::controller::{Controller, SearchEvent, SearchInfo};
+use crate::search::{is_mate_score, resolve_pv, SearchLimits, MATE};
+use crate::smp::SmpSearcher;
+use crate::time::TimeControl;
+use crate::tt::TranspositionTable;
+use crate::uci::ENGINE_NAME;
+use crate::variant::{builtin_variants, Variant};
+
+const MAX_HASH_MB: usize = 4096;
+const MAX_THREADS: usize = 256;
+// how long to think when nothing says otherwise.
+const DEFAULT_MOVE_TIME: Duration = Duration::from_secs(5);
+// the piece letters xboard knows, in the order of its setup command.
+const XBOARD_PIECES: &str = "PNBRQFEACWMOHIJGDVLSUK";
+
+pub struct Cecp<W: Write + Send + 'static> {
+    out: Arc<Mutex<W>>,
+    game: Game,
+    variants: Vec<Variant>,
+    variant: Variant,
+    // in force mode the engine only plays the moves it's told to.
+    force: bool,
+    // the team the engine moves for.
+    engine_side: usize,
+    post: bool,
+    depth: Option<u32>,
+    // moves per time control (0 for all of them), and the increment.
+    level: Option<(u32, Duration)>,
+    move_time: Option<Duration>,
+    // the engine's clock, from time.
+    remaining: Option<Duration>,
+    threads: usize,
+    controller: Controller,
+    // the move the engine sent, played on the game once its search is done.
+    played: Arc<Mutex<Option<MoveKey>>>,
+}
+
+impl<W: Write + Send + 'static> Cecp<W> {
+    pub fn new(out: W) -> Self {
+        let variants = builtin_variants();
+        Self {
+            out: Arc::new(Mutex::new(out)),
+            game: variants[0].start().unwrap(),
+            variant: variants[0].clone(),
+            variants,
+            force: false,
+            engine_side: 1,
+            post: false,
+            depth: None,
+            level: None,
+            move_time: None,
+            remaining: None,
+            threads: 1,
+            controller: Controller::new(SmpSearcher::new(SearchLimits::new(), 1)),
+            played: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// handles every line until quit or the end of the input.
+    pub fn run(&mut self, input: impl BufRead) {
+        for line in input.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if !self.handle(&line) {
+                return;
+            }
+        }
+        self.wait();
+    }
+
+    /// handles one command, false once it's time to quit.
+    pub fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = tokens.split_first() else {
+            return true;
+        };
+        let number = |idx: usize| args.get(idx).and_then(|value| value.parse::<u64>().ok());
+
+        match *command {
+            "xboard" => {}
+            "protover" => {
+                let variants: Vec<&str> = self.variants.iter().map(xboard_name).collect();
+                self.send(&format!(
+                    "feature myname=\"{}\" ping=1 setboard=1 usermove=1 san=0 time=1 draw=0 sigint=0 sigterm=0 \
+                     colors=0 analyze=0 memory=1 smp=1 variants=\"{}\"",
+                    ENGINE_NAME,
+                    variants.join(",")
+                ));
+                self.send("feature done=1");
+            }
+            "new" => {
+                self.cancel();
+                self.variant = self.variants[0].clone();
+                self.game = self.variant.start().unwrap();
+                self.force = false;
+                self.engine_side = 1;
+                self.depth = None;
+                self.searcher().table().clear();
+            }
+            "variant" => {
+                self.cancel();
+                let name = args.first().copied().unwrap_or_default();
+                match self.variants.iter().find(|variant| xboard_name(variant) == name) {
+                    Some(variant) => {
+                        self.variant = variant.clone();
+                        self.game = self.variant.start().unwrap();
+                        if name != "normal" {
+                            self.send_setup();
+                        }
+                    }
+                    None => self.send(&format!("Error (unknown variant): {}", name)),
+                }
+            }
+            "force" => {
+                self.cancel();
+                self.force = true;
+            }
+            "go" => {
+                self.wait();
+                self.force = false;
+                self.engine_side = self.side();
+                self.think();
+            }
+            "playother" => {
+                self.wait();
+                self.force = false;
+                self.engine_side = (self.side() + 1) % self.teams();
+            }
+            "usermove" => {
+                self.wait();
+                let uci = args.first().copied().unwrap_or_default();
+                match self.game.parse_uci(uci).and_then(|m| self.game.execute_move(&m)) {
+                    Ok(()) => match game_result(&self.game) {
+                        Some(result) => self.send(&result),
+                        None => {
+                            if !self.force && self.side() == self.engine_side {
+                                self.think();
+                            }
+                        }
+                    },
+                    Err(_) => self.send(&format!("Illegal move: {}", uci)),
+                }
+            }
+            "?" => {
+                self.controller.stop();
+                self.wait();
+            }
+            "undo" | "remove" => {
+                self.cancel();
+                let plies = if *command == "undo" { 1 } else { self.teams() };
+                for _ in 0..plies {
+                    if self.game.history().is_empty() || self.game.undo().is_err() {
+                        self.send(&format!("Error (nothing to undo): {}", command));
+                        break;
+                    }
+                }
+            }
+            "setboard" => {
+                self.cancel();
+                match self.variant.game(&args.join(" ")) {
+                    Ok(game) => self.game = game,
+                    Err(e) => self.send(&format!("tellusererror Illegal position: {}", e)),
+                }
+            }
+            // level <moves per control> <base> <increment>, the base in minutes or minutes:seconds.
+            "level" => match (number(0), args.get(1).and_then(|base| parse_base(base)), args.get(2)) {
+                (Some(moves), Some(base), Some(increment)) => {
+                    let increment = increment.parse::<f64>().unwrap_or_default().max(0.0);
+                    self.level = Some((moves as u32, Duration::from_secs_f64(increment)));
+                    self.remaining = Some(base);
+                    self.move_time = None;
+                }
+                _ => self.send(&format!("Error (bad level): {}", args.join(" "))),
+            },
+            "st" => self.move_time = number(0).map(Duration::from_secs),
+            "sd" => self.depth = number(0).map(|depth| depth as u32),
+            // the clocks are in centiseconds.
+            "time" => self.remaining = number(0).map(|centis| Duration::from_millis(centis * 10)),
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "ping" => {
+                self.wait();
+                self.send(&format!("pong {}", args.first().copied().unwrap_or_default()));
+            }
+            "memory" => {
+                self.wait();
+                if let Some(hash_mb) = number(0) {
+                    let tt = Arc::new(TranspositionTable::new((hash_mb as usize).clamp(1, MAX_HASH_MB)));
+                    self.controller.update_searcher(|searcher| searcher.with_table(tt));
+                }
+            }
+            "cores" => {
+                self.wait();
+                if let Some(threads) = number(0) {
+                    self.threads = (threads as usize).clamp(1, MAX_THREADS);
+                    let threads = self.threads;
+                    self.controller.update_searcher(|searcher| {
+                        SmpSearcher::new(SearchLimits::new(), threads).with_table(searcher.table().clone())
+                    });
+                }
+            }
+            "result" => {
+                self.cancel();
+                self.force = true;
+            }
+            "quit" => {
+                self.cancel();
+                return false;
+            }
+            // nothing to do for these.
+            "accepted" | "rejected" | "otim" | "hard" | "easy" | "random" | "computer" | "name" | "rating" | "ics"
+            | "white" | "black" | "draw" | "hint" | "bk" => {}
+            _ => self.send(&format!("Error (unknown command): {}", command)),
+        }
+
+        true
+    }
+
+    // searches for the engine's move in the background, it's sent once the search is done.
+    fn think(&mut self) {
+        let mut game = match self.game.deep_clone() {
+            Ok(game) => game,
+            Err(e) => {
+                self.send(&format!("Error (can't search): {}", e));
+                return;
+            }
+        };
+
+        let mut limits = SearchLimits::new();
+        limits.depth = self.depth;
+        let control = match (self.move_time, self.remaining) {
+            (Some(move_time), _) => Some(TimeControl::MoveTime(move_time)),
+            (None, Some(remaining)) => {
+                let (moves, increment) = self.level.unwrap_or_default();
+                // the moves the engine made so far in this time control.
+                let played = self.game.current_move() / self.teams() as u32;
+                Some(TimeControl::Clock {
+                    remaining,
+                    increment,
+                    moves_to_go: (moves > 0).then(|| moves - played % moves),
+                })
+            }
+            (None, None) if self.depth.is_none() => Some(TimeControl::MoveTime(DEFAULT_MOVE_TIME)),
+            (None, None) => None,
+        };
+
+        let out = self.out.clone();
+        let post = self.post;
+        let played = self.played.clone();
+        self.controller.set_on_event(Some(Box::new(move |event| {
+            let mut lines = Vec::new();
+            match event {
+                SearchEvent::Info(info) if post => lines.push(thinking(&mut game, &info)),
+                SearchEvent::Info(_) => {}
+                SearchEvent::BestMove { best_move, .. } => match resolve_pv(&mut game, best_move.as_slice()).pop() {
+                    Some(best_move) => {
+                        lines.push(format!("move {}", game.move_to_uci(&best_move)));
+                        if game.execute_move(&best_move).is_ok() {
+                            *played.lock().unwrap() = Some(best_move.key());
+                            lines.extend(game_result(&game));
+                        }
+                    }
+                    None => lines.extend(game_result(&game)),
+                },
+                SearchEvent::Error(e) => lines.push(format!("Error (search failed): {}", e)),
+            }
+
+            let mut out = out.lock().unwrap();
+            for line in lines {
+                let _ = writeln!(out, "{}", line);
+            }
+            let _ = out.flush();
+        })));
+        if let Err(e) = self.controller.go(&self.game, limits, control) {
+            self.send(&format!("Error (can't search): {}", e));
+        }
+    }
+
+    // stops a search without playing its move.
+    fn cancel(&mut self) {
+        self.controller.cancel();
+        self.wait();
+    }
+
+    // waits for a running search to finish, and plays the move it sent.
+    fn wait(&mut self) {
+        self.controller.wait();
+        if let Some(key) = self.played.lock().unwrap().take() {
+            self.game.play(key).unwrap();
+        }
+    }
+
+    // the variant's pieces in xboard's order, for guis that don't know it.
+    fn send_setup(&self) {
+        let symbols: Vec<char> = self.variant.catalog().iter().map(|p| p.read().unwrap().symbol()).collect();
+        let white: String = XBOARD_PIECES
+            .chars()
+            .map(|piece| if symbols.contains(&piece) { piece } else { '.' })
+            .collect();
+        let board = self.game.board();
+        self.send(&format!(
+            "setup ({}{}) {}x{}+0_{} {}",
+            white,
+            white.to_ascii_lowercase(),
+            board.width(),
+            board.height(),
+            self.variant.name(),
+            self.variant.start_fen()
+        ));
+    }
+
+    fn side(&self) -> usize {
+        self.game.current_move() as usize % self.teams()
+    }
+
+    fn teams(&self) -> usize {
+        self.game.board().piece_sets().len()
+    }
+
+    // between searches.
+    fn searcher(&mut self) -> &mut SmpSearcher {
+        self.wait();
+        self.controller.searcher().unwrap()
+    }
+
+    fn send(&self, line: &str) {
+        let mut out = self.out.lock().unwrap();
+        let _ = writeln!(out, "{}", line);
+        let _ = out.flush();
+    }
+}
+
+// xboard calls chess normal.
+fn xboard_name(variant: &Variant) -> &str {
+    match variant.name() {
+        "chess" => "normal",
+        name => name,
+    }
+}
+
+// 5 or 0:30
+fn parse_base(base: &str) -> Option<Duration> {
+    let (minutes, seconds) = base.split_once(':').unwrap_or((base, "0"));
+    let seconds = minutes.parse::<u64>().ok()? * 60 + seconds.parse::<u64>().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// how the game ended, if it did, ex. 1-0 {White mates}.
+fn game_result(game: &Game) -> Option<String> {
+    result_of(game).map(|(result, reason)| format!("{} {{{}}}", result, reason))
+}
+
+/// the score in centipawns, or 100000 + the moves until a mate, negative when getting mated.
+pub fn score_to_cecp(score: i32) -> i32 {
+    if is_mate_score(score) {
+        let moves = (MATE - score.abs() + 1) / 2;
+        score.signum() * (100000 + moves)
+    } else {
+        score
+    }
+}
+
+// <depth> <score> <centiseconds> <nodes> <pv>
+fn thinking(game: &mut Game, info: &SearchInfo) -> String {
+    let pv: Vec<String> = resolve_pv(game, &info.pv).iter().map(|m| game.move_to_uci(m)).collect();
+    format!(
+        "{} {} {} {} {}",
+        info.depth,
+        score_to_cecp(info.score),
+        info.elapsed.as_millis() / 10,
+        info.nodes,
+        pv.join(" ")
+    )
+}
@@ -0,0 +1,349 @@
+// a search in the background, for front ends that can't wait for it, ex. the app's render loop.
+// searches run on their own thread with a copy of the game, and what they find comes back over a
+// channel, picked up with poll between frames or waited for with recv. front ends that block on
+// something else, ex. uci and cecp reading their input, get it in a callback on the search's thread.
+// pondering searches the position after the reply the last search expects, without limits, while
+// the other side thinks. a ponderhit starts the real search with its clock running from then on,
+// the ponder search's work carried over through the shared transposition table. anything else
+// being played is just a new search, the ponder search is thrown away.
+// below full skill the best move it reports is picked from the lines found, see skill.rs.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use chess::error::Result;
+use chess::game::Game;
+use chess::r#move::MoveKey;
+
+use crate::search::{Iteration, SearchLimits};
+use crate::skill::Skill;
+use crate::smp::SmpSearcher;
+use crate::time::{TimeControl, TimeManager};
+
+/// a line of a finished iteration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchInfo {
+    pub depth: u32,
+    pub multipv: usize,
+    pub score: i32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub pv: Vec<MoveKey>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchEvent {
+    Info(SearchInfo),
+    /// the search is over. ponder is the reply it expects, none when it has no idea.
+    BestMove {
+        best_move: Option<MoveKey>,
+        ponder: Option<MoveKey>,
+        score: i32,
+        depth: u32,
+        nodes: u64,
+    },
+    Error(String),
+}
+
+/// gets the current search's events on its thread, see Controller::set_on_event.
+pub type OnEvent = Box<dyn FnMut(SearchEvent) + Send>;
+
+// which search is the current one, and where its events go. a search checks it's still the
+// current one with the lock held, so once a search is thrown away nothing more of it gets out.
+struct Listener {
+    id: u64,
+    on_event: Option<OnEvent>,
+}
+
+impl Listener {
+    fn send(&mut self, id: u64, event: SearchEvent, sender: &Sender<(u64, SearchEvent)>) {
+        if id != self.id {
+            return;
+        }
+        match &mut self.on_event {
+            Some(on_event) => on_event(event),
+            None => {
+                let _ = sender.send((id, event));
+            }
+        }
+    }
+}
+
+// a ponder search waiting for its ponderhit: the position after the reply, and how the real
+// search is limited.
+struct Ponder {
+    game: Game,
+    limits: SearchLimits,
+    time: Option<TimeControl>,
+}
+
+pub struct Controller {
+    // back from the search thread once it's done.
+    searcher: Option<SmpSearcher>,
+    search: Option<JoinHandle<SmpSearcher>>,
+    // the searcher's, cleared once it returns.
+    stop: Arc<AtomicBool>,
+    // set by stop and cancel until the next search, a ponder search waits for it to answer.
+    stop_requested: Arc<AtomicBool>,
+    // every search gets an id, events from searches that were thrown away are left out.
+    listener: Arc<Mutex<Listener>>,
+    sender: Sender<(u64, SearchEvent)>,
+    receiver: Receiver<(u64, SearchEvent)>,
+    ponder: Option<Ponder>,
+    skill: Skill,
+    multi_pv: usize,
+}
+
+impl Controller {
+    pub fn new(searcher: SmpSearcher) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            stop: searcher.stop_flag(),
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            searcher: Some(searcher),
+            search: None,
+            listener: Arc::new(Mutex::new(Listener { id: 0, on_event: None })),
+            sender,
+            receiver,
+            ponder: None,
+            skill: Skill::default(),
+            multi_pv: 1,
+        }
+    }
+
+    /// for the searches that start from now on.
+    pub fn set_skill(&mut self, skill: Skill) {
+        self.skill = skill;
+    }
+
+    pub fn skill(&self) -> Skill {
+        self.skill
+    }
+
+    /// how many lines to report, from the searches that start from now on.
+    pub fn set_multi_pv(&mut self, multi_pv: usize) {
+        self.multi_pv = multi_pv.max(1);
+    }
+
+    /// the searcher between searches, ex. to change its options. none while a search runs.
+    /// its limits and multi pv are set by every search.
+    pub fn searcher(&mut self) -> Option<&mut SmpSearcher> {
+        self.reap();
+        self.searcher.as_mut()
+    }
+
+    /// replaces the searcher with what update makes of it, ex. with another table or more threads.
+    /// waits for the search to be done first.
+    pub fn update_searcher(&mut self, update: impl FnOnce(SmpSearcher) -> SmpSearcher) {
+        self.wait();
+        let searcher = update(self.searcher.take().expect("the last search is done"));
+        self.stop = searcher.stop_flag();
+        self.searcher = Some(searcher);
+    }
+
+    /// the events go to on_event instead of the channel, none to go back to the channel.
+    /// a search that's running is thrown away.
+    pub fn set_on_event(&mut self, on_event: Option<OnEvent>) {
+        self.cancel();
+        self.listener.lock().unwrap().on_event = on_event;
+    }
+
+    /// starts searching a copy of the game. a search that's already running is thrown away.
+    pub fn go(&mut self, game: &Game, limits: SearchLimits, time: Option<TimeControl>) -> Result<()> {
+        self.cancel();
+        self.start(game.deep_clone()?, limits, time, false);
+        Ok(())
+    }
+
+    /// searches the position after reply until a ponderhit or a stop. limits and time are for the
+    /// real search, once the reply is played.
+    pub fn ponder(
+        &mut self,
+        game: &Game,
+        reply: MoveKey,
+        limits: SearchLimits,
+        time: Option<TimeControl>,
+    ) -> Result<()> {
+        let mut game = game.deep_clone()?;
+        game.play(reply)?;
+        self.ponder_after(&game, limits, time)
+    }
+
+    /// like ponder, with the reply already played on the game, ex. for uci's go ponder.
+    pub fn ponder_after(&mut self, game: &Game, limits: SearchLimits, time: Option<TimeControl>) -> Result<()> {
+        self.cancel();
+        self.start(game.deep_clone()?, SearchLimits::new(), None, true);
+        self.ponder = Some(Ponder {
+            game: game.deep_clone()?,
+            limits,
+            time,
+        });
+        Ok(())
+    }
+
+    /// searches without limits until a stop, which is only when it answers, ex. for analysis.
+    pub fn analyze(&mut self, game: &Game) -> Result<()> {
+        self.cancel();
+        self.start(game.deep_clone()?, SearchLimits::new(), None, true);
+        Ok(())
+    }
+
+    /// the reply that was pondered on was played, the real search takes over.
+    /// without a ponder search it does nothing.
+    pub fn ponderhit(&mut self) {
+        let Some(ponder) = self.ponder.take() else {
+            return;
+        };
+        self.cancel();
+        self.start(ponder.game, ponder.limits, ponder.time, false);
+    }
+
+    /// stops the search, which still reports its best move. a ponder search stops too, as if it
+    /// was the real one.
+    pub fn stop(&mut self) {
+        self.ponder = None;
+        self.stop.store(true, Ordering::Relaxed);
+        self.stop_requested.store(true, Ordering::Relaxed);
+    }
+
+    /// stops the search and throws away what it found, waiting for its thread to be done.
+    pub fn cancel(&mut self) {
+        self.ponder = None;
+        if self.search.is_some() {
+            // thrown away before it's told to stop, so a search waiting for that can't answer.
+            self.listener.lock().unwrap().id += 1;
+            self.stop.store(true, Ordering::Relaxed);
+            self.stop_requested.store(true, Ordering::Relaxed);
+            self.wait();
+        }
+    }
+
+    pub fn is_searching(&mut self) -> bool {
+        self.reap();
+        self.search.is_some()
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.ponder.is_some()
+    }
+
+    /// what the search found since the last look, without waiting.
+    pub fn poll(&mut self) -> Vec<SearchEvent> {
+        let current = self.id();
+        let events = self
+            .receiver
+            .try_iter()
+            .filter(|(id, _)| *id == current)
+            .map(|(_, event)| event)
+            .collect();
+        self.reap();
+        events
+    }
+
+    /// waits up to timeout for what the search finds next.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Option<SearchEvent> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.receiver.recv_timeout(left) {
+                Ok((id, event)) if id == self.id() => return Some(event),
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+
+    /// waits for the search's thread to be done. a ponder or analysis search only ends once it's
+    /// stopped.
+    pub fn wait(&mut self) {
+        if let Some(search) = self.search.take() {
+            match search.join() {
+                Ok(searcher) => self.searcher = Some(searcher),
+                Err(e) => std::panic::resume_unwind(e),
+            }
+        }
+    }
+
+    fn id(&self) -> u64 {
+        self.listener.lock().unwrap().id
+    }
+
+    // takes the searcher back from a thread that's done.
+    fn reap(&mut self) {
+        if self.search.as_ref().is_some_and(|search| search.is_finished()) {
+            self.wait();
+        }
+    }
+
+    // until_stopped searches only answer once they're stopped.
+    fn start(&mut self, mut game: Game, limits: SearchLimits, time: Option<TimeControl>, until_stopped: bool) {
+        let mut searcher = self.searcher.take().expect("the last search is done");
+        let id = {
+            let mut listener = self.listener.lock().unwrap();
+            listener.id += 1;
+            listener.id
+        };
+
+        let skill = self.skill;
+        let random = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64);
+        searcher.set_limits(skill.limits(limits));
+        searcher.set_multi_pv(skill.multi_pv(self.multi_pv));
+        searcher.set_eval_noise(skill.noise(), random);
+        searcher.set_time_manager(time.map(TimeManager::new));
+        let (listener, sender) = (self.listener.clone(), self.sender.clone());
+        searcher.set_on_iteration(Some(Box::new(move |_: &Game, iteration: &Iteration| {
+            let info = SearchInfo {
+                depth: iteration.depth,
+                multipv: iteration.multipv,
+                score: iteration.score,
+                nodes: iteration.nodes,
+                elapsed: iteration.elapsed,
+                pv: iteration.pv.iter().map(|m| m.key()).collect(),
+            };
+            listener.lock().unwrap().send(id, SearchEvent::Info(info), &sender);
+        })));
+
+        self.stop = searcher.stop_flag();
+        self.stop.store(false, Ordering::Relaxed);
+        self.stop_requested.store(false, Ordering::Relaxed);
+        let stop_requested = self.stop_requested.clone();
+        let (listener, sender) = (self.listener.clone(), self.sender.clone());
+        self.search = Some(thread::spawn(move || {
+            let result = searcher.search(&mut game);
+
+            // even when it ran out of depth.
+            while until_stopped && !stop_requested.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+
+            let event = match result {
+                Ok(result) => {
+                    let (score, pv) = match skill.pick(&result.lines, random) {
+                        Some(line) if !skill.is_full_strength() => (line.score, &line.pv),
+                        _ => (result.score, &result.pv),
+                    };
+                    SearchEvent::BestMove {
+                        best_move: pv.first().map(|m| m.key()),
+                        ponder: pv.get(1).map(|m| m.key()),
+                        score,
+                        depth: result.depth,
+                        nodes: result.nodes,
+                    }
+                }
+                Err(e) => SearchEvent::Error(e.to_string()),
+            };
+            searcher.set_on_iteration(None);
+            listener.lock().unwrap().send(id, event, &sender);
+            searcher
+        }));
+    }
+}
+
+impl Drop for Controller {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
@@ -0,0 +1,189 @@
+// training data for the network, from the engine playing itself.
+// every game starts with a few random moves so the games differ, then both sides search with the
+// same limits until the game is over. positions where the search found a quiet move are kept,
+// one per line: <fen> | <score> | <result>, the score in centipoints from the search and the
+// result 1, 0.5 or 0 for how the game ended, both for the team to move.
+
+use std::sync::Arc;
+
+use chess::error::{ChessError, Result};
+use chess::pgn::result_of;
+
+use crate::nnue::Network;
+use crate::search::{is_mate_score, SearchLimits, Searcher};
+use crate::variant::Variant;
+
+pub const DEFAULT_RANDOM_PLIES: usize = 8;
+pub const DEFAULT_NODES: u64 = 5000;
+pub const DEFAULT_MAX_PLIES: usize = 400;
+
+fn datagen_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+/// a position, what the search thought of it and how the game ended.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub fen: String,
+    pub score: i32,
+    pub result: f64,
+}
+
+impl Sample {
+    pub fn parse(line: &str) -> Result<Self> {
+        let line_error = || datagen_error(format!("Expected <fen> | <score> | <result>, got {}.", line));
+        let [fen, score, result] = line.split('|').map(str::trim).collect::<Vec<_>>()[..] else {
+            return Err(line_error());
+        };
+        let score = score.parse().map_err(|_| line_error())?;
+        let result = result
+            .parse()
+            .ok()
+            .filter(|result| (0.0..=1.0).contains(result))
+            .ok_or_else(line_error)?;
+        Ok(Self {
+            fen: fen.to_string(),
+            score,
+            result,
+        })
+    }
+
+    pub fn to_line(&self) -> String {
+        format!("{} | {} | {}", self.fen, self.score, self.result)
+    }
+}
+
+/// every sample in a file, skipping empty lines.
+pub fn parse_samples(text: &str) -> Result<Vec<Sample>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| Sample::parse(line).map_err(|e| datagen_error(format!("Line {}: {}", idx + 1, e))))
+        .collect()
+}
+
+pub struct DataGen {
+    variant: Variant,
+    games: usize,
+    limits: SearchLimits,
+    random_plies: usize,
+    max_plies: usize,
+    seed: u64,
+    network: Option<Arc<Network>>,
+}
+
+impl DataGen {
+    pub fn new(variant: Variant) -> Self {
+        Self {
+            variant,
+            games: 1,
+            limits: SearchLimits::new().nodes(DEFAULT_NODES),
+            random_plies: DEFAULT_RANDOM_PLIES,
+            max_plies: DEFAULT_MAX_PLIES,
+            seed: 1,
+            network: None,
+        }
+    }
+
+    pub fn with_games(mut self, games: usize) -> Self {
+        self.games = games;
+        self
+    }
+
+    /// for every move, DEFAULT_NODES nodes otherwise.
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_random_plies(mut self, random_plies: usize) -> Self {
+        self.random_plies = random_plies;
+        self
+    }
+
+    /// games that go on longer are drawn.
+    pub fn with_max_plies(mut self, max_plies: usize) -> Self {
+        self.max_plies = max_plies;
+        self
+    }
+
+    /// picks the random moves, the same seed plays the same games.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// searches with a network, ex. to make better data for the next one.
+    pub fn with_network(mut self, network: Option<Arc<Network>>) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// plays the games, handing on_game every game's samples. stops early once on_game gives back
+    /// false. gives back how many samples there were.
+    pub fn run(&self, mut on_game: impl FnMut(usize, &[Sample]) -> bool) -> Result<usize> {
+        let mut searcher = Searcher::new(self.limits).with_network(self.network.clone());
+        let mut total = 0;
+        for round in 0..self.games {
+            searcher.table().clear();
+            let samples = self.play(&mut searcher, round)?;
+            total += samples.len();
+            if !on_game(round, &samples) {
+                break;
+            }
+        }
+        Ok(total)
+    }
+
+    fn play(&self, searcher: &mut Searcher, round: usize) -> Result<Vec<Sample>> {
+        let mut game = self.variant.start()?;
+        let mut rng = (self.seed ^ (round as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15)) | 1;
+        for _ in 0..self.random_plies {
+            let moves = game.legal_moves()?;
+            if moves.is_empty() {
+                break;
+            }
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            game.execute_move(&moves[(rng % moves.len() as u64) as usize])?;
+        }
+
+        // positions with the team that was to move.
+        let mut positions = Vec::new();
+        let white_result = loop {
+            if let Some((result, _)) = result_of(&game) {
+                break match result {
+                    "1-0" => 1.0,
+                    "0-1" => 0.0,
+                    _ => 0.5,
+                };
+            }
+            if game.history().len() >= self.max_plies {
+                break 0.5;
+            }
+
+            let result = searcher.search(&mut game)?;
+            let Some(best_move) = result.best_move else {
+                break 0.5;
+            };
+            let quiet = !is_mate_score(result.score)
+                && !best_move.is_kill()
+                && best_move.promotion().is_none()
+                && !game.is_in_check(game.current_team().name());
+            if quiet {
+                positions.push((game.to_fen()?, result.score, game.current_move() % 2));
+            }
+            game.execute_move(&best_move)?;
+        };
+
+        Ok(positions
+            .into_iter()
+            .map(|(fen, score, team)| Sample {
+                fen,
+                score,
+                result: if team == 0 { white_result } else { 1.0 - white_result },
+            })
+            .collect())
+    }
+}
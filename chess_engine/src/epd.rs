@@ -0,0 +1,161 @@
+// extended position description, the first four fen fields followed by operations.
+// every operation is an opcode, its operands and a ';', ex.
+// r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - bm Qxf7#; id "scholar";
+// string operands are quoted and can hold spaces and ';'.
+
+use chess::error::{ChessError, Result};
+use chess::game::Game;
+use chess::r#move::{Move, MoveKey};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Operation {
+    pub opcode: String,
+    pub operands: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EpdPosition {
+    // the four position fields.
+    position: String,
+    operations: Vec<Operation>,
+}
+
+fn epd_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+impl EpdPosition {
+    pub fn parse(line: &str) -> Result<Self> {
+        let line = line.trim();
+        let mut rest = line;
+        let mut fields = Vec::new();
+        for _ in 0..4 {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(epd_error(format!("Expected 4 position fields in {}.", line)));
+            }
+            fields.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+
+        let mut operations = Vec::new();
+        let mut tokens = Vec::new();
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    let mut token = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some(c) => token.push(c),
+                            None => return Err(epd_error(format!("Unterminated string in {}.", line))),
+                        }
+                    }
+                    tokens.push(token);
+                }
+                ';' => {
+                    if tokens.is_empty() {
+                        continue;
+                    }
+                    let opcode = tokens.remove(0);
+                    operations.push(Operation {
+                        opcode,
+                        operands: std::mem::take(&mut tokens),
+                    });
+                }
+                c if c.is_whitespace() => {}
+                c => {
+                    let mut token = c.to_string();
+                    while let Some(&c) = chars.peek() {
+                        if c.is_whitespace() || c == ';' || c == '"' {
+                            break;
+                        }
+                        token.push(c);
+                        chars.next();
+                    }
+                    tokens.push(token);
+                }
+            }
+        }
+        if !tokens.is_empty() {
+            return Err(epd_error(format!("Operation {} is missing its ';'.", tokens[0])));
+        }
+
+        Ok(Self {
+            position: fields.join(" "),
+            operations,
+        })
+    }
+
+    pub fn operations(&self) -> &Vec<Operation> {
+        &self.operations
+    }
+
+    pub fn operands(&self, opcode: &str) -> Option<&Vec<String>> {
+        self.operations
+            .iter()
+            .find(|op| op.opcode == opcode)
+            .map(|op| &op.operands)
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.operands("id")?.first().map(|s| s.as_str())
+    }
+
+    /// c0 to c9.
+    pub fn comment(&self, n: u8) -> Option<&str> {
+        self.operands(&format!("c{}", n))?.first().map(|s| s.as_str())
+    }
+
+    pub fn best_moves(&self) -> &[String] {
+        self.operands("bm").map_or(&[], |ops| ops.as_slice())
+    }
+
+    pub fn avoid_moves(&self) -> &[String] {
+        self.operands("am").map_or(&[], |ops| ops.as_slice())
+    }
+
+    /// full fen, the clocks come from the hmvc and fmvn opcodes when they're there.
+    pub fn fen(&self) -> String {
+        let clock = |opcode: &str, default: &str| {
+            self.operands(opcode)
+                .and_then(|ops| ops.first().cloned())
+                .unwrap_or(default.to_string())
+        };
+        format!("{} {} {}", self.position, clock("hmvc", "0"), clock("fmvn", "1"))
+    }
+
+    pub fn game(&self) -> Result<Game> {
+        Game::from_fen(&self.fen())
+    }
+
+    /// whether the move is one of the best moves and none of the moves to avoid.
+    /// game has to be in this position.
+    pub fn is_solved_by(&self, game: &Game, m: &Move) -> Result<bool> {
+        let keys = |sans: &[String]| -> Result<Vec<MoveKey>> {
+            sans.iter().map(|san| Ok(game.parse_san(san)?.key())).collect()
+        };
+
+        let best = keys(self.best_moves())?;
+        let avoid = keys(self.avoid_moves())?;
+
+        Ok((best.is_empty() || best.contains(&m.key())) && !avoid.contains(&m.key()))
+    }
+}
+
+/// one position per line, blank lines and lines starting with '#' are skipped.
+pub fn parse_epd(contents: &str) -> Result<Vec<EpdPosition>> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(idx, line)| {
+            EpdPosition::parse(line).map_err(|e| match e {
+                ChessError::NotationError { why } => epd_error(format!("Line {}: {}", idx + 1, why)),
+                e => e,
+            })
+        })
+        .collect()
+}
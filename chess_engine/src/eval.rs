@@ -0,0 +1,524 @@
+// static evaluation worked out from the piece definitions, so user made pieces need no tuning.
+// material comes from the pieces' points, mobility from walking their rules on the current
+// board, and the piece-square tables from how much of an empty board each tile lets a piece reach.
+// scores are in centipoints, a piece worth 1 point is worth 100.
+// the weights of each piece can be replaced with tuned ones from a parameter file, one line per
+// piece symbol: <symbol> <material> <mobility> <center> <advance>. pieces without a line keep
+// the defaults.
+// for weaker play the score can be off by up to some noise, the same for a position every time
+// it's seen so the transposition table stays consistent.
+// with a network (see nnue.rs) that fits the game, the score comes from it instead, the terms
+// and tables here are still the hand-written ones. the search makes its moves through make and
+// unmake so the network's accumulators can follow along.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLockReadGuard};
+
+use chess::board::Board;
+use chess::error::{ChessError, Result};
+use chess::game::Game;
+use chess::piece::{Piece, PieceRef};
+use chess::piece_rules::{Distance, MoveRules};
+use chess::r#move::{Coord, Move};
+use chess::team::StartInfo;
+use chess::vec2::Vec2;
+
+use crate::nnue::{Accumulators, Network};
+
+// centipoints for every tile a piece can reach.
+const MOBILITY_WEIGHT: i32 = 4;
+// the spread between the worst and best tile of a table, before advancement.
+const CENTER_WEIGHT: i32 = 30;
+// the bonus for a promotable piece one row away from promoting.
+const ADVANCE_WEIGHT: i32 = 60;
+
+/// the weights of one piece, in centipoints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PieceWeights {
+    pub material: i32,
+    /// for every tile the piece can reach.
+    pub mobility: i32,
+    /// the spread between the worst and best tile of its table, before advancement.
+    pub center: i32,
+    /// the bonus for being one row away from promoting.
+    pub advance: i32,
+}
+
+impl PieceWeights {
+    /// the piece's points, and the same weights as every other piece.
+    pub fn default_for(data: &PieceRef) -> Self {
+        Self {
+            material: data.points() as i32 * 100,
+            mobility: MOBILITY_WEIGHT,
+            center: CENTER_WEIGHT,
+            advance: ADVANCE_WEIGHT,
+        }
+    }
+
+    pub fn to_array(&self) -> [i32; 4] {
+        [self.material, self.mobility, self.center, self.advance]
+    }
+
+    pub fn from_array(weights: [i32; 4]) -> Self {
+        let [material, mobility, center, advance] = weights;
+        Self {
+            material,
+            mobility,
+            center,
+            advance,
+        }
+    }
+}
+
+/// weights by piece symbol.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EvalParams {
+    weights: BTreeMap<char, PieceWeights>,
+}
+
+fn params_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+impl EvalParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the defaults of every piece in the catalog, where tuning starts from.
+    pub fn seeded(catalog: &[Piece]) -> Self {
+        let mut params = Self::new();
+        for piece in catalog {
+            let data = piece.read().unwrap();
+            params.set(data.symbol(), PieceWeights::default_for(&data));
+        }
+        params
+    }
+
+    pub fn get(&self, symbol: char) -> Option<PieceWeights> {
+        self.weights.get(&symbol.to_ascii_uppercase()).copied()
+    }
+
+    pub fn set(&mut self, symbol: char, weights: PieceWeights) {
+        self.weights.insert(symbol.to_ascii_uppercase(), weights);
+    }
+
+    /// the piece's weights, or its defaults.
+    pub fn weights(&self, data: &PieceRef) -> PieceWeights {
+        self.get(data.symbol()).unwrap_or_else(|| PieceWeights::default_for(data))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut params = Self::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let line_error = || params_error(format!("Line {}: expected a symbol and 4 weights, got {}.", idx + 1, line));
+            let [symbol, weights @ ..] = tokens.as_slice() else {
+                return Err(line_error());
+            };
+            let mut chars = symbol.chars();
+            let (Some(symbol), None) = (chars.next(), chars.next()) else {
+                return Err(line_error());
+            };
+            let weights: Vec<i32> = weights.iter().map(|w| w.parse().map_err(|_| line_error())).collect::<Result<_>>()?;
+            let weights = weights.try_into().map_err(|_| line_error())?;
+            params.set(symbol, PieceWeights::from_array(weights));
+        }
+        Ok(params)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = "# symbol material mobility center advance\n".to_string();
+        for (symbol, weights) in &self.weights {
+            let [material, mobility, center, advance] = weights.to_array();
+            text += &format!("{} {} {} {} {}\n", symbol, material, mobility, center, advance);
+        }
+        text
+    }
+}
+
+pub struct Evaluator {
+    width: u32,
+    height: u32,
+    params: EvalParams,
+    // the most the score is off by, and what picks how much for each position.
+    noise: i32,
+    seed: u64,
+    network: Option<Accumulators>,
+    // piece set index, then piece name (or betza for unnamed pieces), indexed by y * width + x.
+    tables: HashMap<usize, HashMap<String, Vec<i32>>>,
+    // the same, before the table is weighted.
+    table_terms: HashMap<usize, HashMap<String, Vec<TableTerms>>>,
+}
+
+// where a piece stands on its table: how central the tile is and how far it has advanced, each
+// a fraction of the most it can be.
+#[derive(Clone, Copy)]
+struct TableTerms {
+    center: (i32, i32),
+    advance: (i32, i32),
+}
+
+// a piece that's on the board.
+struct Placed<'a> {
+    set_idx: usize,
+    coord: Coord,
+    data: RwLockReadGuard<'a, PieceRef>,
+    // none for royal pieces, their mobility doesn't count.
+    reach: Option<u32>,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            params: EvalParams::new(),
+            noise: 0,
+            seed: 0,
+            network: None,
+            tables: HashMap::new(),
+            table_terms: HashMap::new(),
+        }
+    }
+
+    pub fn with_params(mut self, params: EvalParams) -> Self {
+        self.params = params;
+        self.tables.clear();
+        self
+    }
+
+    pub fn params(&self) -> &EvalParams {
+        &self.params
+    }
+
+    /// scores from the network for the games it fits.
+    pub fn with_network(mut self, network: Option<Arc<Network>>) -> Self {
+        self.network = network.map(Accumulators::new);
+        self
+    }
+
+    pub fn network(&self) -> Option<&Arc<Network>> {
+        self.network.as_ref().map(|accumulators| accumulators.network())
+    }
+
+    /// scores end up off by up to noise centipoints either way, 0 for none.
+    /// the seed picks how much for each position.
+    pub fn set_noise(&mut self, noise: i32, seed: u64) {
+        self.noise = noise.max(0);
+        self.seed = seed;
+    }
+
+    pub fn noise(&self) -> (i32, u64) {
+        (self.noise, self.seed)
+    }
+
+    /// the score for the team to move, positive when it's ahead of everyone else together.
+    pub fn evaluate(&mut self, game: &Game) -> i32 {
+        let current = game.current_move() as usize % game.board().piece_sets().len().max(1);
+        let network_score = self.accumulators(game).map(|accumulators| accumulators.evaluate(game));
+        let score: i32 = match network_score {
+            Some(score) => score,
+            None => self
+                .evaluate_teams(game)
+                .iter()
+                .enumerate()
+                .map(|(idx, score)| if idx == current { *score } else { -score })
+                .sum(),
+        };
+        if self.noise == 0 {
+            return score;
+        }
+
+        // splitmix64, so positions with close hashes still get unrelated noise.
+        let mut x = (game.hash() ^ self.seed).wrapping_add(0x9e3779b97f4a7c15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+        x ^= x >> 31;
+        score + (x % (self.noise as u64 * 2 + 1)) as i32 - self.noise
+    }
+
+    /// executes the move on the game, noting what changed for the network. unmake takes it back.
+    pub fn make(&mut self, game: &mut Game, m: &Move) -> Result<()> {
+        match self.accumulators(game) {
+            Some(accumulators) => accumulators.make(game, m),
+            None => game.execute_move(m),
+        }
+    }
+
+    /// undoes the game's last move or passed turn.
+    pub fn unmake(&mut self, game: &mut Game) -> Result<()> {
+        match self.accumulators(game) {
+            Some(accumulators) => accumulators.unmake(game),
+            None => game.undo(),
+        }
+    }
+
+    fn accumulators(&mut self, game: &Game) -> Option<&mut Accumulators> {
+        self.network.as_mut().filter(|accumulators| accumulators.network().fits(game))
+    }
+
+    /// what each team's own pieces are worth where they stand, in piece set order.
+    pub fn evaluate_teams(&mut self, game: &Game) -> Vec<i32> {
+        let board = game.board();
+        let mut scores = vec![0; board.piece_sets().len()];
+        for placed in self.placed(game) {
+            let weights = self.params.weights(&placed.data);
+            let mut piece_score = weights.material;
+            if let Some(reach) = placed.reach {
+                piece_score += reach as i32 * weights.mobility;
+                let idx = self.index(placed.coord);
+                piece_score += self.table(board, placed.set_idx, &placed.data)[idx];
+            }
+
+            scores[placed.set_idx] += piece_score;
+        }
+
+        scores
+    }
+
+    /// what the score is made of before it's weighted, by piece symbol: the number of pieces, the
+    /// tiles they reach and how central and advanced they stand, in the order of PieceWeights.
+    /// the team to move's pieces count for it and everyone else's against, so the score is about
+    /// the sum of every term times its weight.
+    pub fn terms(&mut self, game: &Game) -> BTreeMap<char, [f64; 4]> {
+        let board = game.board();
+        let current = game.current_move() as usize % board.piece_sets().len().max(1);
+        let mut terms: BTreeMap<char, [f64; 4]> = BTreeMap::new();
+        for placed in self.placed(game) {
+            let sign = if placed.set_idx == current { 1.0 } else { -1.0 };
+            let piece_terms = terms.entry(placed.data.symbol().to_ascii_uppercase()).or_default();
+            piece_terms[0] += sign;
+            if let Some(reach) = placed.reach {
+                let idx = self.index(placed.coord);
+                let table = self.table_terms(board, placed.set_idx, &placed.data)[idx];
+                piece_terms[1] += sign * reach as f64;
+                piece_terms[2] += sign * table.center.0 as f64 / table.center.1 as f64;
+                piece_terms[3] += sign * table.advance.0 as f64 / table.advance.1 as f64;
+            }
+        }
+        terms
+    }
+
+    // every piece on the board, with what it reaches where it stands.
+    fn placed<'a>(&mut self, game: &'a Game) -> Vec<Placed<'a>> {
+        let board = game.board();
+        if board.width() != self.width || board.height() != self.height {
+            self.width = board.width();
+            self.height = board.height();
+            self.tables.clear();
+            self.table_terms.clear();
+        }
+
+        // the piece set index on every tile.
+        let mut occupancy = vec![None; (self.width * self.height) as usize];
+        let mut pieces = Vec::new();
+        for (set_idx, set) in board.piece_sets().iter().enumerate() {
+            let start_info = set.team().start_info();
+            for piece in set.pieces() {
+                let data = piece.read().unwrap();
+                let Some(rel_pos) = data.rel_pos().filter(|_| data.is_alive()) else {
+                    continue;
+                };
+                let coord = board.rel_coord_to_absolute(rel_pos, start_info);
+                occupancy[self.index(coord)] = Some(set_idx);
+                pieces.push((set_idx, start_info, coord, data));
+            }
+        }
+
+        pieces
+            .into_iter()
+            .map(|(set_idx, start_info, coord, data)| {
+                let reach = (!data.is_royal()).then(|| {
+                    reach(&data, start_info, coord, board, |c| match occupancy[self.index(c)] {
+                        None => Tile::Empty,
+                        Some(idx) if idx == set_idx => Tile::Friend,
+                        Some(_) => Tile::Enemy,
+                    })
+                });
+                Placed {
+                    set_idx,
+                    coord,
+                    data,
+                    reach,
+                }
+            })
+            .collect()
+    }
+
+    /// bonuses for the piece standing on each tile, indexed by y * width + x.
+    /// royal pieces get no table, where they're safe depends on the rest of the board.
+    pub fn table(&mut self, board: &Board, set_idx: usize, data: &PieceRef) -> &Vec<i32> {
+        let key = table_key(data);
+        if !self.tables.get(&set_idx).is_some_and(|tables| tables.contains_key(&key)) {
+            let weights = self.params.weights(data);
+            let table = self
+                .table_terms(board, set_idx, data)
+                .iter()
+                .map(|terms| terms.center.0 * weights.center / terms.center.1 + terms.advance.0 * weights.advance / terms.advance.1)
+                .collect();
+            self.tables.entry(set_idx).or_default().insert(key.clone(), table);
+        }
+        &self.tables[&set_idx][&key]
+    }
+
+    fn table_terms(&mut self, board: &Board, set_idx: usize, data: &PieceRef) -> &Vec<TableTerms> {
+        let start_info = board.piece_sets()[set_idx].team().start_info();
+        self.table_terms
+            .entry(set_idx)
+            .or_default()
+            .entry(table_key(data))
+            .or_insert_with(|| build_table_terms(board, start_info, data))
+    }
+
+    fn index(&self, coord: Coord) -> usize {
+        (coord.y() * self.width + coord.x()) as usize
+    }
+}
+
+fn table_key(data: &PieceRef) -> String {
+    if data.name().is_empty() {
+        data.betza()
+    } else {
+        data.name().to_string()
+    }
+}
+
+fn build_table_terms(board: &Board, start_info: StartInfo, data: &PieceRef) -> Vec<TableTerms> {
+    let none = TableTerms {
+        center: (0, 1),
+        advance: (0, 1),
+    };
+    let mut table = vec![none; (board.width() * board.height()) as usize];
+    if data.is_royal() {
+        return table;
+    }
+
+    let mut reaches = Vec::with_capacity(table.len());
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            reaches.push(reach(data, start_info, Coord::new(x, y), board, |_| Tile::Empty) as i32);
+        }
+    }
+    let min = *reaches.iter().min().unwrap_or(&0);
+    let max = *reaches.iter().max().unwrap_or(&0);
+    let mean = reaches.iter().sum::<i32>() / reaches.len().max(1) as i32;
+    let spread = (max - min).max(1);
+
+    let last_row = match start_info {
+        StartInfo::Bottom { .. } | StartInfo::Top { .. } => board.height() - 1,
+        StartInfo::Left { .. } | StartInfo::Right { .. } => board.width() - 1,
+    };
+
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            let idx = (y * board.width() + x) as usize;
+            // tiles that let the piece reach more of the board are better.
+            table[idx].center = (reaches[idx] - mean, spread);
+
+            // promotable pieces are worth more the closer they get.
+            if !data.promotions().is_empty() && last_row > 0 {
+                let row = board.absolute_coord_to_rel(Coord::new(x, y), start_info).y().min(last_row);
+                table[idx].advance = ((row * row) as i32, (last_row * last_row) as i32);
+            }
+        }
+    }
+
+    table
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tile {
+    Empty,
+    Friend,
+    Enemy,
+}
+
+// how many tiles the piece's rules reach from coord, counting the same tile twice when two rules
+// reach it. it's an estimate: immunities, nth move rules and pierce limits are left out.
+fn reach(data: &PieceRef, start_info: StartInfo, from: Coord, board: &Board, tile: impl Fn(Coord) -> Tile) -> u32 {
+    let mut count = 0;
+
+    let step = |coord: Coord, v: Vec2| -> Option<Coord> {
+        let x = coord.x().checked_add_signed(v.x())?;
+        let y = coord.y().checked_add_signed(v.y())?;
+        (x < board.width() && y < board.height()).then(|| Coord::new(x, y))
+    };
+    let max_steps = |distance: Distance| match distance {
+        Distance::Finite { distance } => distance,
+        Distance::Infinite => u32::MAX,
+    };
+
+    let mut leap = |v: Vec2| {
+        if let Some(to) = step(from, v.rel_to_absolute(start_info)) {
+            if tile(to) != Tile::Friend {
+                count += 1;
+            }
+        }
+    };
+    for rule in data.move_rules().iter().chain(data.kill_rules()) {
+        match rule {
+            MoveRules::Jump { translation } => leap(*translation),
+            MoveRules::KnightJump { radius, offset } => Vec2::symmetric(*radius, *offset).into_iter().for_each(&mut leap),
+            MoveRules::Radius { tiles } => {
+                let tiles = *tiles as i32;
+                for x in -tiles..=tiles {
+                    for y in -tiles..=tiles {
+                        if x != 0 || y != 0 {
+                            leap(Vec2::new(x, y));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let rays: Vec<(Vec2, u32)> = data
+        .move_rules()
+        .iter()
+        .chain(data.kill_rules())
+        .flat_map(|rule| match rule {
+            MoveRules::Blunt { move_info } | MoveRules::LineJump { move_info } | MoveRules::Pierce { move_info, .. } => {
+                move_info
+                    .iter()
+                    .map(|mv| (mv.direction().as_vec(), max_steps(mv.distance())))
+                    .collect()
+            }
+            MoveRules::Rider { translation, distance } => vec![(*translation, max_steps(*distance))],
+            _ => Vec::new(),
+        })
+        .collect();
+
+    // every ray stops at the first piece, hoppers and piercers are treated like sliders.
+    for (v, max_steps) in rays {
+        let v = v.rel_to_absolute(start_info);
+        let mut coord = from;
+        for _ in 0..max_steps {
+            let Some(next) = step(coord, v) else {
+                break;
+            };
+            coord = next;
+            match tile(coord) {
+                Tile::Empty => count += 1,
+                Tile::Enemy => {
+                    count += 1;
+                    break;
+                }
+                Tile::Friend => break,
+            }
+        }
+    }
+
+    count
+}
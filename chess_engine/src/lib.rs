@@ -1,14 +1,22 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub mod book;
+pub mod cecp;
+pub mod controller;
+pub mod datagen;
+pub mod epd;
+pub mod eval;
+pub mod mcts;
+pub mod multi;
+pub mod nnue;
+pub mod ordering;
+pub mod polyglot;
+pub mod search;
+pub mod see;
+pub mod selfplay;
+pub mod skill;
+pub mod smp;
+pub mod tablebase;
+pub mod time;
+pub mod tt;
+pub mod tune;
+pub mod uci;
+pub mod variant;
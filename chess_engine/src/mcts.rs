@@ -0,0 +1,474 @@
+// monte carlo tree search, for variants where the evaluation doesn't know what's going on.
+// every iteration walks down the tree by uct, adds one move to it, plays the game out from there
+// and counts the result on the way back up. playouts that run too long are scored by the evaluator.
+// results are kept per team, so it works the same with more than two teams.
+// the tree is kept between searches and picked up again when the game reached one of its positions.
+
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use chess::error::Result;
+use chess::game::Game;
+use chess::r#move::{Move, MoveKey};
+
+use crate::eval::Evaluator;
+use crate::ordering::mvv_lva;
+use crate::search::{resolve_pv, PvLine, SearchLimits, SearchResult, MATE, MAX_DEPTH};
+
+/// the usual uct exploration constant, sqrt(2).
+pub const DEFAULT_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+/// how many playouts a search makes when the limits don't say.
+pub const DEFAULT_PLAYOUTS: u64 = 1000;
+/// how many moves a playout goes before the evaluator scores it.
+pub const DEFAULT_PLAYOUT_DEPTH: u32 = 20;
+
+// centipoints of an evaluation that make a team about 10 times as likely to win as to lose.
+const WIN_SCALE: f64 = 400.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Playout {
+    /// every move is as likely.
+    Random,
+    /// the kill that takes the most with the least is played when there is one, otherwise random.
+    Light,
+}
+
+struct Node {
+    // the move that leads here, none for the root.
+    key: Option<MoveKey>,
+    hash: u64,
+    // the team that made the move leading here, its results are the ones counted.
+    mover: usize,
+    visits: u32,
+    reward: f64,
+    children: Vec<usize>,
+    // the node's legal moves, generated again every search since they point into the game.
+    moves: Option<Vec<Move>>,
+    // how it ends for every team, when it's over.
+    outcome: Option<Vec<f64>>,
+}
+
+impl Node {
+    fn new(key: Option<MoveKey>, hash: u64, mover: usize) -> Self {
+        Self {
+            key,
+            hash,
+            mover,
+            visits: 0,
+            reward: 0.0,
+            children: Vec::new(),
+            moves: None,
+            outcome: None,
+        }
+    }
+}
+
+pub struct MctsSearcher {
+    limits: SearchLimits,
+    exploration: f64,
+    playouts: u64,
+    playout: Playout,
+    playout_depth: u32,
+    reuse_tree: bool,
+    evaluator: Evaluator,
+    // xorshift state.
+    rng: u64,
+    // the root is always the first node.
+    tree: Vec<Node>,
+    start: Instant,
+    nodes: u64,
+}
+
+impl MctsSearcher {
+    pub fn new(limits: SearchLimits) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+
+        Self {
+            limits,
+            exploration: DEFAULT_EXPLORATION,
+            playouts: DEFAULT_PLAYOUTS,
+            playout: Playout::Light,
+            playout_depth: DEFAULT_PLAYOUT_DEPTH,
+            reuse_tree: true,
+            evaluator: Evaluator::new(),
+            rng: 0,
+            tree: Vec::new(),
+            start: Instant::now(),
+            nodes: 0,
+        }
+        .with_seed(seed)
+    }
+
+    /// higher tries the less visited moves more often.
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// the playouts per search when the limits have neither nodes nor a movetime.
+    /// with a node limit every node is a playout.
+    pub fn with_playouts(mut self, playouts: u64) -> Self {
+        self.playouts = playouts;
+        self
+    }
+
+    pub fn with_playout(mut self, playout: Playout) -> Self {
+        self.playout = playout;
+        self
+    }
+
+    pub fn with_playout_depth(mut self, playout_depth: u32) -> Self {
+        self.playout_depth = playout_depth;
+        self
+    }
+
+    /// the same seed, game and limits give the same search, as long as there's no movetime.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        // xorshift gets stuck on 0.
+        self.rng = seed | 1;
+        self
+    }
+
+    /// on by default.
+    pub fn with_tree_reuse(mut self, reuse_tree: bool) -> Self {
+        self.reuse_tree = reuse_tree;
+        self
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// the playouts behind the last search's result, including the ones from earlier searches
+    /// when the tree was reused.
+    pub fn root_visits(&self) -> u32 {
+        self.tree.first().map_or(0, |root| root.visits)
+    }
+
+    pub fn search(&mut self, game: &mut Game) -> Result<SearchResult> {
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.prepare_tree(game);
+
+        let max_depth = self.limits.depth.unwrap_or(MAX_DEPTH).max(1);
+        self.expand(game, 0)?;
+        // a repetition or the fifty move rule doesn't end the game at the root, there are still moves to pick from.
+        if self.tree[0].moves.as_ref().is_some_and(|moves| !moves.is_empty()) {
+            self.tree[0].outcome = None;
+        }
+        if self.tree[0].outcome.is_none() {
+            while !self.should_stop() {
+                self.iterate(game, max_depth)?;
+                self.nodes += 1;
+            }
+        }
+
+        Ok(self.result(game))
+    }
+
+    // keeps the part of the tree below the game's position, or starts over.
+    fn prepare_tree(&mut self, game: &Game) {
+        let teams = game.board().piece_sets().len();
+        let hash = game.hash();
+
+        let mut found = None;
+        if self.reuse_tree && !self.tree.is_empty() {
+            // the position is usually a move of every team past the old root.
+            let mut layer = vec![0];
+            for _ in 0..=teams {
+                if let Some(idx) = layer.iter().find(|idx| self.tree[**idx].hash == hash) {
+                    found = Some(*idx);
+                    break;
+                }
+                layer = layer.iter().flat_map(|idx| self.tree[*idx].children.clone()).collect();
+            }
+        }
+
+        let old = std::mem::take(&mut self.tree);
+        match found {
+            Some(idx) => {
+                let mut old: Vec<Option<Node>> = old.into_iter().map(Some).collect();
+                copy_subtree(&mut old, idx, &mut self.tree);
+                self.tree[0].key = None;
+            }
+            None => {
+                let previous = (game.current_move() as usize + teams - 1) % teams;
+                self.tree.push(Node::new(None, hash, previous));
+            }
+        }
+    }
+
+    fn should_stop(&self) -> bool {
+        if self.limits.movetime.is_some_and(|movetime| self.start.elapsed() >= movetime) {
+            return true;
+        }
+        match (self.limits.nodes, self.limits.movetime) {
+            (Some(nodes), _) => self.nodes >= nodes,
+            (None, Some(_)) => false,
+            (None, None) => self.nodes >= self.playouts,
+        }
+    }
+
+    // one selection, expansion, playout and backpropagation.
+    fn iterate(&mut self, game: &mut Game, max_depth: u32) -> Result<()> {
+        let mut path = vec![0];
+        let rewards = self.descend(game, max_depth, &mut path);
+        for _ in 1..path.len() {
+            game.undo()?;
+        }
+        let rewards = rewards?;
+
+        for idx in path {
+            let node = &mut self.tree[idx];
+            node.visits += 1;
+            node.reward += rewards[node.mover];
+        }
+        Ok(())
+    }
+
+    // walks down to a node that isn't in the tree yet, adds it and plays it out. the moves stay
+    // made, path gets the nodes they went through.
+    fn descend(&mut self, game: &mut Game, max_depth: u32, path: &mut Vec<usize>) -> Result<Vec<f64>> {
+        let mut idx = 0;
+        loop {
+            self.expand(game, idx)?;
+            if let Some(outcome) = &self.tree[idx].outcome {
+                return Ok(outcome.clone());
+            }
+            if path.len() as u32 > max_depth {
+                return self.playout(game);
+            }
+
+            let node = &self.tree[idx];
+            let moves = node.moves.as_ref().unwrap();
+            let untried: Vec<usize> = (0..moves.len())
+                .filter(|m| !node.children.iter().any(|child| self.tree[*child].key == Some(moves[*m].key())))
+                .collect();
+
+            if !untried.is_empty() {
+                let pick = untried[self.random(untried.len())];
+                let m = self.tree[idx].moves.as_ref().unwrap()[pick].clone();
+                let mover = current(game);
+                game.execute_move(&m)?;
+
+                let child = self.tree.len();
+                self.tree.push(Node::new(Some(m.key()), game.hash(), mover));
+                self.tree[idx].children.push(child);
+                path.push(child);
+
+                self.expand(game, child)?;
+                return match &self.tree[child].outcome {
+                    Some(outcome) => Ok(outcome.clone()),
+                    None => self.playout(game),
+                };
+            }
+
+            let child = self.select(idx);
+            let key = self.tree[child].key;
+            let m = self.tree[idx]
+                .moves
+                .as_ref()
+                .unwrap()
+                .iter()
+                .find(|m| Some(m.key()) == key)
+                .unwrap()
+                .clone();
+            game.execute_move(&m)?;
+            path.push(child);
+            idx = child;
+        }
+    }
+
+    // the child with the best upper confidence bound.
+    fn select(&self, idx: usize) -> usize {
+        let parent_visits = (self.tree[idx].visits.max(1) as f64).ln();
+        let uct = |child: &Node| {
+            let visits = child.visits.max(1) as f64;
+            child.reward / visits + self.exploration * (parent_visits / visits).sqrt()
+        };
+
+        *self.tree[idx]
+            .children
+            .iter()
+            .max_by(|a, b| uct(&self.tree[**a]).total_cmp(&uct(&self.tree[**b])))
+            .unwrap()
+    }
+
+    // generates the moves of a node the game is at, and whether it's over.
+    fn expand(&mut self, game: &Game, idx: usize) -> Result<()> {
+        if self.tree[idx].moves.is_some() {
+            return Ok(());
+        }
+
+        let moves = game.legal_moves()?;
+        self.tree[idx].outcome = outcome(game, &moves);
+        self.tree[idx].moves = Some(moves);
+        Ok(())
+    }
+
+    // plays the game out, then takes the moves back.
+    fn playout(&mut self, game: &mut Game) -> Result<Vec<f64>> {
+        let mut played = 0;
+        let rewards = loop {
+            if played >= self.playout_depth {
+                break Ok(self.rewards(game));
+            }
+
+            let moves = match game.calculate_moves_for(game.current_team().name().to_string()) {
+                Ok(moves) => moves,
+                Err(e) => break Err(e),
+            };
+            if let Some(outcome) = outcome(game, &moves) {
+                break Ok(outcome);
+            }
+
+            let m = match self.pick(game, &moves) {
+                Ok(m) => m,
+                Err(e) => break Err(e),
+            };
+            if let Err(e) = game.execute_move(&moves[m]) {
+                break Err(e);
+            }
+            played += 1;
+        };
+
+        for _ in 0..played {
+            game.undo()?;
+        }
+        rewards
+    }
+
+    fn pick(&mut self, game: &Game, moves: &[Move]) -> Result<usize> {
+        if self.playout == Playout::Light {
+            let mut best: Option<(i32, usize)> = None;
+            for (idx, m) in moves.iter().enumerate() {
+                if !m.is_kill() {
+                    continue;
+                }
+                let score = mvv_lva(game, m)?;
+                if best.is_none_or(|(best_score, _)| score > best_score) {
+                    best = Some((score, idx));
+                }
+            }
+            if let Some((_, idx)) = best {
+                return Ok(idx);
+            }
+        }
+
+        Ok(self.random(moves.len()))
+    }
+
+    // the evaluation as a chance to win, against the best of the other teams.
+    fn rewards(&mut self, game: &Game) -> Vec<f64> {
+        let scores = self.evaluator.evaluate_teams(game);
+        (0..scores.len())
+            .map(|team| {
+                let best_other = (0..scores.len())
+                    .filter(|other| *other != team)
+                    .map(|other| scores[other])
+                    .max()
+                    .unwrap_or(0);
+                let diff = (scores[team] - best_other) as f64;
+                1.0 / (1.0 + 10f64.powf(-diff / WIN_SCALE))
+            })
+            .collect()
+    }
+
+    fn result(&self, game: &mut Game) -> SearchResult {
+        let mut pv = Vec::new();
+        let mut idx = 0;
+        while let Some(child) = self.most_visited(idx) {
+            pv.push(self.tree[child].key.unwrap());
+            idx = child;
+        }
+
+        let root = &self.tree[0];
+        let best = self.most_visited(0).map(|idx| &self.tree[idx]);
+        let score = match best {
+            // the move ends the game.
+            Some(node) if node.outcome.is_some() => match node.outcome.as_ref().unwrap()[node.mover] {
+                won if won >= 1.0 => MATE - 1,
+                lost if lost <= 0.0 => -MATE + 1,
+                _ => 0,
+            },
+            Some(node) => {
+                let chance = (node.reward / node.visits.max(1) as f64).clamp(0.001, 0.999);
+                (-WIN_SCALE * (1.0 / chance - 1.0).log10()) as i32
+            }
+            None => match &root.outcome {
+                Some(outcome) if outcome[current(game)] <= 0.0 => -MATE,
+                _ => 0,
+            },
+        };
+
+        // the tree only keeps keys, the moves are looked up again by playing the pv out.
+        let pv = resolve_pv(game, &pv);
+        SearchResult {
+            best_move: pv.first().cloned(),
+            score,
+            depth: pv.len() as u32,
+            lines: (!pv.is_empty()).then(|| PvLine { score, pv: pv.clone() }).into_iter().collect(),
+            pv,
+            nodes: self.nodes,
+        }
+    }
+
+    fn most_visited(&self, idx: usize) -> Option<usize> {
+        self.tree[idx]
+            .children
+            .iter()
+            .copied()
+            .filter(|child| self.tree[*child].visits > 0)
+            .max_by_key(|child| self.tree[*child].visits)
+    }
+
+    fn random(&mut self, len: usize) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng % len as u64) as usize
+    }
+}
+
+/// the search with default settings.
+pub fn search(game: &mut Game, limits: SearchLimits) -> Result<SearchResult> {
+    MctsSearcher::new(limits).search(game)
+}
+
+// how the game ends for every team, when the team to move has no moves or it's a draw.
+fn outcome(game: &Game, moves: &[Move]) -> Option<Vec<f64>> {
+    let teams = game.board().piece_sets().len();
+    if game.halfmove_clock() >= 100 || game.repetitions() > 0 {
+        return Some(vec![0.5; teams]);
+    }
+    if !moves.is_empty() {
+        return None;
+    }
+    if !game.is_in_check(game.current_team().name()) {
+        return Some(vec![0.5; teams]);
+    }
+
+    let loser = current(game);
+    Some((0..teams).map(|team| if team == loser { 0.0 } else { 1.0 }).collect())
+}
+
+// moves a node and everything below it into a new tree, the node becomes the root.
+fn copy_subtree(old: &mut [Option<Node>], idx: usize, tree: &mut Vec<Node>) -> usize {
+    let mut node = old[idx].take().unwrap();
+    node.moves = None;
+    node.outcome = None;
+    let children = std::mem::take(&mut node.children);
+
+    let new_idx = tree.len();
+    tree.push(node);
+    for child in children {
+        let child = copy_subtree(old, child, tree);
+        tree[new_idx].children.push(child);
+    }
+    new_idx
+}
+
+fn current(game: &Game) -> usize {
+    game.current_move() as usize % game.board().piece_sets().len()
+}
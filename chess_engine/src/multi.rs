@@ -0,0 +1,337 @@
+// search for games with more than two teams, where one team's gain isn't simply another's loss.
+// every mode scores positions with a vector holding each team's utility, in piece set order.
+// max^n: every team plays what's best for itself and its allies.
+// paranoid: every team outside the searching team's alliance is assumed to be against it, which
+// turns the search into two sided alpha-beta.
+// best reply: like paranoid, but of all the opponents' replies only the strongest is played and
+// the others pass, so the searching team gets to move more often within the same depth.
+
+use std::time::Instant;
+
+use chess::error::{ChessError, Result};
+use chess::game::Game;
+use chess::r#move::Move;
+
+use crate::eval::Evaluator;
+use crate::search::{SearchLimits, MATE, MAX_DEPTH};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultiMode {
+    MaxN,
+    Paranoid,
+    BestReply,
+}
+
+/// which teams play together, ex. two against two.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alliances {
+    // the alliance of every piece set.
+    groups: Vec<usize>,
+}
+
+impl Alliances {
+    /// every team on its own.
+    pub fn free_for_all(game: &Game) -> Self {
+        Self {
+            groups: (0..game.board().piece_sets().len()).collect(),
+        }
+    }
+
+    /// teams are grouped by name, teams left out play on their own.
+    pub fn new(game: &Game, groups: &[&[&str]]) -> Result<Self> {
+        let sets = game.board().piece_sets();
+        let mut alliances = Self::free_for_all(game);
+
+        for (group_idx, names) in groups.iter().enumerate() {
+            for name in *names {
+                let idx = sets
+                    .iter()
+                    .position(|set| set.team().name() == *name)
+                    .ok_or(ChessError::UnknownTeam { name: name.to_string() })?;
+                // past the piece sets, so they can't clash with the teams that play alone.
+                alliances.groups[idx] = sets.len() + group_idx;
+            }
+        }
+
+        Ok(alliances)
+    }
+
+    pub fn allied(&self, a: usize, b: usize) -> bool {
+        self.groups[a] == self.groups[b]
+    }
+
+    /// each team's utility: the average score of its alliance minus the average of everyone else.
+    pub fn utilities(&self, scores: &[i32]) -> Vec<i32> {
+        let average = |values: Vec<i32>| {
+            let len = values.len().max(1) as i32;
+            values.iter().sum::<i32>() / len
+        };
+
+        (0..scores.len())
+            .map(|team| {
+                let (allies, others): (Vec<usize>, Vec<usize>) =
+                    (0..scores.len()).partition(|other| self.allied(team, *other));
+                average(allies.iter().map(|idx| scores[*idx]).collect())
+                    - average(others.iter().map(|idx| scores[*idx]).collect())
+            })
+            .collect()
+    }
+}
+
+pub struct MultiResult {
+    pub best_move: Option<Move>,
+    // the utilities the best line is expected to end with, in piece set order.
+    pub scores: Vec<i32>,
+    // the deepest iteration that finished.
+    pub depth: u32,
+    pub nodes: u64,
+}
+
+pub struct MultiSearcher {
+    mode: MultiMode,
+    alliances: Alliances,
+    limits: SearchLimits,
+    evaluator: Evaluator,
+    start: Instant,
+    nodes: u64,
+    stopped: bool,
+}
+
+impl MultiSearcher {
+    pub fn new(mode: MultiMode, alliances: Alliances, limits: SearchLimits) -> Self {
+        Self {
+            mode,
+            alliances,
+            limits,
+            evaluator: Evaluator::new(),
+            start: Instant::now(),
+            nodes: 0,
+            stopped: false,
+        }
+    }
+
+    pub fn search(&mut self, game: &mut Game) -> Result<MultiResult> {
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.stopped = false;
+
+        let root = current(game);
+        let moves = game.legal_moves()?;
+        let mut result = MultiResult {
+            best_move: moves.first().cloned(),
+            scores: vec![0; game.board().piece_sets().len()],
+            depth: 0,
+            nodes: 0,
+        };
+        if moves.is_empty() {
+            result.scores = self.terminal(game, 0);
+            return Ok(result);
+        }
+
+        let max_depth = self.limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+        for depth in 1..=max_depth {
+            let mut best: Option<(Move, Vec<i32>)> = None;
+            for m in &moves {
+                let alpha = best.as_ref().map_or(-MATE - 1, |(_, scores)| scores[root]);
+                game.execute_move(m)?;
+                let scores = self.node(game, depth - 1, 1, alpha, MATE + 1, root);
+                game.undo()?;
+                let scores = scores?;
+
+                if self.stopped {
+                    break;
+                }
+                if best.as_ref().is_none_or(|(_, best_scores)| scores[root] > best_scores[root]) {
+                    best = Some((m.clone(), scores));
+                }
+            }
+
+            // a cut off iteration only counts when nothing finished before it.
+            if !self.stopped || result.depth == 0 {
+                if let Some((m, scores)) = best {
+                    result.best_move = Some(m);
+                    result.scores = scores;
+                }
+            }
+            if self.stopped {
+                break;
+            }
+            result.depth = depth;
+        }
+
+        result.nodes = self.nodes;
+        Ok(result)
+    }
+
+    fn node(&mut self, game: &mut Game, depth: u32, ply: i32, alpha: i32, beta: i32, root: usize) -> Result<Vec<i32>> {
+        let teams = game.board().piece_sets().len();
+        self.nodes += 1;
+        self.stopped = self.stopped || self.limits.reached(self.start, self.nodes);
+        if self.stopped {
+            return Ok(vec![0; teams]);
+        }
+
+        // fifty move rule and repetitions.
+        if game.halfmove_clock() >= 100 || game.repetitions() > 0 {
+            return Ok(vec![0; teams]);
+        }
+        if depth == 0 {
+            let scores = self.evaluator.evaluate_teams(game);
+            return Ok(self.alliances.utilities(&scores));
+        }
+
+        let mover = current(game);
+        match self.mode {
+            MultiMode::BestReply if !self.alliances.allied(mover, root) => self.best_reply(game, depth, ply, alpha, beta, root),
+            mode => {
+                let moves = game.legal_moves()?;
+                if moves.is_empty() {
+                    return Ok(self.terminal(game, ply));
+                }
+
+                // max^n looks after the team to move, the others after the searching team.
+                let (perspective, maximizing) = match mode {
+                    MultiMode::MaxN => (mover, true),
+                    _ => (root, self.alliances.allied(mover, root)),
+                };
+                self.pick(game, moves, depth, ply, alpha, beta, root, perspective, maximizing)
+            }
+        }
+    }
+
+    // searches every move and keeps the scores that are best or worst for perspective. alpha and
+    // beta are the searching team's bounds and are only used when perspective is the searching team.
+    #[allow(clippy::too_many_arguments)]
+    fn pick(
+        &mut self,
+        game: &mut Game,
+        moves: Vec<Move>,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        mut beta: i32,
+        root: usize,
+        perspective: usize,
+        maximizing: bool,
+    ) -> Result<Vec<i32>> {
+        let prunes = perspective == root && self.mode != MultiMode::MaxN;
+        let mut best: Option<Vec<i32>> = None;
+
+        for m in moves {
+            game.execute_move(&m)?;
+            let scores = self.node(game, depth - 1, ply + 1, alpha, beta, root);
+            game.undo()?;
+            let scores = scores?;
+            if self.stopped {
+                return Ok(scores);
+            }
+
+            let better = best.as_ref().is_none_or(|best| {
+                if maximizing {
+                    scores[perspective] > best[perspective]
+                } else {
+                    scores[perspective] < best[perspective]
+                }
+            });
+            if better {
+                best = Some(scores);
+            }
+
+            if prunes {
+                let score = best.as_ref().unwrap()[root];
+                if maximizing {
+                    alpha = alpha.max(score);
+                } else {
+                    beta = beta.min(score);
+                }
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        Ok(best.unwrap())
+    }
+
+    // the opponents' turns until an ally of the searching team is to move again. every opponent's
+    // moves are tried, the others pass, and only the reply that's worst for the searching team counts.
+    fn best_reply(&mut self, game: &mut Game, depth: u32, ply: i32, alpha: i32, mut beta: i32, root: usize) -> Result<Vec<i32>> {
+        let teams = game.board().piece_sets().len();
+        let first = current(game);
+        let opponents = (0..teams)
+            .map(|offset| (first + offset) % teams)
+            .take_while(|team| !self.alliances.allied(*team, root))
+            .count();
+
+        let mut best: Option<Vec<i32>> = None;
+        for offset in 0..opponents {
+            for _ in 0..offset {
+                game.pass_turn();
+            }
+
+            let moves = game.legal_moves()?;
+            for m in moves {
+                game.execute_move(&m)?;
+                for _ in offset + 1..opponents {
+                    game.pass_turn();
+                }
+                let scores = self.node(game, depth - 1, ply + 1, alpha, beta, root);
+                for _ in offset..opponents {
+                    game.undo()?;
+                }
+                let scores = scores?;
+                if self.stopped {
+                    break;
+                }
+
+                if best.as_ref().is_none_or(|best| scores[root] < best[root]) {
+                    beta = beta.min(scores[root]);
+                    best = Some(scores);
+                }
+                if alpha >= beta {
+                    break;
+                }
+            }
+
+            for _ in 0..offset {
+                game.undo()?;
+            }
+            if self.stopped || alpha >= beta {
+                break;
+            }
+        }
+
+        match best {
+            Some(best) => Ok(best),
+            // none of the opponents can move, they all pass.
+            None if !self.stopped => {
+                for _ in 0..opponents {
+                    game.pass_turn();
+                }
+                let scores = self.node(game, depth - 1, ply + 1, alpha, beta, root);
+                for _ in 0..opponents {
+                    game.undo()?;
+                }
+                scores
+            }
+            None => Ok(vec![0; teams]),
+        }
+    }
+
+    // the team to move has no moves. when in check its alliance lost, otherwise nobody wins.
+    fn terminal(&self, game: &Game, ply: i32) -> Vec<i32> {
+        let teams = game.board().piece_sets().len();
+        if !game.is_in_check(game.current_team().name()) {
+            return vec![0; teams];
+        }
+
+        let loser = current(game);
+        (0..teams)
+            .map(|team| if self.alliances.allied(team, loser) { -MATE + ply } else { MATE - ply })
+            .collect()
+    }
+}
+
+fn current(game: &Game) -> usize {
+    game.current_move() as usize % game.board().piece_sets().len()
+}
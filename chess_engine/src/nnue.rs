@@ -0,0 +1,772 @@
+// a small neural network evaluation, for variants the hand-written evaluation doesn't know well.
+// every (team, piece, tile) is an input, seen from each team's side: its own pieces come first,
+// then the next team's and so on, so one network plays every team. the inputs go through one
+// hidden layer per side, clipped to 0..1, and the output reads the team to move's layer and the
+// next team's. the network is made for one board size, team count and set of piece symbols, and
+// pieces it doesn't know are left out.
+// the hidden layers (accumulators) are kept on a stack along the game. the search makes and
+// unmakes its moves through it, each move noting the pieces it took off and put on the board, and
+// a position's accumulators are worked out from its parent's with just those once it's evaluated.
+// a game that got to its position some other way is worked out from scratch.
+// inference is integers only, with avx2 where the cpu has it. the trainer works in floats and
+// rounds its network to the same integers.
+//
+// file format, little endian:
+//   "CRNN", version (u32), width, height, teams, hidden size, symbol count (u32 each),
+//   the symbols (u32 each), feature weights (i16, every input's hidden weights in a row),
+//   feature biases (i16), output weights (i16, the team to move's half first), output bias (i32).
+
+use std::sync::Arc;
+
+use chess::error::{ChessError, Result};
+use chess::game::Game;
+use chess::piece::{Piece, PieceRef};
+use chess::r#move::{Coord, Move, MoveLog};
+
+use crate::datagen::Sample;
+use crate::variant::Variant;
+
+const MAGIC: &[u8; 4] = b"CRNN";
+const VERSION: u32 = 1;
+// an accumulator of 1.0, where it's clipped.
+const QA: i32 = 255;
+// an output weight of 1.0.
+const QB: i32 = 64;
+/// centipoints for an output of 1.0.
+pub const SCALE: i32 = 400;
+pub const DEFAULT_HIDDEN: usize = 64;
+/// how much the search's score counts in the training target, against the game's result.
+pub const DEFAULT_LAMBDA: f32 = 0.5;
+// what the trainer keeps its weights within, so they round into i16 and the sums into i32.
+const MAX_FEATURE_WEIGHT: f32 = 2.0;
+const MAX_OUTPUT_WEIGHT: f32 = 8.0;
+const LEARNING_RATE: f32 = 0.001;
+const BETA1: f32 = 0.9;
+const BETA2: f32 = 0.999;
+const EPSILON: f32 = 1e-8;
+const BATCH_SIZE: usize = 256;
+
+fn nnue_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+// what the inputs are made of.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Shape {
+    width: u32,
+    height: u32,
+    teams: usize,
+    symbols: Vec<char>,
+}
+
+impl Shape {
+    fn of(game: &Game, catalog: &[Piece]) -> Self {
+        let board = game.board();
+        let mut symbols: Vec<char> = Vec::new();
+        for piece in catalog {
+            let symbol = piece.read().unwrap().symbol().to_ascii_uppercase();
+            if !symbols.contains(&symbol) {
+                symbols.push(symbol);
+            }
+        }
+        Self {
+            width: board.width(),
+            height: board.height(),
+            teams: board.piece_sets().len(),
+            symbols,
+        }
+    }
+
+    fn tiles(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+
+    fn inputs(&self) -> usize {
+        self.teams * self.symbols.len() * self.tiles()
+    }
+
+    fn fits(&self, game: &Game) -> bool {
+        let board = game.board();
+        board.width() == self.width && board.height() == self.height && board.piece_sets().len() == self.teams
+    }
+
+    // every piece it knows as (team, symbol, tile).
+    fn pieces(&self, game: &Game) -> Vec<(usize, usize, usize)> {
+        let board = game.board();
+        let mut pieces = Vec::new();
+        for (set_idx, set) in board.piece_sets().iter().enumerate() {
+            let start_info = set.team().start_info();
+            for piece in set.pieces() {
+                let data = piece.read().unwrap();
+                let Some(rel_pos) = data.rel_pos().filter(|_| data.is_alive()) else {
+                    continue;
+                };
+                let Some(symbol) = self.symbol(&data) else {
+                    continue;
+                };
+                let coord = board.rel_coord_to_absolute(rel_pos, start_info);
+                pieces.push((set_idx, symbol, self.tile(coord)));
+            }
+        }
+        pieces
+    }
+
+    fn symbol(&self, data: &PieceRef) -> Option<usize> {
+        self.symbols.iter().position(|s| *s == data.symbol().to_ascii_uppercase())
+    }
+
+    fn tile(&self, coord: Coord) -> usize {
+        (coord.y() * self.width + coord.x()) as usize
+    }
+
+    // a piece that stood on the tile, if it's one the network knows.
+    fn piece(&self, game: &Game, data: &PieceRef, coord: Coord) -> Option<(usize, usize, usize)> {
+        let team = data.team()?;
+        let set_idx = game.board().piece_sets().iter().position(|set| set.team().name() == team.name())?;
+        Some((set_idx, self.symbol(data)?, self.tile(coord)))
+    }
+
+    // the pieces a move took off the board and put on it, true for the ones put on.
+    fn changes(&self, game: &Game, log: &MoveLog, changes: &mut Vec<((usize, usize, usize), bool)>) -> Result<()> {
+        let moved = log.moved_piece();
+        let data = moved.read()?;
+        let before = log.promoted_from().unwrap_or(&data);
+        changes.extend(self.piece(game, before, log.from()).map(|piece| (piece, false)));
+        changes.extend(self.piece(game, &data, log.to()).map(|piece| (piece, true)));
+        drop(data);
+        for (piece, coord) in log.kills() {
+            changes.extend(self.piece(game, &*piece.read()?, coord).map(|piece| (piece, false)));
+        }
+        if let Some(castle) = log.castle() {
+            self.changes(game, castle, changes)?;
+        }
+        Ok(())
+    }
+
+    // the input for a piece, seen from a team's side.
+    fn feature(&self, (team, symbol, tile): (usize, usize, usize), side: usize) -> usize {
+        let team = (team + self.teams - side) % self.teams;
+        (team * self.symbols.len() + symbol) * self.tiles() + tile
+    }
+}
+
+// the team to move and the next one, whose sides the output reads.
+fn sides(game: &Game, teams: usize) -> [usize; 2] {
+    let current = game.current_move() as usize % teams.max(1);
+    [current, (current + 1) % teams.max(1)]
+}
+
+pub struct Network {
+    shape: Shape,
+    hidden: usize,
+    feature_weights: Vec<i16>,
+    feature_bias: Vec<i16>,
+    output_weights: Vec<i16>,
+    output_bias: i32,
+}
+
+impl Network {
+    pub fn hidden(&self) -> usize {
+        self.hidden
+    }
+
+    pub fn symbols(&self) -> &[char] {
+        &self.shape.symbols
+    }
+
+    /// every team, piece and tile.
+    pub fn inputs(&self) -> usize {
+        self.shape.inputs()
+    }
+
+    /// whether it's made for the game's board and teams.
+    pub fn fits(&self, game: &Game) -> bool {
+        self.shape.fits(game)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(nnue_error("Not a network file.".to_string()));
+        }
+        let version = reader.u32()?;
+        if version != VERSION {
+            return Err(nnue_error(format!("Network version {} isn't supported.", version)));
+        }
+
+        let width = reader.u32()?;
+        let height = reader.u32()?;
+        let teams = reader.u32()? as usize;
+        let hidden = reader.u32()? as usize;
+        if teams == 0 || hidden == 0 {
+            return Err(nnue_error("A network needs teams and a hidden layer.".to_string()));
+        }
+        let symbol_count = reader.u32()? as usize;
+        // the sizes come from the file, so they're checked against what's left of it before
+        // anything is read into memory.
+        if symbol_count.checked_mul(4).is_none_or(|len| len > reader.remaining()) {
+            return Err(nnue_error("The network file ends too early.".to_string()));
+        }
+        let symbols = (0..symbol_count)
+            .map(|_| {
+                let code = reader.u32()?;
+                char::from_u32(code).ok_or_else(|| nnue_error(format!("{} is not a symbol.", code)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // the weights and biases are 2 bytes each and the output bias 4.
+        let inputs = width
+            .checked_mul(height)
+            .and_then(|tiles| (tiles as usize).checked_mul(teams))
+            .and_then(|len| len.checked_mul(symbol_count));
+        let len = inputs
+            .and_then(|inputs| inputs.checked_mul(hidden))
+            .and_then(|len| len.checked_add(hidden.checked_mul(3)?))
+            .and_then(|len| len.checked_mul(2))
+            .and_then(|len| len.checked_add(4));
+        if len != Some(reader.remaining()) {
+            return Err(nnue_error("The network's sizes don't match the file's.".to_string()));
+        }
+        let shape = Shape {
+            width,
+            height,
+            teams,
+            symbols,
+        };
+
+        let feature_weights = reader.i16s(shape.inputs() * hidden)?;
+        let feature_bias = reader.i16s(hidden)?;
+        let output_weights = reader.i16s(hidden * 2)?;
+        let output_bias = reader.u32()? as i32;
+
+        Ok(Self {
+            shape,
+            hidden,
+            feature_weights,
+            feature_bias,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        let header = [
+            VERSION,
+            self.shape.width,
+            self.shape.height,
+            self.shape.teams as u32,
+            self.hidden as u32,
+            self.shape.symbols.len() as u32,
+        ];
+        for value in header.into_iter().chain(self.shape.symbols.iter().map(|symbol| *symbol as u32)) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for weight in self.feature_weights.iter().chain(&self.feature_bias).chain(&self.output_weights) {
+            bytes.extend_from_slice(&weight.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.output_bias.to_le_bytes());
+        bytes
+    }
+
+    /// the score for the team to move, worked out from scratch. the game has to fit.
+    pub fn evaluate(&self, game: &Game) -> i32 {
+        let pieces = self.shape.pieces(game);
+        let sides = sides(game, self.shape.teams);
+        let accumulators: Vec<Vec<i32>> = (0..self.shape.teams).map(|side| self.refresh(&pieces, side)).collect();
+        self.output(&accumulators, sides)
+    }
+
+    fn weights(&self, feature: usize) -> &[i16] {
+        &self.feature_weights[feature * self.hidden..(feature + 1) * self.hidden]
+    }
+
+    fn refresh(&self, pieces: &[(usize, usize, usize)], side: usize) -> Vec<i32> {
+        let mut accumulator: Vec<i32> = self.feature_bias.iter().map(|bias| *bias as i32).collect();
+        for piece in pieces {
+            add(&mut accumulator, self.weights(self.shape.feature(*piece, side)));
+        }
+        accumulator
+    }
+
+    fn output(&self, accumulators: &[Vec<i32>], [current, next]: [usize; 2]) -> i32 {
+        let (own, other) = self.output_weights.split_at(self.hidden);
+        let sum = clipped_dot(&accumulators[current], own) + clipped_dot(&accumulators[next], other) + self.output_bias;
+        (sum as i64 * SCALE as i64 / (QA * QB) as i64) as i32
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .ok_or_else(|| nnue_error("The network file ends too early.".to_string()))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn i16s(&mut self, len: usize) -> Result<Vec<i16>> {
+        let len = len
+            .checked_mul(2)
+            .ok_or_else(|| nnue_error("The network file ends too early.".to_string()))?;
+        let bytes = self.take(len)?;
+        Ok(bytes.chunks_exact(2).map(|pair| i16::from_le_bytes([pair[0], pair[1]])).collect())
+    }
+}
+
+// a position on the stack.
+struct Entry {
+    // how many positions came before it, and its hash.
+    ply: usize,
+    hash: u64,
+    // the pieces the move to it took off and put on the board.
+    changes: Vec<((usize, usize, usize), bool)>,
+    // one per side, once it's been evaluated. the bottom of the stack always has them.
+    accumulators: Option<Vec<Vec<i32>>>,
+}
+
+/// a network's accumulators along a game, see the top of the file.
+pub struct Accumulators {
+    network: Arc<Network>,
+    stack: Vec<Entry>,
+}
+
+impl Accumulators {
+    pub fn new(network: Arc<Network>) -> Self {
+        Self {
+            network,
+            stack: Vec::new(),
+        }
+    }
+
+    pub fn network(&self) -> &Arc<Network> {
+        &self.network
+    }
+
+    /// executes the move on the game and notes the pieces it changed. the game has to fit the network.
+    pub fn make(&mut self, game: &mut Game, m: &Move) -> Result<()> {
+        self.follow(game);
+        game.execute_move(m)?;
+        let mut changes = Vec::new();
+        if let Some(log) = game.history().last() {
+            self.network.shape.changes(game, log, &mut changes)?;
+        }
+        self.push(game, changes);
+        Ok(())
+    }
+
+    /// passes the game's turn, nothing changes on the board.
+    pub fn pass(&mut self, game: &mut Game) {
+        self.follow(game);
+        game.pass_turn();
+        self.push(game, Vec::new());
+    }
+
+    /// undoes the game's last move or passed turn.
+    pub fn unmake(&mut self, game: &mut Game) -> Result<()> {
+        game.undo()?;
+        let ply = ply(game);
+        while self.stack.last().is_some_and(|entry| entry.ply > ply) {
+            self.stack.pop();
+        }
+        Ok(())
+    }
+
+    /// the score for the team to move. the game has to fit the network.
+    pub fn evaluate(&mut self, game: &Game) -> i32 {
+        self.follow(game);
+        let (network, stack) = (&self.network, &mut self.stack);
+
+        // from the closest position that's been evaluated.
+        let mut idx = stack.len() - 1;
+        while stack[idx].accumulators.is_none() {
+            idx -= 1;
+        }
+        for idx in idx + 1..stack.len() {
+            let mut accumulators = stack[idx - 1].accumulators.clone().unwrap();
+            for (piece, added) in &stack[idx].changes {
+                for (side, accumulator) in accumulators.iter_mut().enumerate() {
+                    let weights = network.weights(network.shape.feature(*piece, side));
+                    if *added {
+                        add(accumulator, weights);
+                    } else {
+                        sub(accumulator, weights);
+                    }
+                }
+            }
+            stack[idx].accumulators = Some(accumulators);
+        }
+
+        let accumulators = stack.last().unwrap().accumulators.as_ref().unwrap();
+        network.output(accumulators, sides(game, network.shape.teams))
+    }
+
+    fn push(&mut self, game: &Game, changes: Vec<((usize, usize, usize), bool)>) {
+        self.stack.push(Entry {
+            ply: ply(game),
+            hash: game.hash(),
+            changes,
+            accumulators: None,
+        });
+    }
+
+    // starts the stack over from the game's position, unless that's the one on top.
+    fn follow(&mut self, game: &Game) {
+        let (ply, hash) = (ply(game), game.hash());
+        if self.stack.last().is_some_and(|entry| entry.ply == ply && entry.hash == hash) {
+            return;
+        }
+        let network = &self.network;
+        let pieces = network.shape.pieces(game);
+        let accumulators = (0..network.shape.teams).map(|side| network.refresh(&pieces, side)).collect();
+        self.stack.clear();
+        self.stack.push(Entry {
+            ply,
+            hash,
+            changes: Vec::new(),
+            accumulators: Some(accumulators),
+        });
+    }
+}
+
+fn ply(game: &Game) -> usize {
+    game.hashes().len().saturating_sub(1)
+}
+
+// the hot loops, with avx2 versions picked when the cpu has it. both give the same results.
+#[inline(always)]
+fn add_scalar(accumulator: &mut [i32], weights: &[i16]) {
+    for (value, weight) in accumulator.iter_mut().zip(weights) {
+        *value += *weight as i32;
+    }
+}
+
+#[inline(always)]
+fn sub_scalar(accumulator: &mut [i32], weights: &[i16]) {
+    for (value, weight) in accumulator.iter_mut().zip(weights) {
+        *value -= *weight as i32;
+    }
+}
+
+#[inline(always)]
+fn clipped_dot_scalar(accumulator: &[i32], weights: &[i16]) -> i32 {
+    accumulator
+        .iter()
+        .zip(weights)
+        .map(|(value, weight)| (*value).clamp(0, QA) * *weight as i32)
+        .sum()
+}
+
+// 8 values at a time, the weights widened to i32. what's left over goes through the scalar loops.
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 8;
+
+    // the next 8 weights as i32s. the caller makes sure there are 8.
+    #[target_feature(enable = "avx2")]
+    unsafe fn widen(weights: &[i16]) -> __m256i {
+        _mm256_cvtepi16_epi32(_mm_loadu_si128(weights.as_ptr() as *const __m128i))
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn add(accumulator: &mut [i32], weights: &[i16]) {
+        let len = accumulator.len().min(weights.len());
+        let full = len - len % LANES;
+        for idx in (0..full).step_by(LANES) {
+            let ptr = accumulator[idx..].as_mut_ptr() as *mut __m256i;
+            let sum = _mm256_add_epi32(_mm256_loadu_si256(ptr), widen(&weights[idx..]));
+            _mm256_storeu_si256(ptr, sum);
+        }
+        super::add_scalar(&mut accumulator[full..len], &weights[full..len])
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sub(accumulator: &mut [i32], weights: &[i16]) {
+        let len = accumulator.len().min(weights.len());
+        let full = len - len % LANES;
+        for idx in (0..full).step_by(LANES) {
+            let ptr = accumulator[idx..].as_mut_ptr() as *mut __m256i;
+            let difference = _mm256_sub_epi32(_mm256_loadu_si256(ptr), widen(&weights[idx..]));
+            _mm256_storeu_si256(ptr, difference);
+        }
+        super::sub_scalar(&mut accumulator[full..len], &weights[full..len])
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn clipped_dot(accumulator: &[i32], weights: &[i16]) -> i32 {
+        let len = accumulator.len().min(weights.len());
+        let full = len - len % LANES;
+        let (zero, one) = (_mm256_setzero_si256(), _mm256_set1_epi32(super::QA));
+        let mut sums = zero;
+        for idx in (0..full).step_by(LANES) {
+            let values = _mm256_loadu_si256(accumulator[idx..].as_ptr() as *const __m256i);
+            let clipped = _mm256_min_epi32(_mm256_max_epi32(values, zero), one);
+            sums = _mm256_add_epi32(sums, _mm256_mullo_epi32(clipped, widen(&weights[idx..])));
+        }
+
+        let mut lanes = [0i32; LANES];
+        _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sums);
+        lanes.iter().sum::<i32>() + super::clipped_dot_scalar(&accumulator[full..len], &weights[full..len])
+    }
+}
+
+fn add(accumulator: &mut [i32], weights: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // the cpu was just checked for avx2.
+        return unsafe { avx2::add(accumulator, weights) };
+    }
+    add_scalar(accumulator, weights)
+}
+
+fn sub(accumulator: &mut [i32], weights: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // the cpu was just checked for avx2.
+        return unsafe { avx2::sub(accumulator, weights) };
+    }
+    sub_scalar(accumulator, weights)
+}
+
+fn clipped_dot(accumulator: &[i32], weights: &[i16]) -> i32 {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // the cpu was just checked for avx2.
+        return unsafe { avx2::clipped_dot(accumulator, weights) };
+    }
+    clipped_dot_scalar(accumulator, weights)
+}
+
+// a sample's inputs from the team to move's side and the next team's, and what it should score.
+struct TrainingSample {
+    features: [Vec<usize>; 2],
+    target: f32,
+}
+
+/// fits a network to samples from datagen, with adam on the squared error of the predicted result.
+/// the target mixes the search's score with the game's result, by lambda.
+pub struct Trainer {
+    variant: Variant,
+    shape: Shape,
+    hidden: usize,
+    // feature weights, feature biases, output weights and the output bias, in a row.
+    weights: Vec<f32>,
+    moments: Vec<(f32, f32)>,
+    steps: i32,
+    samples: Vec<TrainingSample>,
+    lambda: f32,
+    rng: u64,
+}
+
+impl Trainer {
+    /// a network for the variant's board and pieces, with random weights.
+    pub fn new(variant: &Variant, hidden: usize) -> Result<Self> {
+        let shape = Shape::of(&variant.start()?, variant.catalog());
+        let hidden = hidden.max(1);
+        let len = shape.inputs() * hidden + hidden + hidden * 2 + 1;
+        let mut trainer = Self {
+            variant: variant.clone(),
+            shape,
+            hidden,
+            weights: vec![0.0; len],
+            moments: vec![(0.0, 0.0); len],
+            steps: 0,
+            samples: Vec::new(),
+            lambda: DEFAULT_LAMBDA,
+            rng: 0x9e3779b97f4a7c15,
+        };
+        trainer.randomize();
+        Ok(trainer)
+    }
+
+    pub fn with_lambda(mut self, lambda: f32) -> Self {
+        self.lambda = lambda.clamp(0.0, 1.0);
+        self
+    }
+
+    /// different random weights to start from.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = seed | 1;
+        self.randomize();
+        self
+    }
+
+    fn randomize(&mut self) {
+        let (biases, outputs) = (self.shape.inputs() * self.hidden, self.shape.inputs() * self.hidden + self.hidden);
+        for idx in 0..self.weights.len() {
+            let random = self.random() as f32 / u64::MAX as f32 - 0.5;
+            self.weights[idx] = match idx {
+                // half of the hidden layer starts out clipped at 0 otherwise.
+                _ if idx >= biases && idx < outputs => 0.25,
+                _ if idx >= outputs => random / 2.0,
+                _ => random / 5.0,
+            };
+        }
+        self.moments.fill((0.0, 0.0));
+        self.steps = 0;
+    }
+
+    fn random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+
+    pub fn add_sample(&mut self, sample: &Sample) -> Result<()> {
+        let game = self.variant.game(&sample.fen)?;
+        let pieces = self.shape.pieces(&game);
+        let features =
+            sides(&game, self.shape.teams).map(|side| pieces.iter().map(|piece| self.shape.feature(*piece, side)).collect::<Vec<_>>());
+        let expected = sigmoid(sample.score as f32 / SCALE as f32);
+        let target = self.lambda * expected + (1.0 - self.lambda) * sample.result as f32;
+        self.samples.push(TrainingSample { features, target });
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// the mean squared error of the predictions.
+    pub fn error(&self) -> f64 {
+        let total: f64 = self
+            .samples
+            .iter()
+            .map(|sample| (sigmoid(self.forward(sample).0) - sample.target).powi(2) as f64)
+            .sum();
+        total / self.samples.len().max(1) as f64
+    }
+
+    // the output, and the hidden layers before they're clipped.
+    fn forward(&self, sample: &TrainingSample) -> (f32, [Vec<f32>; 2]) {
+        let hidden = self.hidden;
+        let biases = self.shape.inputs() * hidden;
+        let outputs = biases + hidden;
+        let layers = sample.features.clone().map(|features| {
+            let mut layer = self.weights[biases..outputs].to_vec();
+            for feature in features {
+                for (value, weight) in layer.iter_mut().zip(&self.weights[feature * hidden..(feature + 1) * hidden]) {
+                    *value += weight;
+                }
+            }
+            layer
+        });
+
+        let mut output = self.weights[outputs + hidden * 2];
+        for (half, layer) in layers.iter().enumerate() {
+            let weights = &self.weights[outputs + half * hidden..outputs + (half + 1) * hidden];
+            output += layer
+                .iter()
+                .zip(weights)
+                .map(|(value, weight)| value.clamp(0.0, 1.0) * weight)
+                .sum::<f32>();
+        }
+        (output, layers)
+    }
+
+    /// goes over every sample epochs times in a random order, giving back the error after.
+    pub fn train(&mut self, epochs: usize) -> f64 {
+        let hidden = self.hidden;
+        let biases = self.shape.inputs() * hidden;
+        let outputs = biases + hidden;
+        let mut order: Vec<usize> = (0..self.samples.len()).collect();
+        let mut gradient = vec![0.0f32; self.weights.len()];
+
+        for _ in 0..epochs {
+            for idx in (1..order.len()).rev() {
+                let other = (self.random() % (idx as u64 + 1)) as usize;
+                order.swap(idx, other);
+            }
+
+            for batch in order.chunks(BATCH_SIZE) {
+                gradient.fill(0.0);
+                for sample_idx in batch {
+                    let sample = &self.samples[*sample_idx];
+                    let (output, layers) = self.forward(sample);
+                    let predicted = sigmoid(output);
+                    let slope = 2.0 * (predicted - sample.target) * predicted * (1.0 - predicted) / batch.len() as f32;
+
+                    gradient[outputs + hidden * 2] += slope;
+                    for (half, layer) in layers.iter().enumerate() {
+                        for (h, value) in layer.iter().enumerate() {
+                            let output_weight = outputs + half * hidden + h;
+                            gradient[output_weight] += slope * value.clamp(0.0, 1.0);
+                            // the clip lets nothing through outside 0..1.
+                            if *value <= 0.0 || *value >= 1.0 {
+                                continue;
+                            }
+                            let layer_slope = slope * self.weights[output_weight];
+                            gradient[biases + h] += layer_slope;
+                            for feature in &sample.features[half] {
+                                gradient[feature * hidden + h] += layer_slope;
+                            }
+                        }
+                    }
+                }
+                self.step(&gradient);
+            }
+        }
+        self.error()
+    }
+
+    fn step(&mut self, gradient: &[f32]) {
+        let outputs = self.shape.inputs() * self.hidden + self.hidden;
+        self.steps += 1;
+        for (idx, gradient) in gradient.iter().enumerate() {
+            let (m, v) = &mut self.moments[idx];
+            *m = BETA1 * *m + (1.0 - BETA1) * gradient;
+            *v = BETA2 * *v + (1.0 - BETA2) * gradient * gradient;
+            let m_hat = *m / (1.0 - BETA1.powi(self.steps));
+            let v_hat = *v / (1.0 - BETA2.powi(self.steps));
+            let weight = self.weights[idx] - LEARNING_RATE * m_hat / (v_hat.sqrt() + EPSILON);
+            let limit = if idx < outputs { MAX_FEATURE_WEIGHT } else { MAX_OUTPUT_WEIGHT };
+            // the output bias isn't multiplied with anything, it can be as big as it needs.
+            self.weights[idx] = if idx == self.weights.len() - 1 {
+                weight
+            } else {
+                weight.clamp(-limit, limit)
+            };
+        }
+    }
+
+    /// the weights rounded to the network's integers.
+    pub fn network(&self) -> Network {
+        let hidden = self.hidden;
+        let biases = self.shape.inputs() * hidden;
+        let outputs = biases + hidden;
+        let round = |weights: &[f32], scale: i32| weights.iter().map(|weight| (weight * scale as f32).round() as i16).collect();
+        Network {
+            shape: self.shape.clone(),
+            hidden,
+            feature_weights: round(&self.weights[..biases], QA),
+            feature_bias: round(&self.weights[biases..outputs], QA),
+            output_weights: round(&self.weights[outputs..outputs + hidden * 2], QB),
+            output_bias: (self.weights[outputs + hidden * 2] * (QA * QB) as f32).round() as i32,
+        }
+    }
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
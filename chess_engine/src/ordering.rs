@@ -0,0 +1,181 @@
+// move ordering, the sooner the best move is searched the more of the others get cut off.
+// in order: the transposition table's move, kills and promotions by most valuable victim and
+// least valuable killer, the killer moves of the ply, the countermove to the last move, then
+// everything else by history.
+// pieces are told apart by team and name, unnamed ones by their betza notation.
+
+use std::collections::HashMap;
+
+use chess::error::Result;
+use chess::game::Game;
+use chess::piece::{Piece, PieceRef};
+use chess::r#move::{Coord, Move, MoveKey};
+
+use crate::search::MAX_DEPTH;
+use crate::see::kill_value;
+
+const TT_MOVE: i32 = 10_000_000;
+const KILL: i32 = 1_000_000;
+const KILLER: [i32; 2] = [900_000, 899_999];
+const COUNTERMOVE: i32 = 800_000;
+// history scores are halved once one gets past this, so they stay under the countermove.
+const HISTORY_MAX: i32 = 400_000;
+
+pub struct MoveOrdering {
+    width: u32,
+    height: u32,
+    // team name, then piece name.
+    kinds: HashMap<String, HashMap<String, usize>>,
+    // quiet moves that caused a cut off, two per ply.
+    killers: Vec<[Option<MoveKey>; 2]>,
+    // per piece kind, per destination tile.
+    history: Vec<Vec<i32>>,
+    // per piece kind and destination tile of the last move, the move that refuted it.
+    countermoves: Vec<Vec<Option<MoveKey>>>,
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            kinds: HashMap::new(),
+            killers: vec![[None; 2]; MAX_DEPTH as usize + 1],
+            history: Vec::new(),
+            countermoves: Vec::new(),
+        }
+    }
+
+    /// forgets the killers and fades the history.
+    pub fn new_search(&mut self, game: &Game) {
+        self.fit(game);
+        self.killers.iter_mut().for_each(|killers| *killers = [None; 2]);
+        for scores in &mut self.history {
+            scores.iter_mut().for_each(|score| *score /= 2);
+        }
+    }
+
+    /// sorts the moves best first.
+    pub fn sort(&mut self, game: &Game, moves: &mut Vec<Move>, tt_move: Option<MoveKey>, ply: i32) -> Result<()> {
+        self.fit(game);
+        let killers = self.killers.get(ply as usize).copied().unwrap_or([None; 2]);
+        let countermove = match game.history().last() {
+            Some(last) => {
+                let kind = self.kind_of(&last.moved_piece())?;
+                self.countermoves[kind][self.index(last.to())]
+            }
+            None => None,
+        };
+
+        let mut scored = Vec::with_capacity(moves.len());
+        for m in moves.drain(..) {
+            let key = m.key();
+            let score = if Some(key) == tt_move {
+                TT_MOVE
+            } else if m.is_kill() || m.promotion().is_some() {
+                KILL + mvv_lva(game, &m)?
+            } else if let Some(idx) = killers.iter().position(|killer| *killer == Some(key)) {
+                KILLER[idx]
+            } else if Some(key) == countermove {
+                COUNTERMOVE
+            } else {
+                let kind = self.kind_of(&m.piece())?;
+                self.history[kind][self.index(m.to())]
+            };
+            scored.push((score, m));
+        }
+
+        scored.sort_by_key(|(score, _)| -score);
+        moves.extend(scored.into_iter().map(|(_, m)| m));
+        Ok(())
+    }
+
+    /// a quiet move caused a cut off, remembers it for the ply, its piece and the move it answered.
+    pub fn cut_off(&mut self, game: &Game, m: &Move, ply: i32, depth: u32) -> Result<()> {
+        if m.is_kill() || m.promotion().is_some() {
+            return Ok(());
+        }
+        self.fit(game);
+        let key = m.key();
+
+        if let Some(killers) = self.killers.get_mut(ply as usize) {
+            if killers[0] != Some(key) {
+                killers[1] = killers[0];
+                killers[0] = Some(key);
+            }
+        }
+
+        let kind = self.kind_of(&m.piece())?;
+        let idx = self.index(m.to());
+        self.history[kind][idx] += (depth * depth) as i32;
+        if self.history[kind][idx] > HISTORY_MAX {
+            for scores in &mut self.history {
+                scores.iter_mut().for_each(|score| *score /= 2);
+            }
+        }
+
+        if let Some(last) = game.history().last() {
+            let kind = self.kind_of(&last.moved_piece())?;
+            let idx = self.index(last.to());
+            self.countermoves[kind][idx] = Some(key);
+        }
+
+        Ok(())
+    }
+
+    // the tables are per tile, so they're sized for the game's board on first use and thrown
+    // away when the board dimensions change.
+    fn fit(&mut self, game: &Game) {
+        let board = game.board();
+        if board.width() != self.width || board.height() != self.height {
+            self.width = board.width();
+            self.height = board.height();
+            self.kinds.clear();
+            self.history.clear();
+            self.countermoves.clear();
+        }
+    }
+
+    fn kind_of(&mut self, piece: &Piece) -> Result<usize> {
+        let lock = piece.read()?;
+        Ok(self.kind(&lock))
+    }
+
+    // a new kind gets its own history and countermove tables.
+    fn kind(&mut self, data: &PieceRef) -> usize {
+        let team = data.team_unchecked();
+        let next = self.history.len();
+        if !self.kinds.contains_key(team.name()) {
+            self.kinds.insert(team.name().to_string(), HashMap::new());
+        }
+        let names = self.kinds.get_mut(team.name()).unwrap();
+
+        let kind = match names.get(data.name()) {
+            Some(kind) => *kind,
+            None if data.name().is_empty() => *names.entry(data.betza()).or_insert(next),
+            None => *names.entry(data.name().to_string()).or_insert(next),
+        };
+        if kind == next {
+            let tiles = (self.width * self.height) as usize;
+            self.history.push(vec![0; tiles]);
+            self.countermoves.push(vec![None; tiles]);
+        }
+        kind
+    }
+
+    fn index(&self, coord: Coord) -> usize {
+        (coord.y() * self.width + coord.x()) as usize
+    }
+}
+
+/// most valuable victim, least valuable killer. the victims count first, the killer only breaks ties.
+pub fn mvv_lva(game: &Game, m: &Move) -> Result<i32> {
+    let killer = m.piece().read()?.points() as i32;
+    Ok(kill_value(game, m)? * 16 - killer)
+}
@@ -0,0 +1,734 @@
+// negamax with alpha-beta pruning and iterative deepening over the game's legal moves, with a
+// quiescence search over kills and promotions at the leaves.
+// moves are made and unmade on the game itself, so it's left the way it was found.
+// with tablebases, positions in them are scored from the tables instead of being searched.
+// with more than one pv, each line is the best of the moves the lines before it left out, and
+// lines are reported as they're found. the root can be kept to some of its moves, ex. for uci's
+// searchmoves.
+
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chess::error::Result;
+use chess::game::Game;
+use chess::r#move::{Move, MoveKey};
+
+use crate::eval::{EvalParams, Evaluator};
+use crate::nnue::Network;
+use crate::ordering::{mvv_lva, MoveOrdering};
+use crate::see::{kill_value, see};
+use crate::tablebase::{Outcome, Tablebases};
+use crate::time::TimeManager;
+use crate::tt::{Bound, TranspositionTable, TtEntry};
+
+/// score of a royal piece being taken, far above any evaluation, minus the plies it takes to get there.
+pub const MATE: i32 = 1_000_000;
+/// how deep iterative deepening goes when there's no depth limit.
+pub const MAX_DEPTH: u32 = 64;
+
+// how far past the root quiescence search goes before it settles for the evaluation.
+const MAX_PLY: i32 = 2 * MAX_DEPTH as i32;
+// how much a kill can be worth beyond the points it takes, before quiescence search skips it.
+const DELTA_MARGIN: i32 = 200;
+// how many nodes are searched between looks at the clock.
+const TIME_CHECK_INTERVAL: u64 = 1024;
+// which depths helper threads skip, so they don't all search the same thing: helper n skips the
+// depths where (depth + SKIP_PHASE[n]) / SKIP_SIZE[n] is odd.
+const SKIP_SIZE: [u32; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+const SKIP_PHASE: [u32; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+/// when to stop searching. without any limits the search goes to MAX_DEPTH.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    pub fn nodes(mut self, nodes: u64) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    pub fn movetime(mut self, movetime: Duration) -> Self {
+        self.movetime = Some(movetime);
+        self
+    }
+
+    // whether a search that started at start and searched nodes has to stop.
+    pub(crate) fn reached(&self, start: Instant, nodes: u64) -> bool {
+        if self.nodes.is_some_and(|limit| nodes >= limit) {
+            return true;
+        }
+        nodes.is_multiple_of(TIME_CHECK_INTERVAL) && self.movetime.is_some_and(|movetime| start.elapsed() >= movetime)
+    }
+}
+
+/// a move with the moves expected after it, and its score for the team to move.
+#[derive(Clone)]
+pub struct PvLine {
+    pub score: i32,
+    pub pv: Vec<Move>,
+}
+
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    // from the point of view of the team to move.
+    pub score: i32,
+    // the moves both sides are expected to play, starting with best_move.
+    pub pv: Vec<Move>,
+    // the best lines, the first one being best_move's. only one without multi pv.
+    pub lines: Vec<PvLine>,
+    // the deepest iteration that finished.
+    pub depth: u32,
+    pub nodes: u64,
+}
+
+/// a line of a finished iteration, reported while the search goes on.
+pub struct Iteration<'a> {
+    pub depth: u32,
+    // which line it is, from 1.
+    pub multipv: usize,
+    pub score: i32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub pv: &'a [Move],
+}
+
+/// called with the game being searched after every finished iteration.
+pub type OnIteration = Box<dyn FnMut(&Game, &Iteration) + Send>;
+
+/// whether the score means someone gets mated.
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE - MAX_DEPTH as i32
+}
+
+pub struct Searcher {
+    limits: SearchLimits,
+    evaluator: Evaluator,
+    tt: Arc<TranspositionTable>,
+    ordering: MoveOrdering,
+    // without it moves are searched in the order they're generated in.
+    order_moves: bool,
+    // set from outside to stop the search.
+    stop: Option<Arc<AtomicBool>>,
+    time: Option<TimeManager>,
+    on_iteration: Option<OnIteration>,
+    tablebases: Option<Arc<Tablebases>>,
+    multi_pv: usize,
+    // the only moves searched at the root, every legal move without it.
+    root_moves: Option<Vec<MoveKey>>,
+    // 0 for the main thread, helpers skip some depths.
+    thread: usize,
+    start: Instant,
+    nodes: u64,
+    stopped: bool,
+}
+
+impl Searcher {
+    pub fn new(limits: SearchLimits) -> Self {
+        Self {
+            limits,
+            evaluator: Evaluator::new(),
+            tt: Arc::new(TranspositionTable::default()),
+            ordering: MoveOrdering::new(),
+            order_moves: true,
+            stop: None,
+            time: None,
+            on_iteration: None,
+            tablebases: None,
+            multi_pv: 1,
+            root_moves: None,
+            thread: 0,
+            start: Instant::now(),
+            nodes: 0,
+            stopped: false,
+        }
+    }
+
+    /// shares a table with other searchers, or keeps one between searches.
+    pub fn with_table(mut self, tt: Arc<TranspositionTable>) -> Self {
+        self.tt = tt;
+        self
+    }
+
+    /// on by default, turning it off is only useful to measure what it saves.
+    pub fn with_move_ordering(mut self, order_moves: bool) -> Self {
+        self.order_moves = order_moves;
+        self
+    }
+
+    /// the search stops as soon as it sees the flag set, ex. when another thread is done.
+    pub fn with_stop_flag(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    /// positions in the tables get their exact score instead of being searched.
+    pub fn with_tablebases(mut self, tablebases: Arc<Tablebases>) -> Self {
+        self.tablebases = Some(tablebases);
+        self
+    }
+
+    /// tuned evaluation weights instead of the defaults.
+    pub fn with_eval_params(mut self, params: EvalParams) -> Self {
+        self.evaluator = std::mem::take(&mut self.evaluator).with_params(params);
+        self
+    }
+
+    /// evaluates with a network, in the games it fits.
+    pub fn with_network(mut self, network: Option<Arc<Network>>) -> Self {
+        self.evaluator = std::mem::take(&mut self.evaluator).with_network(network);
+        self
+    }
+
+    pub(crate) fn with_thread(mut self, thread: usize) -> Self {
+        self.thread = thread;
+        self
+    }
+
+    /// how many lines to find, at least one.
+    pub fn with_multi_pv(mut self, multi_pv: usize) -> Self {
+        self.set_multi_pv(multi_pv);
+        self
+    }
+
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    pub fn set_multi_pv(&mut self, multi_pv: usize) {
+        self.multi_pv = multi_pv.max(1);
+    }
+
+    /// only these moves are searched at the root, the ones that aren't legal are left out. none,
+    /// or none of them being legal, searches every legal move.
+    pub fn set_root_moves(&mut self, root_moves: Option<Vec<MoveKey>>) {
+        self.root_moves = root_moves;
+    }
+
+    /// static scores off by up to noise centipoints, for weaker play. see Evaluator::set_noise.
+    pub fn set_eval_noise(&mut self, noise: i32, seed: u64) {
+        self.evaluator.set_noise(noise, seed);
+    }
+
+    /// for the next search, on top of the limits. its clock is already running.
+    pub fn set_time_manager(&mut self, time: Option<TimeManager>) {
+        self.time = time;
+    }
+
+    pub fn set_on_iteration(&mut self, on_iteration: Option<OnIteration>) {
+        self.on_iteration = on_iteration;
+    }
+
+    pub fn table(&self) -> &Arc<TranspositionTable> {
+        &self.tt
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn search(&mut self, game: &mut Game) -> Result<SearchResult> {
+        self.begin(game);
+
+        let mut moves = game.legal_moves()?;
+        let restricted = self.restrict(&mut moves);
+        if moves.is_empty() {
+            self.time = None;
+            return Ok(SearchResult {
+                best_move: None,
+                score: terminal_score(game, 0),
+                pv: Vec::new(),
+                lines: Vec::new(),
+                depth: 0,
+                nodes: 1,
+            });
+        }
+
+        let max_depth = self.limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+        let multi_pv = self.multi_pv.min(moves.len());
+        let mut result = SearchResult {
+            best_move: Some(moves[0].clone()),
+            score: -MATE,
+            pv: vec![moves[0].clone()],
+            lines: Vec::new(),
+            depth: 0,
+            nodes: 0,
+        };
+
+        for depth in 1..=max_depth {
+            if self.skips(depth) {
+                continue;
+            }
+
+            let mut lines: Vec<PvLine> = Vec::new();
+            let mut left = moves.clone();
+            let mut finished = true;
+            while lines.len() < multi_pv {
+                let (score, mut pv, done) = self.root(game, &left, depth)?;
+                self.extend_pv(game, &mut pv, depth)?;
+
+                if !done {
+                    // a cut off iteration only counts when nothing finished before it.
+                    if lines.is_empty() && result.depth == 0 && !pv.is_empty() {
+                        lines.push(PvLine { score, pv });
+                    }
+                    finished = false;
+                    break;
+                }
+                // the root's entry is for the best of every move.
+                if lines.is_empty() && !restricted {
+                    self.tt.store(
+                        game.hash(),
+                        0,
+                        TtEntry {
+                            depth,
+                            bound: Bound::Exact,
+                            score,
+                            best_move: pv.first().map(|m| m.key()),
+                        },
+                    );
+                }
+                left.retain(|m| m.key() != pv[0].key());
+                lines.push(PvLine { score, pv });
+
+                if let Some(on_iteration) = &mut self.on_iteration {
+                    let line = lines.last().unwrap();
+                    let iteration = Iteration {
+                        depth,
+                        multipv: lines.len(),
+                        score: line.score,
+                        nodes: self.nodes,
+                        elapsed: self.start.elapsed(),
+                        pv: &line.pv,
+                    };
+                    on_iteration(game, &iteration);
+                }
+            }
+
+            // lines from the last depth fill in for the ones that didn't finish.
+            for line in std::mem::take(&mut result.lines) {
+                if lines.len() < multi_pv && lines.iter().all(|l| l.pv[0].key() != line.pv[0].key()) {
+                    lines.push(line);
+                }
+            }
+            result.lines = lines;
+            if let Some(best) = result.lines.first() {
+                result.best_move = Some(best.pv[0].clone());
+                result.score = best.score;
+                result.pv = best.pv.clone();
+            }
+            if !finished {
+                break;
+            }
+            result.depth = depth;
+
+            if let Some(time) = &mut self.time {
+                time.iteration(result.pv[0].key(), result.score);
+                if !time.keep_going() {
+                    break;
+                }
+            }
+
+            // the lines' moves go first in the next iteration, best first.
+            for line in result.lines.iter().rev() {
+                let key = line.pv[0].key();
+                if let Some(idx) = moves.iter().position(|m| m.key() == key) {
+                    let m = moves.remove(idx);
+                    moves.insert(0, m);
+                }
+            }
+
+            // nothing deeper can change forced mates that fit in this depth.
+            if result
+                .lines
+                .iter()
+                .all(|line| is_mate_score(line.score) && MATE - line.score.abs() <= depth as i32)
+            {
+                break;
+            }
+        }
+
+        // a time manager is only good for one search.
+        self.time = None;
+        result.nodes = self.nodes;
+        Ok(result)
+    }
+
+    /// every legal move searched to depth with its exact score, best first. on_move gets each one
+    /// as soon as it's been searched. once the search is stopped the moves left are left out.
+    pub fn evaluate_moves(
+        &mut self,
+        game: &mut Game,
+        depth: u32,
+        mut on_move: impl FnMut(&Game, &PvLine),
+    ) -> Result<Vec<PvLine>> {
+        self.begin(game);
+        let depth = depth.clamp(1, MAX_DEPTH);
+
+        let mut lines = Vec::new();
+        let mut child_pv = Vec::new();
+        for m in game.legal_moves()? {
+            self.evaluator.make(game, &m)?;
+            let score = -self.negamax(game, depth - 1, 1, -MATE - 1, MATE + 1, &mut child_pv)?;
+            self.evaluator.unmake(game)?;
+            if self.stopped {
+                break;
+            }
+
+            let mut pv = vec![m];
+            pv.append(&mut child_pv);
+            self.extend_pv(game, &mut pv, depth)?;
+            let line = PvLine { score, pv };
+            on_move(game, &line);
+            lines.push(line);
+        }
+
+        self.time = None;
+        lines.sort_by_key(|line| Reverse(line.score));
+        Ok(lines)
+    }
+
+    // keeps the root moves, if any of them are legal. true if it left some out.
+    fn restrict(&self, moves: &mut Vec<Move>) -> bool {
+        let Some(keys) = &self.root_moves else {
+            return false;
+        };
+        if !moves.iter().any(|m| keys.contains(&m.key())) {
+            return false;
+        }
+        let len = moves.len();
+        moves.retain(|m| keys.contains(&m.key()));
+        moves.len() < len
+    }
+
+    fn begin(&mut self, game: &Game) {
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.stopped = false;
+        // helpers share the main thread's table, it only ages once per search.
+        if self.thread == 0 {
+            self.tt.new_search();
+        }
+        self.ordering.new_search(game);
+    }
+
+    // (score, pv, whether every move was searched)
+    fn root(&mut self, game: &mut Game, moves: &[Move], depth: u32) -> Result<(i32, Vec<Move>, bool)> {
+        let mut alpha = -MATE - 1;
+        let beta = MATE + 1;
+        let mut pv = Vec::new();
+        let mut child_pv = Vec::new();
+
+        for m in moves {
+            self.evaluator.make(game, m)?;
+            let score = -self.negamax(game, depth - 1, 1, -beta, -alpha, &mut child_pv)?;
+            self.evaluator.unmake(game)?;
+
+            if self.stopped {
+                return Ok((alpha, pv, false));
+            }
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(m.clone());
+                pv.append(&mut child_pv);
+            }
+        }
+
+        Ok((alpha, pv, true))
+    }
+
+    fn negamax(
+        &mut self,
+        game: &mut Game,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Move>,
+    ) -> Result<i32> {
+        pv.clear();
+        if depth == 0 {
+            return self.quiescence(game, ply, alpha, beta);
+        }
+        self.nodes += 1;
+        if self.should_stop() {
+            return Ok(0);
+        }
+
+        // fifty move rule and repetitions.
+        if game.halfmove_clock() >= 100 || game.repetitions() > 0 {
+            return Ok(0);
+        }
+        if let Some(score) = self.probe_tablebases(game, ply) {
+            return Ok(score);
+        }
+
+        let hash = game.hash();
+        let tt_entry = self.tt.probe(hash, ply);
+        if let Some(entry) = tt_entry.filter(|entry| entry.depth >= depth) {
+            let usable = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.score >= beta,
+                Bound::Upper => entry.score <= alpha,
+            };
+            if usable {
+                return Ok(entry.score);
+            }
+        }
+
+        let mut moves = game.legal_moves()?;
+        if moves.is_empty() {
+            return Ok(terminal_score(game, ply));
+        }
+        if self.order_moves {
+            self.ordering.sort(game, &mut moves, tt_entry.and_then(|entry| entry.best_move), ply)?;
+        }
+
+        let original_alpha = alpha;
+        let mut best_move = None;
+        let mut best = -MATE - 1;
+        let mut child_pv = Vec::new();
+        for m in moves {
+            self.evaluator.make(game, &m)?;
+            let score = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha, &mut child_pv)?;
+            self.evaluator.unmake(game)?;
+
+            if self.stopped {
+                return Ok(0);
+            }
+            if score > best {
+                best = score;
+                best_move = Some(m.key());
+            }
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(m.clone());
+                pv.append(&mut child_pv);
+            }
+            if alpha >= beta {
+                if self.order_moves {
+                    self.ordering.cut_off(game, &m, ply, depth)?;
+                }
+                break;
+            }
+        }
+
+        let bound = if best >= beta {
+            Bound::Lower
+        } else if best > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.tt.store(
+            hash,
+            ply,
+            TtEntry {
+                depth,
+                bound,
+                score: best,
+                best_move,
+            },
+        );
+
+        Ok(best)
+    }
+
+    // only kills and promotions are searched, unless the team to move is in check. the team can
+    // also stop killing and take the evaluation, so a losing kill never has to be made.
+    fn quiescence(&mut self, game: &mut Game, ply: i32, mut alpha: i32, beta: i32) -> Result<i32> {
+        self.nodes += 1;
+        if self.should_stop() {
+            return Ok(0);
+        }
+        if game.halfmove_clock() >= 100 || game.repetitions() > 0 {
+            return Ok(0);
+        }
+        if let Some(score) = self.probe_tablebases(game, ply) {
+            return Ok(score);
+        }
+
+        let in_check = game.is_in_check(game.current_team().name());
+        let mut best = -MATE - 1;
+        let mut stand_pat = -MATE - 1;
+        if !in_check {
+            stand_pat = self.evaluator.evaluate(game);
+            if stand_pat >= beta || ply >= MAX_PLY {
+                return Ok(stand_pat);
+            }
+            alpha = alpha.max(stand_pat);
+            best = stand_pat;
+        }
+
+        let moves = game.legal_moves_matching(|m| in_check || m.is_kill() || m.promotion().is_some())?;
+        if in_check && moves.is_empty() {
+            return Ok(terminal_score(game, ply));
+        }
+
+        let mut noisy = Vec::new();
+        for m in moves {
+            noisy.push((mvv_lva(game, &m)?, kill_value(game, &m)?, m));
+        }
+        if self.order_moves {
+            noisy.sort_by_key(|(order, _, _)| -order);
+        }
+
+        for (_, value, m) in noisy {
+            // hopeless even if nothing gets killed back.
+            if !in_check && stand_pat + value + DELTA_MARGIN <= alpha {
+                continue;
+            }
+            // killing something worth at least the killer can't lose anything.
+            let risky = value < m.piece().read()?.points() as i32 * 100;
+            if !in_check && m.promotion().is_none() && risky && see(game, &m)? < 0 {
+                continue;
+            }
+
+            self.evaluator.make(game, &m)?;
+            let score = -self.quiescence(game, ply + 1, -beta, -alpha)?;
+            self.evaluator.unmake(game)?;
+
+            if self.stopped {
+                return Ok(0);
+            }
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        Ok(best)
+    }
+
+    // a cut off from the table ends the pv early, the rest of it is the table's best moves.
+    fn extend_pv(&self, game: &mut Game, pv: &mut Vec<Move>, depth: u32) -> Result<()> {
+        for m in pv.iter() {
+            game.execute_move(m)?;
+        }
+        let mut played = pv.len();
+
+        while played < depth as usize && game.repetitions() == 0 {
+            let key = self.tt.probe(game.hash(), played as i32).and_then(|entry| entry.best_move);
+            let Some(key) = key else {
+                break;
+            };
+            let Some(m) = game.legal_moves()?.into_iter().find(|m| m.key() == key) else {
+                break;
+            };
+            game.execute_move(&m)?;
+            pv.push(m);
+            played += 1;
+        }
+
+        for _ in 0..played {
+            game.undo()?;
+        }
+        Ok(())
+    }
+
+    // mates from the tables count from the root like the ones the search finds.
+    fn probe_tablebases(&self, game: &Game, ply: i32) -> Option<i32> {
+        match self.tablebases.as_ref()?.probe(game)? {
+            Outcome::Win(plies) => Some(MATE - ply - plies as i32),
+            Outcome::Loss(plies) => Some(-MATE + ply + plies as i32),
+            Outcome::Draw => Some(0),
+        }
+    }
+
+    fn should_stop(&mut self) -> bool {
+        self.stopped = self.stopped
+            || self.limits.reached(self.start, self.nodes)
+            || self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed))
+            || self
+                .time
+                .as_ref()
+                .is_some_and(|time| self.nodes.is_multiple_of(TIME_CHECK_INTERVAL) && time.out_of_time());
+        self.stopped
+    }
+
+    // depth 1 is never skipped, so every thread has a move.
+    fn skips(&self, depth: u32) -> bool {
+        if self.thread == 0 || depth == 1 {
+            return false;
+        }
+        let idx = (self.thread - 1) % SKIP_SIZE.len();
+        (depth + SKIP_PHASE[idx]) / SKIP_SIZE[idx] % 2 == 1
+    }
+}
+
+pub fn search(game: &mut Game, limits: SearchLimits) -> Result<SearchResult> {
+    Searcher::new(limits).search(game)
+}
+
+/// every legal move with its score to depth, best first.
+pub fn evaluate_moves(game: &mut Game, depth: u32) -> Result<Vec<PvLine>> {
+    Searcher::new(SearchLimits::new()).evaluate_moves(game, depth, |_, _| {})
+}
+
+/// the legal moves for the keys, played one after the other on the game and taken back after.
+/// stops at the first key that isn't legal. gets the moves of a pv found on another copy of the game.
+pub fn resolve_pv(game: &mut Game, keys: &[MoveKey]) -> Vec<Move> {
+    let mut pv = Vec::new();
+    for key in keys {
+        let m = game
+            .legal_moves()
+            .ok()
+            .and_then(|moves| moves.into_iter().find(|m| m.key() == *key));
+        let Some(m) = m else {
+            break;
+        };
+        if game.execute_move(&m).is_err() {
+            break;
+        }
+        pv.push(m);
+    }
+
+    for _ in 0..pv.len() {
+        let _ = game.undo();
+    }
+    pv
+}
+
+// no legal moves, mated when in check and stalemated otherwise.
+fn terminal_score(game: &Game, ply: i32) -> i32 {
+    if game.is_in_check(game.current_team().name()) {
+        -MATE + ply
+    } else {
+        0
+    }
+}
+
+/// points of the team to move minus everyone else's.
+pub fn material(game: &Game) -> i32 {
+    let current_team = game.current_team();
+
+    let mut score = 0;
+    for set in game.board().piece_sets() {
+        let sign = if set.team().name() == current_team.name() { 1 } else { -1 };
+        for piece in set.pieces() {
+            let lock = piece.read().unwrap();
+            if lock.is_alive() {
+                score += sign * lock.points() as i32;
+            }
+        }
+    }
+
+    score
+}
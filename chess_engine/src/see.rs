@@ -0,0 +1,74 @@
+// static exchange evaluation, what a kill wins once both sides are done killing on its tile.
+// the usual attack map shortcuts don't hold for user made pieces: a cannon's screen can be the
+// piece that just moved, and a pierce kills every piece on its way. so the exchange is played
+// out on the game with real moves, and each kill is worth every piece it takes.
+// values are in centipoints, like the evaluation.
+
+use chess::error::Result;
+use chess::game::Game;
+use chess::r#move::{Coord, Move};
+
+// how many kills deep an exchange is followed.
+const MAX_EXCHANGE: u32 = 16;
+
+/// the points of every piece the move kills, plus what a promotion adds.
+pub fn kill_value(game: &Game, m: &Move) -> Result<i32> {
+    let mut value = 0;
+    for coord in m.kills() {
+        if let Some(piece) = game.board().tile(coord)?.read()?.piece() {
+            value += piece.read()?.points() as i32 * 100;
+        }
+    }
+
+    if let Some(idx) = m.promotion() {
+        let piece = m.piece();
+        let lock = piece.read()?;
+        if let Some(promoted) = lock.promotions().get(idx as usize) {
+            value += (promoted.points() as i32 - lock.points() as i32) * 100;
+        }
+    }
+
+    Ok(value)
+}
+
+/// what making the move wins for the team making it, if every team then keeps killing on the
+/// tile it went to with its least valuable piece for as long as that pays off.
+pub fn see(game: &mut Game, m: &Move) -> Result<i32> {
+    exchange(game, m, 0)
+}
+
+fn exchange(game: &mut Game, m: &Move, depth: u32) -> Result<i32> {
+    let gain = kill_value(game, m)?;
+    if depth >= MAX_EXCHANGE {
+        return Ok(gain);
+    }
+
+    game.execute_move(m)?;
+    let reply = least_valuable_killer(game, m.to());
+    let score = match reply {
+        Ok(Some(reply)) => exchange(game, &reply, depth + 1).map(|reply_gain| gain - reply_gain.max(0)),
+        Ok(None) => Ok(gain),
+        Err(e) => Err(e),
+    };
+    game.undo()?;
+
+    score
+}
+
+// the cheapest legal move of the team to move that kills whatever stands on the tile.
+// royal pieces go last, they can only kill where nothing can kill them back.
+fn least_valuable_killer(game: &Game, target: Coord) -> Result<Option<Move>> {
+    let mut best: Option<(u32, Move)> = None;
+    for m in game.legal_moves_matching(|m| m.kills().contains(&target))? {
+        let lock = m.piece();
+        let lock = lock.read()?;
+        let worth = if lock.is_royal() { u32::MAX } else { lock.points() as u32 };
+        drop(lock);
+
+        if best.as_ref().is_none_or(|(best_worth, _)| worth < *best_worth) {
+            best = Some((worth, m));
+        }
+    }
+
+    Ok(best.map(|(_, m)| m))
+}
@@ -0,0 +1,730 @@
+// self-play matches: two engines, each a uci process with its own options, play each other from a
+// set of openings. every opening is played twice with the colors swapped, games run in parallel
+// with their own pair of engine processes, and games that are decided or dead drawn can be
+// adjudicated on the scores the engines report. an engine that runs out of its clock without
+// answering loses on time and is started again for the next game.
+// results are counted for the first engine, with the elo difference and an sprt on top.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chess::error::{ChessError, Result};
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+use chess::pgn::{parse_pgn, result_of, PgnGame};
+
+use crate::search::MATE;
+use crate::variant::{builtin_variants, Variant};
+
+// how far past its clock an engine can go before it loses on time.
+const TIME_MARGIN: Duration = Duration::from_millis(50);
+// how long an engine gets to answer anything but a search.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(10);
+// how long an engine gets to quit before it's killed.
+const QUIT_TIMEOUT: Duration = Duration::from_secs(1);
+// the z score of a 95% confidence interval.
+const CONFIDENCE_Z: f64 = 1.96;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EngineConfig {
+    name: String,
+    command: String,
+    args: Vec<String>,
+    // sent with setoption, in order.
+    options: Vec<(String, String)>,
+}
+
+impl EngineConfig {
+    /// named after the file it runs until it's given a name.
+    pub fn new(command: &str) -> Self {
+        let name = Path::new(command)
+            .file_stem()
+            .map_or(command.to_string(), |stem| stem.to_string_lossy().to_string());
+        Self {
+            name,
+            command: command.to_string(),
+            args: Vec::new(),
+            options: Vec::new(),
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn with_arg(mut self, arg: &str) -> Self {
+        self.args.push(arg.to_string());
+        self
+    }
+
+    pub fn with_option(mut self, name: &str, value: &str) -> Self {
+        self.options.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn options(&self) -> &[(String, String)] {
+        &self.options
+    }
+}
+
+/// an engine process, talked to over uci.
+pub struct UciEngine {
+    config: EngineConfig,
+    child: Child,
+    stdin: ChildStdin,
+    // read on their own thread, so waiting for an answer can time out.
+    lines: Receiver<String>,
+}
+
+impl UciEngine {
+    /// starts the engine and waits until it's set up with its options.
+    pub fn start(config: &EngineConfig) -> io::Result<Self> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("could not start {}: {}", config.command, e)))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines().map_while(|line| line.ok()) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = Self {
+            config: config.clone(),
+            child,
+            stdin,
+            lines,
+        };
+        engine.send("uci")?;
+        engine.wait_for("uciok")?;
+        for (name, value) in &config.options {
+            engine.send(&format!("setoption name {} value {}", name, value))?;
+        }
+        engine.send("isready")?;
+        engine.wait_for("readyok")?;
+        Ok(engine)
+    }
+
+    pub fn new_game(&mut self) -> io::Result<()> {
+        self.send("ucinewgame")?;
+        self.send("isready")?;
+        self.wait_for("readyok")
+    }
+
+    /// kills the engine, which might be stuck, and starts it again.
+    pub fn restart(&mut self) -> io::Result<()> {
+        let _ = self.child.kill();
+        *self = Self::start(&self.config.clone())?;
+        Ok(())
+    }
+
+    /// searches a position, giving back the move it picked and the last score it reported,
+    /// for the team to move. without an answer within the timeout the error is TimedOut.
+    pub fn best_move(&mut self, position: &str, go: &str, timeout: Option<Duration>) -> io::Result<(String, Option<i32>)> {
+        self.send(position)?;
+        self.send(go)?;
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut score = None;
+        loop {
+            let line = self.read_line(deadline)?;
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens.first() {
+                Some(&"info") => {
+                    if let Some(idx) = tokens.iter().position(|token| *token == "score") {
+                        score = parse_score(&tokens[idx + 1..]).or(score);
+                    }
+                }
+                Some(&"bestmove") => {
+                    let best_move = tokens.get(1).unwrap_or(&"0000").to_string();
+                    return Ok((best_move, score));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{}", line)?;
+        self.stdin.flush()
+    }
+
+    fn read_line(&mut self, deadline: Option<Instant>) -> io::Result<String> {
+        let line = match deadline {
+            Some(deadline) => self.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => self.lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        line.map_err(|e| match e {
+            RecvTimeoutError::Timeout => io::Error::new(io::ErrorKind::TimedOut, format!("{} stopped answering", self.config.name)),
+            RecvTimeoutError::Disconnected => io::Error::new(io::ErrorKind::UnexpectedEof, format!("{} quit", self.config.name)),
+        })
+    }
+
+    fn wait_for(&mut self, answer: &str) -> io::Result<()> {
+        let deadline = Instant::now() + ANSWER_TIMEOUT;
+        while self.read_line(Some(deadline))?.trim() != answer {}
+        Ok(())
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let deadline = Instant::now() + QUIT_TIMEOUT;
+        while matches!(self.child.try_wait(), Ok(None)) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// cp n or mate n, mates become our mate scores.
+fn parse_score(tokens: &[&str]) -> Option<i32> {
+    let value = tokens.get(1)?.parse::<i32>().ok()?;
+    match *tokens.first()? {
+        "cp" => Some(value),
+        "mate" if value > 0 => Some(MATE - (2 * value - 1)),
+        "mate" => Some(-MATE + 2 * -value),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchLimit {
+    Depth(u32),
+    Nodes(u64),
+    MoveTime(Duration),
+    /// each engine gets its own clock, with the increment added after every move.
+    Clock { base: Duration, increment: Duration },
+}
+
+impl MatchLimit {
+    // clocks are for white then black.
+    fn go(&self, clocks: &[Duration; 2]) -> String {
+        match self {
+            MatchLimit::Depth(depth) => format!("go depth {}", depth),
+            MatchLimit::Nodes(nodes) => format!("go nodes {}", nodes),
+            MatchLimit::MoveTime(time) => format!("go movetime {}", time.as_millis()),
+            MatchLimit::Clock { increment, .. } => format!(
+                "go wtime {} btime {} winc {} binc {}",
+                clocks[0].as_millis(),
+                clocks[1].as_millis(),
+                increment.as_millis(),
+                increment.as_millis()
+            ),
+        }
+    }
+
+    // how long the engine to move can take before it's given up on, under depth and node limits
+    // it can think as long as it likes.
+    fn timeout(&self, clocks: &[Duration; 2], team: usize) -> Option<Duration> {
+        match self {
+            MatchLimit::Depth(_) | MatchLimit::Nodes(_) => None,
+            MatchLimit::MoveTime(time) => Some(*time * 2 + Duration::from_secs(1)),
+            MatchLimit::Clock { .. } => Some(clocks[team] + TIME_MARGIN),
+        }
+    }
+}
+
+/// when games end before the rules end them. scores are in centipawns, moves are full moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Adjudication {
+    /// a draw once both engines scored within draw_score of even for their last draw_moves moves,
+    /// if the game is at least draw_after moves long.
+    pub draw_after: usize,
+    pub draw_moves: usize,
+    pub draw_score: i32,
+    /// a loss for an engine that scored itself at -resign_score or worse for resign_moves moves.
+    pub resign_moves: usize,
+    pub resign_score: i32,
+    /// a draw after this many moves, 0 to play on forever.
+    pub max_moves: usize,
+}
+
+impl Default for Adjudication {
+    fn default() -> Self {
+        Self {
+            draw_after: 40,
+            draw_moves: 8,
+            draw_score: 10,
+            resign_moves: 4,
+            resign_score: 1000,
+            max_moves: 200,
+        }
+    }
+}
+
+/// where a game starts: a position and the moves played from it, in uci.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Opening {
+    pub fen: String,
+    pub moves: Vec<String>,
+}
+
+fn opening_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+/// openings from a pgn file, or a file with a fen or epd position on each line.
+/// every opening has to be legal in the variant.
+pub fn parse_openings(text: &str, variant: &Variant) -> Result<Vec<Opening>> {
+    let is_pgn = text.trim_start().starts_with('[') || text.trim_start().starts_with("1.");
+    if is_pgn {
+        return parse_pgn(text)?
+            .iter()
+            .map(|pgn| {
+                let mut game = pgn.start_with(variant.catalog())?;
+                let fen = game.to_fen()?;
+                let mut moves = Vec::new();
+                for san in &pgn.moves {
+                    let m = game.parse_san(san)?;
+                    moves.push(game.move_to_uci(&m));
+                    game.execute_move(&m)?;
+                }
+                Ok(Opening { fen, moves })
+            })
+            .collect();
+    }
+
+    let mut openings = Vec::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(opening_error(format!("{} is not a position.", line)));
+        }
+        // epd has no move counters, and operations after the position.
+        let counted = fields.len() >= 6 && fields[4..6].iter().all(|field| field.parse::<u32>().is_ok());
+        let fen = match counted {
+            true => fields[..6].join(" "),
+            false => format!("{} 0 1", fields[..4].join(" ")),
+        };
+        variant.game(&fen)?;
+        openings.push(Opening { fen, moves: Vec::new() });
+    }
+    Ok(openings)
+}
+
+#[derive(Clone)]
+pub struct MatchConfig {
+    engines: [EngineConfig; 2],
+    variant: Variant,
+    // none means the variant's starting position.
+    openings: Vec<Opening>,
+    games: usize,
+    concurrency: usize,
+    limit: MatchLimit,
+    adjudication: Option<Adjudication>,
+    event: String,
+}
+
+impl MatchConfig {
+    pub fn new(first: EngineConfig, second: EngineConfig) -> Self {
+        Self {
+            engines: [first, second],
+            variant: builtin_variants().remove(0),
+            openings: Vec::new(),
+            games: 2,
+            concurrency: 1,
+            limit: MatchLimit::Depth(4),
+            adjudication: Some(Adjudication::default()),
+            event: "chess-rs match".to_string(),
+        }
+    }
+
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    pub fn with_openings(mut self, openings: Vec<Opening>) -> Self {
+        self.openings = openings;
+        self
+    }
+
+    pub fn with_games(mut self, games: usize) -> Self {
+        self.games = games;
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: MatchLimit) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn with_adjudication(mut self, adjudication: Option<Adjudication>) -> Self {
+        self.adjudication = adjudication;
+        self
+    }
+
+    pub fn with_event(mut self, event: &str) -> Self {
+        self.event = event.to_string();
+        self
+    }
+
+    pub fn engines(&self) -> &[EngineConfig; 2] {
+        &self.engines
+    }
+
+    pub fn games(&self) -> usize {
+        self.games
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchGame {
+    // from 0, games 2n and 2n + 1 share an opening.
+    pub round: usize,
+    pub first_is_white: bool,
+    // why it ended, ex. White mates or adjudication.
+    pub reason: String,
+    pub pgn: PgnGame,
+}
+
+impl MatchGame {
+    /// 1 for a win of the first engine, 0.5 for a draw and 0 for a loss.
+    pub fn score(&self) -> f64 {
+        match (self.pgn.result.as_str(), self.first_is_white) {
+            ("1-0", true) | ("0-1", false) => 1.0,
+            ("1-0", false) | ("0-1", true) => 0.0,
+            _ => 0.5,
+        }
+    }
+}
+
+/// the results of the first engine.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchStats {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+}
+
+impl MatchStats {
+    pub fn add(&mut self, game: &MatchGame) {
+        match (game.pgn.result.as_str(), game.first_is_white) {
+            ("1-0", true) | ("0-1", false) => self.wins += 1,
+            ("1-0", false) | ("0-1", true) => self.losses += 1,
+            _ => self.draws += 1,
+        }
+    }
+
+    pub fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    /// the average score per game.
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games().max(1) as f64
+    }
+
+    // of the score of a single game.
+    fn variance(&self) -> f64 {
+        let n = self.games().max(1) as f64;
+        let s = self.score();
+        (self.wins as f64 * (1.0 - s).powi(2) + self.draws as f64 * (0.5 - s).powi(2) + self.losses as f64 * s.powi(2)) / n
+    }
+
+    /// the elo difference and the margin of its 95% confidence interval.
+    /// a perfect score is infinitely better.
+    pub fn elo(&self) -> (f64, f64) {
+        let s = self.score();
+        let deviation = (self.variance() / self.games().max(1) as f64).sqrt();
+        let low = score_to_elo((s - CONFIDENCE_Z * deviation).max(0.0));
+        let high = score_to_elo((s + CONFIDENCE_Z * deviation).min(1.0));
+        (score_to_elo(s), (high - low) / 2.0)
+    }
+}
+
+fn score_to_elo(score: f64) -> f64 {
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SprtVerdict {
+    /// the first engine is no better than elo0.
+    H0,
+    /// the first engine is at least elo1 better.
+    H1,
+}
+
+/// a sequential probability ratio test between the first engine being elo0 or elo1 better than
+/// the second, with the normal approximation of the log likelihood ratio that fishtest uses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    /// how often it can accept H1 when H0 holds.
+    pub alpha: f64,
+    /// how often it can accept H0 when H1 holds.
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Self {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    /// the llr at which H0 and then H1 are accepted.
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    pub fn llr(&self, stats: &MatchStats) -> f64 {
+        let variance = stats.variance();
+        if stats.games() == 0 || variance == 0.0 {
+            return 0.0;
+        }
+        let (s0, s1) = (elo_to_score(self.elo0), elo_to_score(self.elo1));
+        stats.games() as f64 * (s1 - s0) * (2.0 * stats.score() - s0 - s1) / (2.0 * variance)
+    }
+
+    /// none while the test goes on.
+    pub fn verdict(&self, stats: &MatchStats) -> Option<SprtVerdict> {
+        let llr = self.llr(stats);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            Some(SprtVerdict::H1)
+        } else if llr <= lower {
+            Some(SprtVerdict::H0)
+        } else {
+            None
+        }
+    }
+}
+
+/// plays the match, giving every finished game to on_game, which can stop the match by returning
+/// false. games already started are finished and counted.
+/// engines that can't be started, that quit or stop answering end the match with an error,
+/// except for running out of a clock, which loses the game on time.
+pub fn run_match(config: &MatchConfig, mut on_game: impl FnMut(&MatchGame, &MatchStats) -> bool) -> io::Result<MatchStats> {
+    let next_round = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel::<io::Result<MatchGame>>();
+
+    // the engines are told the variant before anything else.
+    let engines = config.engines.clone().map(|mut engine| {
+        if config.variant.name() != "chess" {
+            engine.options.insert(0, ("UCI_Variant".to_string(), config.variant.name().to_string()));
+        }
+        engine
+    });
+
+    let mut stats = MatchStats::default();
+    let mut error = None;
+    thread::scope(|scope| {
+        for _ in 0..config.concurrency.min(config.games) {
+            let sender = sender.clone();
+            let (engines, next_round, stop) = (&engines, &next_round, &stop);
+            scope.spawn(move || {
+                let mut pair = match (UciEngine::start(&engines[0]), UciEngine::start(&engines[1])) {
+                    (Ok(first), Ok(second)) => [first, second],
+                    (Err(e), _) | (_, Err(e)) => {
+                        let _ = sender.send(Err(e));
+                        return;
+                    }
+                };
+                while !stop.load(Ordering::Relaxed) {
+                    let round = next_round.fetch_add(1, Ordering::Relaxed);
+                    if round >= config.games {
+                        break;
+                    }
+                    let game = play_game(config, &mut pair, round);
+                    let failed = game.is_err();
+                    if sender.send(game).is_err() || failed {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for game in receiver {
+            match game {
+                Ok(game) => {
+                    stats.add(&game);
+                    if !on_game(&game, &stats) {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                }
+                Err(e) => {
+                    stop.store(true, Ordering::Relaxed);
+                    error.get_or_insert(e);
+                }
+            }
+        }
+    });
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(stats),
+    }
+}
+
+fn chess_to_io(e: ChessError) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+/// one game of the match, the first engine is white in even rounds.
+pub fn play_game(config: &MatchConfig, engines: &mut [UciEngine; 2], round: usize) -> io::Result<MatchGame> {
+    let start = Opening {
+        fen: config.variant.start_fen().to_string(),
+        moves: Vec::new(),
+    };
+    let opening = match config.openings.is_empty() {
+        true => &start,
+        false => &config.openings[(round / 2) % config.openings.len()],
+    };
+    let first_is_white = round.is_multiple_of(2);
+    // the engine playing each team, white then black.
+    let players = if first_is_white { [0, 1] } else { [1, 0] };
+
+    let mut game = config.variant.game(&opening.fen).map_err(chess_to_io)?;
+    let mut sans = Vec::new();
+    let mut ucis = Vec::new();
+    for uci in &opening.moves {
+        play_uci(&mut game, uci, &mut sans).map_err(chess_to_io)?;
+        ucis.push(uci.clone());
+    }
+    for engine in engines.iter_mut() {
+        engine.new_game()?;
+    }
+
+    let mut clocks = match config.limit {
+        MatchLimit::Clock { base, .. } => [base; 2],
+        _ => [Duration::ZERO; 2],
+    };
+    // what each team's engine thought of its moves.
+    let mut scores: [Vec<i32>; 2] = [Vec::new(), Vec::new()];
+    let loss = |team: usize| if team == 0 { "0-1" } else { "1-0" };
+
+    let (result, reason, termination) = loop {
+        if let Some((result, reason)) = result_of(&game) {
+            break (result, reason.to_string(), "normal");
+        }
+        let team = game.current_move() as usize % 2;
+        let engine = &mut engines[players[team]];
+
+        let mut position = format!("position fen {}", opening.fen);
+        if !ucis.is_empty() {
+            position += &format!(" moves {}", ucis.join(" "));
+        }
+        let started = Instant::now();
+        let answer = engine.best_move(&position, &config.limit.go(&clocks), config.limit.timeout(&clocks, team));
+        let elapsed = started.elapsed();
+        let (best_move, score) = match answer {
+            Err(e) if e.kind() == io::ErrorKind::TimedOut && matches!(config.limit, MatchLimit::Clock { .. }) => {
+                engine.restart()?;
+                break (loss(team), "Time forfeit".to_string(), "time forfeit");
+            }
+            answer => answer?,
+        };
+
+        if let MatchLimit::Clock { increment, .. } = config.limit {
+            if elapsed > clocks[team] + TIME_MARGIN {
+                break (loss(team), "Time forfeit".to_string(), "time forfeit");
+            }
+            clocks[team] = clocks[team].saturating_sub(elapsed) + increment;
+        }
+        if play_uci(&mut game, &best_move, &mut sans).is_err() {
+            break (loss(team), format!("Illegal move {}", best_move), "rules infraction");
+        }
+        ucis.push(best_move);
+
+        let Some(adjudication) = config.adjudication else {
+            continue;
+        };
+        if let Some(score) = score {
+            scores[team].push(score);
+        }
+        // the scores of a team's last moves, if it has that many.
+        let last = |team: usize, moves: usize| {
+            let scores = &scores[team];
+            let moves = moves.max(1);
+            (scores.len() >= moves).then(|| scores[scores.len() - moves..].iter())
+        };
+        if last(team, adjudication.resign_moves).is_some_and(|mut s| s.all(|s| *s <= -adjudication.resign_score)) {
+            break (loss(team), "Resigned".to_string(), "adjudication");
+        }
+        let moves = sans.len() / 2;
+        let even = |team: usize| last(team, adjudication.draw_moves).is_some_and(|mut s| s.all(|s| s.abs() <= adjudication.draw_score));
+        if moves >= adjudication.draw_after && even(0) && even(1) {
+            break ("1/2-1/2", "Draw adjudicated".to_string(), "adjudication");
+        }
+        if adjudication.max_moves > 0 && moves >= adjudication.max_moves {
+            break ("1/2-1/2", "Move limit".to_string(), "adjudication");
+        }
+    };
+
+    let names = players.map(|idx| config.engines[idx].name().to_string());
+    let mut tags = vec![
+        ("Event".to_string(), config.event.clone()),
+        ("Round".to_string(), (round + 1).to_string()),
+        ("White".to_string(), names[0].clone()),
+        ("Black".to_string(), names[1].clone()),
+        ("Result".to_string(), result.to_string()),
+    ];
+    if config.variant.name() != "chess" {
+        tags.push(("Variant".to_string(), config.variant.name().to_string()));
+    }
+    if opening.fen != STARTING_FEN {
+        tags.push(("SetUp".to_string(), "1".to_string()));
+        tags.push(("FEN".to_string(), opening.fen.clone()));
+    }
+    tags.push(("Termination".to_string(), termination.to_string()));
+
+    Ok(MatchGame {
+        round,
+        first_is_white,
+        reason,
+        pgn: PgnGame {
+            tags,
+            moves: sans,
+            result: result.to_string(),
+        },
+    })
+}
+
+// plays a move given in uci, writing it down in san.
+fn play_uci(game: &mut Game, uci: &str, sans: &mut Vec<String>) -> Result<()> {
+    let m = game.parse_uci(uci)?;
+    sans.push(game.move_to_san(&m)?);
+    game.execute_move(&m)?;
+    Ok(())
+}
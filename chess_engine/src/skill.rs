@@ -0,0 +1,172 @@
+// playing below full strength, for people who can't beat the engine yet.
+// a skill level from 0 to 20 caps how deep and how many nodes the search goes, puts noise on the
+// evaluation, and has the move picked from the best few lines, a worse one the lower the level,
+// like stockfish's skill level. 20 is full strength.
+// levels map to elo in steps of 100, a rough guess anchored at full strength rather than a
+// measurement, so UCI_Elo picks the nearest level.
+// personalities change how the evaluation weighs things instead of how well the engine plays.
+
+use chess::error::Result;
+use chess::piece::Piece;
+
+use crate::eval::{EvalParams, PieceWeights};
+use crate::search::{PvLine, SearchLimits};
+
+pub const MAX_LEVEL: u32 = 20;
+pub const MIN_ELO: u32 = 800;
+pub const MAX_ELO: u32 = MIN_ELO + MAX_LEVEL * ELO_PER_LEVEL;
+const ELO_PER_LEVEL: u32 = 100;
+// how many lines a limited engine picks its move from.
+const SKILL_LINES: usize = 4;
+// the most a candidate's score counts as worse than the best one's when picking.
+const MAX_SPREAD: i32 = 100;
+// the most the evaluation is off by at level 0, less for every level above.
+const NOISE_PER_LEVEL: i32 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Skill {
+    level: u32,
+}
+
+impl Default for Skill {
+    fn default() -> Self {
+        Self::new(MAX_LEVEL)
+    }
+}
+
+impl Skill {
+    /// levels above MAX_LEVEL are full strength.
+    pub fn new(level: u32) -> Self {
+        Self { level: level.min(MAX_LEVEL) }
+    }
+
+    /// the level closest to the elo.
+    pub fn from_elo(elo: u32) -> Self {
+        let elo = elo.clamp(MIN_ELO, MAX_ELO);
+        Self::new((elo - MIN_ELO + ELO_PER_LEVEL / 2) / ELO_PER_LEVEL)
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    pub fn elo(&self) -> u32 {
+        MIN_ELO + self.level * ELO_PER_LEVEL
+    }
+
+    pub fn is_full_strength(&self) -> bool {
+        self.level == MAX_LEVEL
+    }
+
+    /// the limits, capped for the level.
+    pub fn limits(&self, limits: SearchLimits) -> SearchLimits {
+        if self.is_full_strength() {
+            return limits;
+        }
+        let depth = 1 + self.level / 3;
+        let nodes = 500 << (self.level / 2);
+        SearchLimits {
+            depth: Some(limits.depth.map_or(depth, |limit| limit.min(depth))),
+            nodes: Some(limits.nodes.map_or(nodes, |limit| limit.min(nodes))),
+            ..limits
+        }
+    }
+
+    /// how many lines to search, enough to pick a worse move from.
+    pub fn multi_pv(&self, multi_pv: usize) -> usize {
+        if self.is_full_strength() {
+            multi_pv
+        } else {
+            multi_pv.max(SKILL_LINES)
+        }
+    }
+
+    /// the most the evaluation is off by, in centipoints.
+    pub fn noise(&self) -> i32 {
+        (MAX_LEVEL - self.level) as i32 * NOISE_PER_LEVEL
+    }
+
+    /// the line to play out of the best ones found, best first. every line gets a random push
+    /// that's bigger the lower the level, and more so the worse the line is, so weak levels often
+    /// play the second or third best move but seldom a blunder that loses a lot more.
+    pub fn pick<'a>(&self, lines: &'a [PvLine], random: u64) -> Option<&'a PvLine> {
+        let best = lines.first()?;
+        if self.is_full_strength() {
+            return Some(best);
+        }
+
+        let candidates = &lines[..lines.len().min(SKILL_LINES)];
+        let weakness = 120 - 2 * self.level as i32;
+        let spread = (best.score - candidates.last().unwrap().score).min(MAX_SPREAD);
+        let mut rng = random | 1;
+        candidates.iter().max_by_key(|line| {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            let push = (weakness * (best.score - line.score) + spread * (rng % weakness as u64) as i32) / 128;
+            line.score + push
+        })
+    }
+}
+
+/// how the engine likes to play, as changes to its evaluation weights.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Personality {
+    #[default]
+    Normal,
+    /// active pieces and pushing forward, over material.
+    Aggressive,
+    /// grabs material and holds on to it.
+    Materialistic,
+    /// keeps its pieces central and its promotable pieces back. there's no king safety term, so
+    /// that's as defensive as the evaluation gets.
+    Defensive,
+}
+
+impl Personality {
+    pub const ALL: [Personality; 4] =
+        [Personality::Normal, Personality::Aggressive, Personality::Materialistic, Personality::Defensive];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Personality::Normal => "normal",
+            Personality::Aggressive => "aggressive",
+            Personality::Materialistic => "materialistic",
+            Personality::Defensive => "defensive",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|personality| personality.name().eq_ignore_ascii_case(name))
+    }
+
+    // percent of the material, mobility, center and advance weights.
+    fn scales(&self) -> [i32; 4] {
+        match self {
+            Personality::Normal => [100, 100, 100, 100],
+            Personality::Aggressive => [90, 200, 100, 200],
+            Personality::Materialistic => [125, 50, 75, 75],
+            Personality::Defensive => [100, 50, 150, 25],
+        }
+    }
+
+    /// the catalog's weights from params, or their defaults, changed for the personality.
+    pub fn params(&self, catalog: &[Piece], params: &EvalParams) -> Result<EvalParams> {
+        if *self == Personality::Normal {
+            return Ok(params.clone());
+        }
+
+        let scales = self.scales();
+        let mut changed = params.clone();
+        for piece in catalog {
+            let data = piece.read()?;
+            let weights = params.weights(&data).to_array();
+            let mut scaled = [0; 4];
+            for (idx, weight) in weights.into_iter().enumerate() {
+                scaled[idx] = weight * scales[idx] / 100;
+            }
+            changed.set(data.symbol(), PieceWeights::from_array(scaled));
+        }
+        Ok(changed)
+    }
+}
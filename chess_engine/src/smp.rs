@@ -0,0 +1,177 @@
+// lazy smp: every thread runs its own search on its own copy of the game, and they help each
+// other through the shared transposition table. helpers skip some depths so they get ahead of the
+// main thread and fill the table with what it's about to need.
+// each thread keeps its own move ordering tables, and the main thread's limits decide when
+// everyone stops.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use chess::error::Result;
+use chess::game::Game;
+use chess::r#move::MoveKey;
+
+use crate::eval::EvalParams;
+use crate::nnue::Network;
+use crate::search::{resolve_pv, OnIteration, PvLine, SearchLimits, SearchResult, Searcher};
+use crate::tablebase::Tablebases;
+use crate::time::TimeManager;
+use crate::tt::TranspositionTable;
+
+pub struct SmpSearcher {
+    tt: Arc<TranspositionTable>,
+    stop: Arc<AtomicBool>,
+    // the first one is the main thread.
+    workers: Vec<Searcher>,
+}
+
+impl SmpSearcher {
+    /// at least one thread.
+    pub fn new(limits: SearchLimits, threads: usize) -> Self {
+        let tt = Arc::new(TranspositionTable::default());
+        let stop = Arc::new(AtomicBool::new(false));
+        let workers = (0..threads.max(1))
+            .map(|thread| {
+                // helpers go on until the main thread is done.
+                let limits = if thread == 0 { limits } else { SearchLimits::new() };
+                Searcher::new(limits)
+                    .with_table(tt.clone())
+                    .with_stop_flag(stop.clone())
+                    .with_thread(thread)
+            })
+            .collect();
+
+        Self { tt, stop, workers }
+    }
+
+    /// shares a table with other searchers, or keeps one between searches.
+    pub fn with_table(mut self, tt: Arc<TranspositionTable>) -> Self {
+        self.workers = self.workers.into_iter().map(|worker| worker.with_table(tt.clone())).collect();
+        self.tt = tt;
+        self
+    }
+
+    pub fn with_tablebases(mut self, tablebases: Arc<Tablebases>) -> Self {
+        self.workers = self.workers.into_iter().map(|worker| worker.with_tablebases(tablebases.clone())).collect();
+        self
+    }
+
+    pub fn with_eval_params(mut self, params: EvalParams) -> Self {
+        self.workers = self.workers.into_iter().map(|worker| worker.with_eval_params(params.clone())).collect();
+        self
+    }
+
+    /// every thread keeps its own accumulators.
+    pub fn with_network(mut self, network: Option<Arc<Network>>) -> Self {
+        self.workers = self.workers.into_iter().map(|worker| worker.with_network(network.clone())).collect();
+        self
+    }
+
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.workers[0].set_limits(limits);
+    }
+
+    /// only the main thread looks for more than one line.
+    pub fn set_multi_pv(&mut self, multi_pv: usize) {
+        self.workers[0].set_multi_pv(multi_pv);
+    }
+
+    /// every thread keeps to the same moves at the root.
+    pub fn set_root_moves(&mut self, root_moves: Option<Vec<MoveKey>>) {
+        for worker in &mut self.workers {
+            worker.set_root_moves(root_moves.clone());
+        }
+    }
+
+    /// every thread gets the same noise, so they agree on what goes in the table.
+    pub fn set_eval_noise(&mut self, noise: i32, seed: u64) {
+        for worker in &mut self.workers {
+            worker.set_eval_noise(noise, seed);
+        }
+    }
+
+    /// the main thread's, helpers stop with it.
+    pub fn set_time_manager(&mut self, time: Option<TimeManager>) {
+        self.workers[0].set_time_manager(time);
+    }
+
+    /// reports the main thread's iterations.
+    pub fn set_on_iteration(&mut self, on_iteration: Option<OnIteration>) {
+        self.workers[0].set_on_iteration(on_iteration);
+    }
+
+    pub fn table(&self) -> &Arc<TranspositionTable> {
+        &self.tt
+    }
+
+    /// setting it stops every thread, the search then returns what it has.
+    /// it's cleared when the search returns.
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn search(&mut self, game: &mut Game) -> Result<SearchResult> {
+        let mut copies = (1..self.workers.len())
+            .map(|_| game.deep_clone())
+            .collect::<Result<Vec<_>>>()?;
+
+        let stop = &self.stop;
+        let (main, helpers) = self.workers.split_first_mut().unwrap();
+        let (main_result, helper_results) = thread::scope(|scope| {
+            let handles: Vec<_> = helpers
+                .iter_mut()
+                .zip(copies.iter_mut())
+                .map(|(helper, copy)| {
+                    scope.spawn(move || {
+                        // the moves point into the helper's copy, only their keys leave the thread.
+                        helper.search(copy).map(|result| {
+                            let pv: Vec<MoveKey> = result.pv.iter().map(|m| m.key()).collect();
+                            (result.score, pv, result.depth, result.nodes)
+                        })
+                    })
+                })
+                .collect();
+
+            let main_result = main.search(game);
+            stop.store(true, Ordering::Relaxed);
+            let helper_results: Vec<_> = handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                .collect();
+            (main_result, helper_results)
+        });
+        self.stop.store(false, Ordering::Relaxed);
+
+        let mut result = main_result?;
+        for helper_result in helper_results {
+            let (score, pv, depth, nodes) = helper_result?;
+            result.nodes += nodes;
+
+            // a helper that got deeper than the main thread knows better, unless the main thread
+            // has more lines than the helper.
+            if depth > result.depth && !pv.is_empty() && result.lines.len() <= 1 {
+                let pv = resolve_pv(game, &pv);
+                if let Some(first) = pv.first() {
+                    result.best_move = Some(first.clone());
+                    result.score = score;
+                    result.depth = depth;
+                    result.lines = vec![PvLine { score, pv: pv.clone() }];
+                    result.pv = pv;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// the search with as many threads as the machine has cores.
+pub fn search(game: &mut Game, limits: SearchLimits) -> Result<SearchResult> {
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    SmpSearcher::new(limits, threads).search(game)
+}
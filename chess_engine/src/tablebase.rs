@@ -0,0 +1,477 @@
+// endgame tablebases for small sets of pieces, made by retrograde analysis.
+// every placement of the pieces on the board is a position, for each team to move. the moves out
+// of every position are generated once, then the results spread backwards from the mates: a
+// position is won when a move reaches one that's lost for the other team, and lost when every move
+// reaches one that's won for it. whatever is left when nothing changes anymore is a draw.
+// kills leave fewer pieces, so the tables for those are made first.
+//
+// material is written like KQvK, the first team's pieces before the v. pieces are told apart by
+// symbol only, so a table fits games that use the same pieces as the catalog it was made with.
+// pieces that promote or have rules for their first moves (ex. pawns) aren't supported.
+
+use std::collections::HashMap;
+
+use chess::error::{ChessError, Result};
+use chess::game::Game;
+use chess::piece::Piece;
+
+const MAGIC: &[u8; 4] = b"CTB1";
+// values are stored in a byte: 0 is a draw, 255 a position that can't happen, and anything else
+// is the plies to mate + 1, even for a win and odd for a loss.
+const DRAW: u8 = 0;
+const INVALID: u8 = 255;
+const MAX_PLIES: u32 = 253;
+
+/// how a position ends for the team to move, with perfect play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// mates in this many plies.
+    Win(u32),
+    /// gets mated in this many plies, 0 when it already is.
+    Loss(u32),
+    Draw,
+}
+
+impl Outcome {
+    fn to_byte(self) -> u8 {
+        match self {
+            Outcome::Win(plies) | Outcome::Loss(plies) => plies as u8 + 1,
+            Outcome::Draw => DRAW,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            DRAW => Some(Outcome::Draw),
+            INVALID => None,
+            byte if (byte - 1) % 2 == 1 => Some(Outcome::Win(byte as u32 - 1)),
+            byte => Some(Outcome::Loss(byte as u32 - 1)),
+        }
+    }
+}
+
+fn tablebase_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+// the symbols of each team's pieces, sorted, ex. [[K, Q], [K]] for KQvK.
+fn parse_material(material: &str) -> Result<[Vec<char>; 2]> {
+    let Some((first, second)) = material.split_once('v') else {
+        return Err(tablebase_error(format!("Expected material like KQvK, not {}.", material)));
+    };
+    let team = |symbols: &str| {
+        let mut symbols: Vec<char> = symbols.chars().map(|c| c.to_ascii_uppercase()).collect();
+        symbols.sort();
+        symbols
+    };
+    Ok([team(first), team(second)])
+}
+
+fn material_name(teams: &[Vec<char>; 2]) -> String {
+    format!("{}v{}", teams[0].iter().collect::<String>(), teams[1].iter().collect::<String>())
+}
+
+/// the results of every position of some material on one board size.
+#[derive(Clone, Debug)]
+pub struct Tablebase {
+    material: String,
+    width: u32,
+    height: u32,
+    values: Vec<u8>,
+}
+
+impl Tablebase {
+    pub fn material(&self) -> &str {
+        &self.material
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// how many positions it has, including the ones that can't happen.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// squares are y * width + x, in the order of the material.
+    pub fn outcome(&self, squares: &[u32], team_to_move: usize) -> Option<Outcome> {
+        Outcome::from_byte(*self.values.get(index(squares, team_to_move, self.width * self.height))?)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.values.len() + 32);
+        bytes.extend(MAGIC);
+        bytes.extend(self.width.to_be_bytes());
+        bytes.extend(self.height.to_be_bytes());
+        bytes.push(self.material.len() as u8);
+        bytes.extend(self.material.bytes());
+        bytes.extend((self.values.len() as u64).to_be_bytes());
+        bytes.extend(&self.values);
+        bytes
+    }
+
+    /// reads a table from the start of bytes, and how many bytes it took.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize)> {
+        let truncated = || tablebase_error("The tablebase is cut short.".to_string());
+        if bytes.get(..4) != Some(MAGIC.as_slice()) {
+            return Err(tablebase_error("Not a tablebase.".to_string()));
+        }
+        let u32_at = |at: usize| -> Result<u32> {
+            Ok(u32::from_be_bytes(bytes.get(at..at + 4).ok_or_else(truncated)?.try_into().unwrap()))
+        };
+        let width = u32_at(4)?;
+        let height = u32_at(8)?;
+
+        let material_len = *bytes.get(12).ok_or_else(truncated)? as usize;
+        let material = bytes.get(13..13 + material_len).ok_or_else(truncated)?;
+        let material = String::from_utf8(material.to_vec()).map_err(|_| truncated())?;
+
+        let at = 13 + material_len;
+        let len = u64::from_be_bytes(bytes.get(at..at + 8).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+        let values = bytes.get(at + 8..at + 8 + len).ok_or_else(truncated)?.to_vec();
+
+        let table = Tablebase {
+            material,
+            width,
+            height,
+            values,
+        };
+        Ok((table, at + 8 + len))
+    }
+}
+
+// one position per team to move and placement, whether or not the pieces overlap.
+fn index(squares: &[u32], team_to_move: usize, tiles: u32) -> usize {
+    let placement = squares.iter().rev().fold(0, |idx, square| idx * tiles as usize + *square as usize);
+    placement * 2 + team_to_move
+}
+
+fn squares_of(mut idx: usize, pieces: usize, tiles: u32) -> (Vec<u32>, usize) {
+    let team_to_move = idx % 2;
+    idx /= 2;
+    let squares = (0..pieces)
+        .map(|_| {
+            let square = (idx % tiles as usize) as u32;
+            idx /= tiles as usize;
+            square
+        })
+        .collect();
+    (squares, team_to_move)
+}
+
+// where a move out of a position leads.
+enum Successor {
+    Position(usize),
+    // a kill into a smaller table, from the point of view of the team that moves next.
+    Known(Outcome),
+}
+
+/// tables for every material they were made for, and all the smaller ones they needed.
+#[derive(Default)]
+pub struct Tablebases {
+    // (material, width, height)
+    tables: HashMap<(String, u32, u32), Tablebase>,
+    max_pieces: usize,
+}
+
+impl Tablebases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, material: &str, width: u32, height: u32) -> Option<&Tablebase> {
+        let material = material_name(&parse_material(material).ok()?);
+        self.tables.get(&(material, width, height))
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item = &Tablebase> {
+        self.tables.values()
+    }
+
+    pub fn insert(&mut self, table: Tablebase) {
+        let pieces = table.material.len() - 1;
+        self.max_pieces = self.max_pieces.max(pieces);
+        self.tables.insert((table.material.clone(), table.width, table.height), table);
+    }
+
+    /// makes the table for the material and every smaller one it needs, unless they're here already.
+    /// the first team in the material is the catalog's uppercase team, like in fen.
+    pub fn generate(&mut self, material: &str, width: u32, height: u32, catalog: &[Piece]) -> Result<()> {
+        let teams = parse_material(material)?;
+        let name = material_name(&teams);
+        if self.tables.contains_key(&(name.clone(), width, height)) {
+            return Ok(());
+        }
+
+        for team in &teams {
+            for symbol in team {
+                let piece = catalog
+                    .iter()
+                    .find(|p| p.read().unwrap().symbol() == *symbol)
+                    .ok_or(tablebase_error(format!("There is no piece {} in the catalog.", symbol)))?;
+                let lock = piece.read()?;
+                if !lock.promotions().is_empty() || !lock.nth_move_rules().is_empty() {
+                    return Err(tablebase_error(format!(
+                        "{} promotes or has first move rules, tablebases can't have it.",
+                        symbol
+                    )));
+                }
+            }
+        }
+
+        // the smaller tables kills lead to, a royal piece never gets killed.
+        for (team_idx, team) in teams.iter().enumerate() {
+            for (idx, symbol) in team.iter().enumerate() {
+                if is_royal(catalog, *symbol) {
+                    continue;
+                }
+                let mut smaller = teams.clone();
+                smaller[team_idx].remove(idx);
+                self.generate(&material_name(&smaller), width, height, catalog)?;
+            }
+        }
+
+        let table = self.solve(&teams, width, height, catalog)?;
+        self.insert(table);
+        Ok(())
+    }
+
+    fn solve(&self, teams: &[Vec<char>; 2], width: u32, height: u32, catalog: &[Piece]) -> Result<Tablebase> {
+        let symbols: Vec<(usize, char)> = teams
+            .iter()
+            .enumerate()
+            .flat_map(|(team, symbols)| symbols.iter().map(move |symbol| (team, *symbol)))
+            .collect();
+        let tiles = width * height;
+        let positions = (tiles as usize).pow(symbols.len() as u32) * 2;
+
+        let mut values = Vec::with_capacity(positions);
+        let mut successors: Vec<Vec<Successor>> = Vec::with_capacity(positions);
+        for idx in 0..positions {
+            let (squares, team_to_move) = squares_of(idx, symbols.len(), tiles);
+            let (value, moves) = self.expand(&symbols, &squares, team_to_move, width, height, catalog)?;
+            values.push(value);
+            successors.push(moves);
+        }
+
+        // the plies of the longest mate a kill leads to, nothing can be decided past it until then.
+        let longest_known = successors
+            .iter()
+            .flatten()
+            .filter_map(|successor| match successor {
+                Successor::Known(Outcome::Win(plies) | Outcome::Loss(plies)) => Some(*plies),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+
+        let outcome_of = |values: &[u8], successor: &Successor| match successor {
+            Successor::Position(idx) => (values[*idx] != DRAW).then(|| Outcome::from_byte(values[*idx])).flatten(),
+            Successor::Known(outcome) => Some(*outcome),
+        };
+
+        // mates are already in, every pass decides the positions one ply further from them.
+        let mut plies = 1;
+        loop {
+            let mut decided = Vec::new();
+            for (idx, moves) in successors.iter().enumerate() {
+                if values[idx] != DRAW || moves.is_empty() {
+                    continue;
+                }
+
+                let outcomes: Vec<Option<Outcome>> = moves.iter().map(|m| outcome_of(&values, m)).collect();
+                let wins = outcomes.iter().any(|o| *o == Some(Outcome::Loss(plies - 1)));
+                let loses = outcomes.iter().all(|o| matches!(o, Some(Outcome::Win(n)) if *n < plies))
+                    && outcomes.contains(&Some(Outcome::Win(plies - 1)));
+                if wins {
+                    decided.push((idx, Outcome::Win(plies)));
+                } else if loses {
+                    decided.push((idx, Outcome::Loss(plies)));
+                }
+            }
+
+            if decided.is_empty() && plies > longest_known {
+                break;
+            }
+            if plies > MAX_PLIES {
+                return Err(tablebase_error(format!("{} has mates too long to store.", material_name(teams))));
+            }
+            for (idx, outcome) in decided {
+                values[idx] = outcome.to_byte();
+            }
+            plies += 1;
+        }
+
+        Ok(Tablebase {
+            material: material_name(teams),
+            width,
+            height,
+            values,
+        })
+    }
+
+    // the value of a position if it's already known, and where its moves lead.
+    fn expand(
+        &self,
+        symbols: &[(usize, char)],
+        squares: &[u32],
+        team_to_move: usize,
+        width: u32,
+        height: u32,
+        catalog: &[Piece],
+    ) -> Result<(u8, Vec<Successor>)> {
+        let tiles = width * height;
+        let overlapping = (1..squares.len()).any(|i| squares[..i].contains(&squares[i]));
+        if overlapping {
+            return Ok((INVALID, Vec::new()));
+        }
+
+        let Ok(game) = Game::from_fen_with(&placement_fen(symbols, squares, team_to_move, width, height), catalog)
+        else {
+            return Ok((INVALID, Vec::new()));
+        };
+        // the team that just moved can't have left its royal piece to be taken.
+        let other = &game.board().piece_sets()[1 - team_to_move];
+        if game.is_in_check(other.team().name()) {
+            return Ok((INVALID, Vec::new()));
+        }
+
+        let moves = game.legal_moves()?;
+        if moves.is_empty() {
+            let value = if game.is_in_check(game.current_team().name()) {
+                Outcome::Loss(0).to_byte()
+            } else {
+                DRAW
+            };
+            return Ok((value, Vec::new()));
+        }
+
+        let mut successors = Vec::new();
+        for m in moves {
+            let from = m.from().y() * width + m.from().x();
+            let to = m.to().y() * width + m.to().x();
+            let mut killed: Vec<u32> = m.kills().iter().map(|c| c.y() * width + c.x()).collect();
+            killed.push(to);
+
+            let mut next_symbols = Vec::new();
+            let mut next_squares = Vec::new();
+            for (symbol, square) in symbols.iter().zip(squares) {
+                if *square == from {
+                    next_symbols.push(*symbol);
+                    next_squares.push(to);
+                } else if !killed.contains(square) {
+                    next_symbols.push(*symbol);
+                    next_squares.push(*square);
+                }
+            }
+
+            if next_symbols.len() == symbols.len() {
+                successors.push(Successor::Position(index(&next_squares, 1 - team_to_move, tiles)));
+                continue;
+            }
+            let mut teams = [Vec::new(), Vec::new()];
+            for (team, symbol) in &next_symbols {
+                teams[*team].push(*symbol);
+            }
+            let table = self
+                .tables
+                .get(&(material_name(&teams), width, height))
+                .ok_or(tablebase_error(format!("Missing the table for {}.", material_name(&teams))))?;
+            let outcome = table.outcome(&next_squares, 1 - team_to_move).unwrap_or(Outcome::Draw);
+            successors.push(Successor::Known(outcome));
+        }
+
+        Ok((DRAW, successors))
+    }
+
+    /// the outcome of the game's position, if there's a table for it.
+    pub fn probe(&self, game: &Game) -> Option<Outcome> {
+        let board = game.board();
+        let sets = board.piece_sets();
+        if sets.len() != 2 {
+            return None;
+        }
+
+        // (team, symbol, square) of every piece on the board.
+        let mut pieces = Vec::new();
+        for (team, set) in sets.iter().enumerate() {
+            for piece in set.pieces() {
+                let lock = piece.read().ok()?;
+                if !lock.is_alive() {
+                    continue;
+                }
+                if pieces.len() == self.max_pieces {
+                    return None;
+                }
+                let coord = board.rel_coord_to_absolute(lock.rel_pos()?, lock.team()?.start_info());
+                pieces.push((team, lock.symbol(), coord.y() * board.width() + coord.x()));
+            }
+        }
+        pieces.sort();
+
+        let mut teams = [Vec::new(), Vec::new()];
+        for (team, symbol, _) in &pieces {
+            teams[*team].push(*symbol);
+        }
+        let table = self.tables.get(&(material_name(&teams), board.width(), board.height()))?;
+        let squares: Vec<u32> = pieces.iter().map(|(_, _, square)| *square).collect();
+        table.outcome(&squares, game.current_move() as usize % 2)
+    }
+
+    /// every table, one after the other.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut tables: Vec<&Tablebase> = self.tables.values().collect();
+        tables.sort_by_key(|table| (table.material.len(), table.material.clone()));
+        tables.iter().flat_map(|table| table.to_bytes()).collect()
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self> {
+        let mut tablebases = Self::new();
+        while !bytes.is_empty() {
+            let (table, len) = Tablebase::from_bytes(bytes)?;
+            tablebases.insert(table);
+            bytes = &bytes[len..];
+        }
+        Ok(tablebases)
+    }
+}
+
+fn is_royal(catalog: &[Piece], symbol: char) -> bool {
+    catalog
+        .iter()
+        .any(|p| p.read().unwrap().symbol() == symbol && p.read().unwrap().is_royal())
+}
+
+// the position as fen, the first team in uppercase.
+fn placement_fen(symbols: &[(usize, char)], squares: &[u32], team_to_move: usize, width: u32, height: u32) -> String {
+    let mut rows = Vec::new();
+    for y in (0..height).rev() {
+        let mut row = String::new();
+        let mut empty = 0;
+        for x in 0..width {
+            let Some(idx) = squares.iter().position(|square| *square == y * width + x) else {
+                empty += 1;
+                continue;
+            };
+            if empty > 0 {
+                row.push_str(&std::mem::take(&mut empty).to_string());
+            }
+            let (team, symbol) = symbols[idx];
+            row.push(if team == 0 { symbol } else { symbol.to_ascii_lowercase() });
+        }
+        if empty > 0 {
+            row.push_str(&empty.to_string());
+        }
+        rows.push(row);
+    }
+    format!("{} {} - - 0 1", rows.join("/"), if team_to_move == 0 { "w" } else { "b" })
+}
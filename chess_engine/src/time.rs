@@ -0,0 +1,132 @@
+// how long to think about a move.
+// on a clock every move gets a soft budget, which is when no new iteration starts, and a hard
+// budget, which stops the search wherever it is. the soft budget stretches while the best move
+// keeps changing or the score drops between iterations, and shrinks back once it settles.
+
+use std::time::{Duration, Instant};
+
+use chess::r#move::MoveKey;
+
+// time lost between deciding on a move and the clock stopping, ex. to the gui.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+// how many more moves the budget is split over in sudden death.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+// the share of the time left a single move can take at most.
+const MAX_SHARE: f64 = 0.6;
+// how far the soft budget can stretch, and how far the hard budget goes past it.
+const MAX_STRETCH: f64 = 3.0;
+const HARD_STRETCH: f64 = 5.0;
+// how far the score has to drop between iterations to count as failing low.
+const FAIL_LOW_MARGIN: i32 = 30;
+const MIN_BUDGET: Duration = Duration::from_millis(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeControl {
+    /// the clock of the team to move.
+    Clock {
+        remaining: Duration,
+        increment: Duration,
+        // moves until the next time control, none for sudden death.
+        moves_to_go: Option<u32>,
+    },
+    /// exactly this long per move.
+    MoveTime(Duration),
+    /// until told to stop.
+    Infinite,
+}
+
+#[derive(Clone, Debug)]
+pub struct TimeManager {
+    control: TimeControl,
+    start: Instant,
+    soft: Option<Duration>,
+    hard: Option<Duration>,
+    // how unsettled the best move is, halved every iteration it stays the same.
+    instability: f64,
+    fail_low: bool,
+    last: Option<(MoveKey, i32)>,
+}
+
+impl TimeManager {
+    /// the clock starts now.
+    pub fn new(control: TimeControl) -> Self {
+        let (soft, hard) = match control {
+            TimeControl::Clock {
+                remaining,
+                increment,
+                moves_to_go,
+            } => {
+                let available = remaining.saturating_sub(MOVE_OVERHEAD);
+                let moves_to_go = moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+                let max = available.mul_f64(MAX_SHARE).max(MIN_BUDGET);
+
+                let soft = (available / moves_to_go + increment.mul_f64(0.75)).clamp(MIN_BUDGET, max);
+                let hard = soft.mul_f64(HARD_STRETCH).min(max);
+                (Some(soft), Some(hard))
+            }
+            TimeControl::MoveTime(movetime) => (Some(movetime), Some(movetime)),
+            TimeControl::Infinite => (None, None),
+        };
+
+        Self {
+            control,
+            start: Instant::now(),
+            soft,
+            hard,
+            instability: 0.0,
+            fail_low: false,
+            last: None,
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// when no new iteration should start, stretched by how unsettled the search is.
+    /// a fixed movetime doesn't stretch.
+    pub fn soft_limit(&self) -> Option<Duration> {
+        let soft = self.soft?;
+        if !matches!(self.control, TimeControl::Clock { .. }) {
+            return Some(soft);
+        }
+
+        let mut stretch = 1.0 + self.instability * 0.5;
+        if self.fail_low {
+            stretch *= 1.5;
+        }
+        let soft = soft.mul_f64(stretch.min(MAX_STRETCH));
+        Some(self.hard.map_or(soft, |hard| soft.min(hard)))
+    }
+
+    /// when the search has to stop, finished or not.
+    pub fn hard_limit(&self) -> Option<Duration> {
+        self.hard
+    }
+
+    /// takes in a finished iteration's best move and score.
+    pub fn iteration(&mut self, best_move: MoveKey, score: i32) {
+        let (changed, dropped) = match self.last {
+            Some((last_move, last_score)) => (last_move != best_move, last_score - score > FAIL_LOW_MARGIN),
+            None => (false, false),
+        };
+
+        self.instability = self.instability * 0.5 + changed as u32 as f64;
+        self.fail_low = dropped;
+        self.last = Some((best_move, score));
+    }
+
+    /// whether another iteration should start.
+    pub fn keep_going(&self) -> bool {
+        self.soft_limit().is_none_or(|soft| self.elapsed() < soft)
+    }
+
+    /// whether the search has to stop right away.
+    pub fn out_of_time(&self) -> bool {
+        self.hard.is_some_and(|hard| self.elapsed() >= hard)
+    }
+}
@@ -0,0 +1,243 @@
+// transposition table, results of searched positions by hash.
+// entries are three atomics, the first one being the key xored with the other two. a read that
+// races with a write gets a key that doesn't match and is treated as a miss, so threads can share
+// the table without locks.
+
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use chess::r#move::{Coord, MoveKey};
+
+use crate::search::{is_mate_score, MATE};
+
+pub const DEFAULT_HASH_MB: usize = 16;
+
+const BUCKET_SIZE: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    // the score is exact.
+    Exact,
+    // the score is at least this, the search failed high.
+    Lower,
+    // the score is at most this, the search failed low.
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TtEntry {
+    pub depth: u32,
+    pub bound: Bound,
+    pub score: i32,
+    pub best_move: Option<MoveKey>,
+}
+
+#[derive(Default)]
+struct Slot {
+    check: AtomicU64,
+    data: AtomicU64,
+    best_move: AtomicU64,
+}
+
+#[derive(Default)]
+struct Bucket {
+    slots: [Slot; BUCKET_SIZE],
+}
+
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    // bumped every search, so entries from old searches get replaced first.
+    generation: AtomicU8,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_MB)
+    }
+}
+
+impl TranspositionTable {
+    pub fn new(hash_mb: usize) -> Self {
+        let mut table = Self {
+            buckets: Vec::new(),
+            generation: AtomicU8::new(0),
+        };
+        table.resize(hash_mb);
+        table
+    }
+
+    /// throws away every entry.
+    pub fn resize(&mut self, hash_mb: usize) {
+        let count = (hash_mb * 1024 * 1024 / size_of::<Bucket>()).max(1);
+        self.buckets = (0..count).map(|_| Bucket::default()).collect();
+    }
+
+    pub fn clear(&self) {
+        for slot in self.buckets.iter().flat_map(|bucket| &bucket.slots) {
+            slot.check.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+            slot.best_move.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// how many entries fit.
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() * BUCKET_SIZE
+    }
+
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// mate scores are relative to the node they're stored from, ply is how far that is from the root.
+    pub fn probe(&self, hash: u64, ply: i32) -> Option<TtEntry> {
+        let bucket = self.bucket(hash);
+        for slot in &bucket.slots {
+            let data = slot.data.load(Ordering::Relaxed);
+            let best_move = slot.best_move.load(Ordering::Relaxed);
+            if data == 0 || slot.check.load(Ordering::Relaxed) ^ data ^ best_move != hash {
+                continue;
+            }
+
+            let mut entry = unpack_data(data);
+            entry.score = score_from_table(entry.score, ply);
+            entry.best_move = unpack_move(best_move);
+            return Some(entry);
+        }
+        None
+    }
+
+    pub fn store(&self, hash: u64, ply: i32, entry: TtEntry) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let bucket = self.bucket(hash);
+
+        let mut victim = &bucket.slots[0];
+        let mut victim_worth = i32::MAX;
+        for slot in &bucket.slots {
+            let data = slot.data.load(Ordering::Relaxed);
+            if data == 0 {
+                victim = slot;
+                break;
+            }
+
+            let old = unpack_data(data);
+            let old_generation = (data >> 48) as u8;
+            let same_key = slot.check.load(Ordering::Relaxed) ^ data ^ slot.best_move.load(Ordering::Relaxed) == hash;
+            if same_key {
+                // a shallower result for the same position only replaces an old or inexact one.
+                if entry.bound != Bound::Exact && entry.depth + 2 < old.depth && old_generation == generation {
+                    return;
+                }
+                victim = slot;
+                break;
+            }
+
+            // the shallowest entry goes, old searches' entries count as shallower.
+            let age = generation.wrapping_sub(old_generation) as i32;
+            let worth = old.depth as i32 - 8 * age;
+            if worth < victim_worth {
+                victim = slot;
+                victim_worth = worth;
+            }
+        }
+
+        let mut stored = entry;
+        stored.score = score_to_table(entry.score, ply);
+        let data = pack_data(&stored, generation);
+        let best_move = pack_move(entry.best_move);
+        victim.data.store(data, Ordering::Relaxed);
+        victim.best_move.store(best_move, Ordering::Relaxed);
+        victim.check.store(hash ^ data ^ best_move, Ordering::Relaxed);
+    }
+
+    /// permille of the first thousand entries that were written during this search.
+    pub fn hashfull(&self) -> u32 {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let sample: Vec<&Slot> = self.buckets.iter().flat_map(|bucket| &bucket.slots).take(1000).collect();
+        let used = sample
+            .iter()
+            .filter(|slot| {
+                let data = slot.data.load(Ordering::Relaxed);
+                data != 0 && (data >> 48) as u8 == generation
+            })
+            .count();
+        (used * 1000 / sample.len().max(1)) as u32
+    }
+
+    fn bucket(&self, hash: u64) -> &Bucket {
+        // the high bits pick the bucket, so it doesn't depend only on the bits the check uses.
+        let idx = ((hash as u128 * self.buckets.len() as u128) >> 64) as usize;
+        &self.buckets[idx]
+    }
+}
+
+fn score_to_table(score: i32, ply: i32) -> i32 {
+    match score {
+        score if is_mate_score(score) && score > 0 => score + ply,
+        score if is_mate_score(score) => score - ply,
+        score => score,
+    }
+}
+
+fn score_from_table(score: i32, ply: i32) -> i32 {
+    match score {
+        score if is_mate_score(score) && score > 0 => (score - ply).min(MATE),
+        score if is_mate_score(score) => (score + ply).max(-MATE),
+        score => score,
+    }
+}
+
+// score in the low 32 bits, then depth, bound and generation. the top bit marks the slot as used.
+fn pack_data(entry: &TtEntry, generation: u8) -> u64 {
+    let bound = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+    (entry.score as u32 as u64)
+        | (entry.depth.min(u8::MAX as u32) as u64) << 32
+        | bound << 40
+        | (generation as u64) << 48
+        | 1 << 63
+}
+
+fn unpack_data(data: u64) -> TtEntry {
+    TtEntry {
+        depth: (data >> 32) as u8 as u32,
+        bound: match (data >> 40) & 0b11 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        },
+        score: data as u32 as i32,
+        best_move: None,
+    }
+}
+
+// a byte per coordinate, moves on boards wider or taller than 256 tiles aren't kept.
+fn pack_move(best_move: Option<MoveKey>) -> u64 {
+    let Some(key) = best_move else {
+        return 0;
+    };
+    let coords = [key.from.x(), key.from.y(), key.to.x(), key.to.y()];
+    if coords.iter().any(|c| *c > u8::MAX as u32) {
+        return 0;
+    }
+
+    let mut packed = coords.iter().fold(0, |packed, c| packed << 8 | *c as u64);
+    if let Some(promotion) = key.promotion {
+        packed |= (promotion as u64) << 32 | 1 << 40;
+    }
+    packed | 1 << 63
+}
+
+fn unpack_move(packed: u64) -> Option<MoveKey> {
+    if packed == 0 {
+        return None;
+    }
+    let byte = |shift: u32| (packed >> shift) as u8 as u32;
+    Some(MoveKey {
+        from: Coord::new(byte(24), byte(16)),
+        to: Coord::new(byte(8), byte(0)),
+        promotion: (packed & 1 << 40 != 0).then_some(byte(32) as u8),
+    })
+}
@@ -0,0 +1,216 @@
+// texel tuning: the evaluation weights are fit to the results of games, ex. our own self-play.
+// every quiet position of a game is labeled with how the game ended for the team to move, and the
+// weights are moved to make sigmoid(score) predict that, by minimizing the mean squared error.
+// the score of a position is the sum of its evaluation terms times their weights, so the terms
+// are worked out once and the weights are fit with gradient descent (adam).
+// the sigmoid's scale is fit first, to the weights the tuning starts from.
+
+use chess::error::Result;
+use chess::game::Game;
+use chess::pgn::PgnGame;
+use chess::piece::Piece;
+
+use crate::eval::{EvalParams, Evaluator, PieceWeights};
+
+// the first plies of a game come from its opening, not the engines.
+pub const DEFAULT_SKIP_PLIES: usize = 8;
+const LEARNING_RATE: f64 = 1.0;
+const BETA1: f64 = 0.9;
+const BETA2: f64 = 0.999;
+const EPSILON: f64 = 1e-8;
+
+// a position's terms, as (weight index, term) for the ones that aren't 0.
+struct Position {
+    terms: Vec<(usize, f64)>,
+    // 1 when the team to move went on to win, 0.5 for a draw.
+    result: f64,
+}
+
+pub struct Tuner {
+    catalog: Vec<Piece>,
+    // each symbol has the 4 weights of PieceWeights, in order.
+    symbols: Vec<char>,
+    weights: Vec<f64>,
+    positions: Vec<Position>,
+    evaluator: Evaluator,
+    skip_plies: usize,
+    scale: f64,
+    // adam's running averages of the gradient and its square.
+    moments: Vec<(f64, f64)>,
+    steps: i32,
+}
+
+impl Tuner {
+    /// starts from the catalog's default weights.
+    pub fn new(catalog: Vec<Piece>) -> Self {
+        let params = EvalParams::seeded(&catalog);
+        let symbols: Vec<char> = catalog
+            .iter()
+            .map(|piece| piece.read().unwrap().symbol().to_ascii_uppercase())
+            .collect();
+        let weights = symbols
+            .iter()
+            .flat_map(|symbol| params.get(*symbol).unwrap().to_array().map(|weight| weight as f64))
+            .collect::<Vec<_>>();
+
+        Self {
+            catalog,
+            moments: vec![(0.0, 0.0); weights.len()],
+            symbols,
+            weights,
+            positions: Vec::new(),
+            evaluator: Evaluator::new(),
+            skip_plies: DEFAULT_SKIP_PLIES,
+            scale: 1.0,
+            steps: 0,
+        }
+    }
+
+    /// starts from earlier weights, pieces without any keep their defaults.
+    pub fn with_params(mut self, params: &EvalParams) -> Self {
+        for (idx, symbol) in self.symbols.iter().enumerate() {
+            if let Some(weights) = params.get(*symbol) {
+                for (weight, value) in self.weights[idx * 4..idx * 4 + 4].iter_mut().zip(weights.to_array()) {
+                    *weight = value as f64;
+                }
+            }
+        }
+        self
+    }
+
+    pub fn with_skip_plies(mut self, skip_plies: usize) -> Self {
+        self.skip_plies = skip_plies;
+        self
+    }
+
+    /// adds the quiet positions of a finished game, giving back how many.
+    /// positions in check and ones where a piece is taken or promoted next aren't quiet.
+    pub fn add_game(&mut self, pgn: &PgnGame) -> Result<usize> {
+        let white_result = match pgn.result.as_str() {
+            "1-0" => 1.0,
+            "0-1" => 0.0,
+            "1/2-1/2" => 0.5,
+            _ => return Ok(0),
+        };
+
+        let mut game = pgn.start_with(&self.catalog)?;
+        let mut added = 0;
+        for (ply, san) in pgn.moves.iter().enumerate() {
+            let m = game.parse_san(san)?;
+            let quiet = !m.is_kill() && m.promotion().is_none() && !game.is_in_check(game.current_team().name());
+            if ply >= self.skip_plies && quiet {
+                let result = if game.current_move().is_multiple_of(2) { white_result } else { 1.0 - white_result };
+                let terms = self.terms(&game);
+                self.positions.push(Position { terms, result });
+                added += 1;
+            }
+            game.execute_move(&m)?;
+        }
+        Ok(added)
+    }
+
+    fn terms(&mut self, game: &Game) -> Vec<(usize, f64)> {
+        let mut terms = Vec::new();
+        for (symbol, piece_terms) in self.evaluator.terms(game) {
+            let Some(idx) = self.symbols.iter().position(|s| *s == symbol) else {
+                continue;
+            };
+            for (kind, term) in piece_terms.into_iter().enumerate() {
+                if term != 0.0 {
+                    terms.push((idx * 4 + kind, term));
+                }
+            }
+        }
+        terms
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// fits the sigmoid's scale to the current weights, giving it back.
+    pub fn fit_scale(&mut self) -> f64 {
+        // the error is convex enough in the scale for a ternary search.
+        let (mut low, mut high) = (0.01, 10.0);
+        for _ in 0..100 {
+            let a = low + (high - low) / 3.0;
+            let b = high - (high - low) / 3.0;
+            if self.error_with_scale(a) < self.error_with_scale(b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        self.scale = (low + high) / 2.0;
+        self.scale
+    }
+
+    /// the mean squared error of the predicted results.
+    pub fn error(&self) -> f64 {
+        self.error_with_scale(self.scale)
+    }
+
+    fn error_with_scale(&self, scale: f64) -> f64 {
+        let total: f64 = self
+            .positions
+            .iter()
+            .map(|position| (position.result - sigmoid(self.score(position), scale)).powi(2))
+            .sum();
+        total / self.positions.len().max(1) as f64
+    }
+
+    fn score(&self, position: &Position) -> f64 {
+        position.terms.iter().map(|(idx, term)| self.weights[*idx] * term).sum()
+    }
+
+    /// runs the steps of gradient descent, giving back the error after.
+    pub fn tune(&mut self, iterations: usize) -> f64 {
+        let n = self.positions.len().max(1) as f64;
+        for _ in 0..iterations {
+            let mut gradient = vec![0.0; self.weights.len()];
+            for position in &self.positions {
+                let predicted = sigmoid(self.score(position), self.scale);
+                // d/ds of (result - sigmoid(s))², leaving out the constant factors.
+                let slope = (predicted - position.result) * predicted * (1.0 - predicted);
+                for (idx, term) in &position.terms {
+                    gradient[*idx] += slope * term;
+                }
+            }
+
+            self.steps += 1;
+            for (idx, gradient) in gradient.into_iter().enumerate() {
+                let gradient = gradient * 2.0 * self.scale * std::f64::consts::LN_10 / 400.0 / n;
+                let (m, v) = &mut self.moments[idx];
+                *m = BETA1 * *m + (1.0 - BETA1) * gradient;
+                *v = BETA2 * *v + (1.0 - BETA2) * gradient * gradient;
+                let m_hat = *m / (1.0 - BETA1.powi(self.steps));
+                let v_hat = *v / (1.0 - BETA2.powi(self.steps));
+                self.weights[idx] -= LEARNING_RATE * m_hat / (v_hat.sqrt() + EPSILON);
+            }
+        }
+        self.error()
+    }
+
+    /// the weights, rounded to centipoints.
+    pub fn params(&self) -> EvalParams {
+        let mut params = EvalParams::new();
+        for (idx, symbol) in self.symbols.iter().enumerate() {
+            let weights: [f64; 4] = self.weights[idx * 4..idx * 4 + 4].try_into().unwrap();
+            params.set(*symbol, PieceWeights::from_array(weights.map(|weight| weight.round() as i32)));
+        }
+        params
+    }
+}
+
+// the expected result for a score, from 0 to 1.
+fn sigmoid(score: f64, scale: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-scale * score / 400.0))
+}
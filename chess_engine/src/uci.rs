@@ -0,0 +1,510 @@
+// the uci protocol, for guis and match runners.
+// commands come in one line at a time and answers go to the writer. searches run in the background
+// (see controller.rs), so stop and isready get answered while they go on.
+// other boards and pieces are picked with UCI_Variant, and more of them loaded from a file with
+// VariantPath, like fairy-stockfish does it. with a BookFile, moves in the book are played without
+// searching, polyglot books for standard chess and variant books for the rest. an EvalFile
+// replaces the evaluation weights with tuned ones, and an NNUEFile evaluates with a network in
+// the variants it's made for. with a MultiPV above 1 every line is reported
+// with its number, and the best one is played.
+// Skill Level, or UCI_Elo with UCI_LimitStrength, makes the engine play weaker, and a Personality
+// changes what its evaluation likes.
+// go ponder searches without limits until a ponderhit, which starts the real search with go's
+// limits and its clock running from then on. a stop while pondering answers with the ponder
+// search's move.
+
+use std::io::{BufRead, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chess::error::Result;
+use chess::game::Game;
+use chess::r#move::{Move, MoveKey};
+
+use crate::book::{Book, BookFormat};
+use crate::controller::{Controller, SearchEvent, SearchInfo};
+use crate::eval::EvalParams;
+use crate::nnue::Network;
+use crate::search::{is_mate_score, resolve_pv, SearchLimits, MATE};
+use crate::skill::{Personality, Skill, MAX_ELO, MAX_LEVEL, MIN_ELO};
+use crate::smp::SmpSearcher;
+use crate::time::TimeControl;
+use crate::tt::{TranspositionTable, DEFAULT_HASH_MB};
+use crate::variant::{builtin_variants, parse_variants, Variant};
+
+pub const ENGINE_NAME: &str = "chess-rs";
+pub const ENGINE_AUTHOR: &str = "niooii";
+
+const MAX_HASH_MB: usize = 4096;
+const MAX_THREADS: usize = 256;
+const MAX_MULTI_PV: usize = 256;
+
+pub struct Uci<W: Write + Send + 'static> {
+    out: Arc<Mutex<W>>,
+    game: Game,
+    variants: Vec<Variant>,
+    // the one positions are set up in.
+    variant: Variant,
+    book: Option<Book>,
+    eval_params: EvalParams,
+    network: Option<Arc<Network>>,
+    personality: Personality,
+    threads: usize,
+    multi_pv: usize,
+    skill_level: u32,
+    // UCI_Elo is only used with UCI_LimitStrength, instead of the skill level.
+    limit_strength: bool,
+    elo: u32,
+    controller: Controller,
+}
+
+// the words go knows, searchmoves takes every word after it up to the next of these.
+const GO_ARGS: [&str; 12] = [
+    "searchmoves",
+    "ponder",
+    "wtime",
+    "btime",
+    "winc",
+    "binc",
+    "movestogo",
+    "depth",
+    "nodes",
+    "mate",
+    "movetime",
+    "infinite",
+];
+
+impl<W: Write + Send + 'static> Uci<W> {
+    pub fn new(out: W) -> Self {
+        let variants = builtin_variants();
+        Self {
+            out: Arc::new(Mutex::new(out)),
+            game: variants[0].start().unwrap(),
+            variant: variants[0].clone(),
+            variants,
+            book: None,
+            eval_params: EvalParams::new(),
+            network: None,
+            personality: Personality::Normal,
+            threads: 1,
+            multi_pv: 1,
+            skill_level: MAX_LEVEL,
+            limit_strength: false,
+            elo: MAX_ELO,
+            controller: Controller::new(SmpSearcher::new(SearchLimits::new(), 1)),
+        }
+    }
+
+    /// handles every line until quit or the end of the input.
+    pub fn run(&mut self, input: impl BufRead) {
+        for line in input.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if !self.handle(&line) {
+                return;
+            }
+        }
+        self.controller.wait();
+    }
+
+    /// handles one command, false once it's time to quit.
+    pub fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = tokens.split_first() else {
+            return true;
+        };
+
+        match *command {
+            "uci" => {
+                self.send(&format!("id name {}", ENGINE_NAME));
+                self.send(&format!("id author {}", ENGINE_AUTHOR));
+                self.send(&format!(
+                    "option name Hash type spin default {} min 1 max {}",
+                    DEFAULT_HASH_MB, MAX_HASH_MB
+                ));
+                self.send(&format!("option name Threads type spin default 1 min 1 max {}", MAX_THREADS));
+                self.send("option name Ponder type check default false");
+                self.send("option name Clear Hash type button");
+                self.send(&format!("option name MultiPV type spin default 1 min 1 max {}", MAX_MULTI_PV));
+                self.send(&format!("option name UCI_Variant type combo default chess {}", self.variant_list()));
+                self.send("option name VariantPath type string default <empty>");
+                self.send("option name BookFile type string default <empty>");
+                self.send("option name EvalFile type string default <empty>");
+                self.send("option name NNUEFile type string default <empty>");
+                self.send(&format!("option name Skill Level type spin default {} min 0 max {}", MAX_LEVEL, MAX_LEVEL));
+                self.send("option name UCI_LimitStrength type check default false");
+                self.send(&format!(
+                    "option name UCI_Elo type spin default {} min {} max {}",
+                    MAX_ELO, MIN_ELO, MAX_ELO
+                ));
+                let personalities: Vec<String> =
+                    Personality::ALL.iter().map(|personality| format!("var {}", personality.name())).collect();
+                self.send(&format!("option name Personality type combo default normal {}", personalities.join(" ")));
+                self.send("uciok");
+            }
+            "isready" => self.send("readyok"),
+            "ucinewgame" => {
+                self.game = self.variant.start().unwrap();
+                self.searcher().table().clear();
+            }
+            "position" => {
+                self.controller.wait();
+                match position(&self.variant, args) {
+                    Ok(game) => self.game = game,
+                    Err(e) => self.send(&format!("info string {}", e)),
+                }
+            }
+            "go" => {
+                self.controller.wait();
+                if let Err(e) = self.go(args) {
+                    self.send(&format!("info string {}", e));
+                    self.send("bestmove 0000");
+                }
+            }
+            "ponderhit" => self.controller.ponderhit(),
+            "stop" => {
+                self.controller.stop();
+                self.controller.wait();
+            }
+            "setoption" => {
+                self.controller.wait();
+                self.set_option(args);
+            }
+            "quit" => {
+                self.controller.stop();
+                self.controller.wait();
+                return false;
+            }
+            // nothing to do for these.
+            "debug" | "register" => {}
+            _ => self.send(&format!("info string unknown command {}", command)),
+        }
+
+        true
+    }
+
+    fn go(&mut self, args: &[&str]) -> Result<()> {
+        let value = |idx: usize| args.get(idx + 1).and_then(|value| value.parse::<u64>().ok());
+        let teams = self.game.board().piece_sets().len();
+        let side = self.game.current_move() as usize % teams;
+
+        let mut limits = SearchLimits::new();
+        let mut remaining = [None, None];
+        let mut increment = [None, None];
+        let mut moves_to_go = None;
+        let mut movetime = None;
+        let mut infinite = false;
+        let mut pondering = false;
+        let mut root_moves: Option<Vec<MoveKey>> = None;
+
+        for (idx, arg) in args.iter().enumerate() {
+            match *arg {
+                "depth" => limits.depth = value(idx).map(|depth| depth as u32),
+                "nodes" => limits.nodes = value(idx),
+                // a mate in n moves takes 2n - 1 plies.
+                "mate" => limits.depth = value(idx).map(|moves| (moves * 2).saturating_sub(1) as u32),
+                "movetime" => movetime = value(idx).map(Duration::from_millis),
+                "wtime" => remaining[0] = value(idx).map(Duration::from_millis),
+                "btime" => remaining[1] = value(idx).map(Duration::from_millis),
+                "winc" => increment[0] = value(idx).map(Duration::from_millis),
+                "binc" => increment[1] = value(idx).map(Duration::from_millis),
+                "movestogo" => moves_to_go = value(idx).map(|moves| moves as u32),
+                "infinite" => infinite = true,
+                "ponder" => pondering = true,
+                "searchmoves" => {
+                    let moves = args[idx + 1..].iter().take_while(|arg| !GO_ARGS.contains(arg));
+                    root_moves = Some(moves.map(|uci| Ok(self.game.parse_uci(uci)?.key())).collect::<Result<_>>()?);
+                }
+                _ => {}
+            }
+        }
+
+        let control = match (movetime, remaining.get(side).copied().flatten()) {
+            _ if infinite => None,
+            (Some(movetime), _) => Some(TimeControl::MoveTime(movetime)),
+            (None, Some(remaining)) => Some(TimeControl::Clock {
+                remaining,
+                increment: increment.get(side).copied().flatten().unwrap_or_default(),
+                moves_to_go,
+            }),
+            (None, None) => None,
+        };
+
+        if !infinite && !pondering {
+            if let Some(book_move) = self.book_move() {
+                self.send(&format!("bestmove {}", self.game.move_to_uci(&book_move)));
+                return Ok(());
+            }
+        }
+
+        self.controller.set_skill(self.skill());
+        self.controller.set_multi_pv(self.multi_pv);
+        self.searcher().set_root_moves(root_moves);
+        self.listen()?;
+        if pondering {
+            self.controller.ponder_after(&self.game, limits, control)
+        } else if infinite {
+            self.controller.analyze(&self.game)
+        } else {
+            self.controller.go(&self.game, limits, control)
+        }
+    }
+
+    // writes out what the searches of the current position find.
+    fn listen(&mut self) -> Result<()> {
+        let mut game = self.game.deep_clone()?;
+        let out = self.out.clone();
+        self.controller.set_on_event(Some(Box::new(move |event| {
+            let line = match event {
+                SearchEvent::Info(info) => info_line(&mut game, &info),
+                SearchEvent::BestMove { best_move, ponder, .. } => {
+                    let keys: Vec<MoveKey> = best_move.into_iter().chain(ponder).collect();
+                    let moves: Vec<String> = resolve_pv(&mut game, &keys).iter().map(|m| game.move_to_uci(m)).collect();
+                    match moves.as_slice() {
+                        [] => "bestmove 0000".to_string(),
+                        [best_move] => format!("bestmove {}", best_move),
+                        [best_move, ponder, ..] => format!("bestmove {} ponder {}", best_move, ponder),
+                    }
+                }
+                SearchEvent::Error(e) => format!("info string {}\nbestmove 0000", e),
+            };
+            let mut out = out.lock().unwrap();
+            let _ = writeln!(out, "{}", line);
+            let _ = out.flush();
+        })));
+        Ok(())
+    }
+
+    // setoption name <name> [value <value>], names can have spaces.
+    fn set_option(&mut self, args: &[&str]) {
+        let value_idx = args.iter().position(|arg| *arg == "value").unwrap_or(args.len());
+        let name = args.get(1..value_idx).unwrap_or_default().join(" ");
+        let value = args.get(value_idx + 1..).unwrap_or_default().join(" ");
+
+        match name.to_ascii_lowercase().as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(hash_mb) => {
+                    let tt = Arc::new(TranspositionTable::new(hash_mb.clamp(1, MAX_HASH_MB)));
+                    self.controller.update_searcher(|searcher| searcher.with_table(tt));
+                }
+                Err(_) => self.send(&format!("info string {} is not a hash size", value)),
+            },
+            "threads" => match value.parse::<usize>() {
+                Ok(threads) => {
+                    self.threads = threads.clamp(1, MAX_THREADS);
+                    let (threads, network) = (self.threads, self.network.clone());
+                    self.controller.update_searcher(|searcher| {
+                        SmpSearcher::new(SearchLimits::new(), threads)
+                            .with_table(searcher.table().clone())
+                            .with_network(network)
+                    });
+                    self.apply_eval_params();
+                }
+                Err(_) => self.send(&format!("info string {} is not a thread count", value)),
+            },
+            "clear hash" => self.searcher().table().clear(),
+            // the gui only says whether it's going to send go ponder.
+            "ponder" => {}
+            "multipv" => match value.parse::<usize>() {
+                Ok(multi_pv) => self.multi_pv = multi_pv.clamp(1, MAX_MULTI_PV),
+                Err(_) => self.send(&format!("info string {} is not a number of lines", value)),
+            },
+            "uci_variant" => match self.variants.iter().find(|variant| variant.name() == value) {
+                Some(variant) => {
+                    self.variant = variant.clone();
+                    self.game = self.variant.start().unwrap();
+                    // a personality changes the new variant's pieces.
+                    self.apply_eval_params();
+                    self.send_variant();
+                }
+                None => self.send(&format!("info string unknown variant {}", value)),
+            },
+            "bookfile" if value.is_empty() || value == "<empty>" => self.book = None,
+            "bookfile" => match self.load_book(&value) {
+                Ok(book) => {
+                    self.send(&format!("info string book {} with {} entries", value, book.len()));
+                    self.book = Some(book);
+                }
+                Err(e) => self.send(&format!("info string {}", e)),
+            },
+            "evalfile" => {
+                let loaded = match value.as_str() {
+                    "" | "<empty>" => Ok(EvalParams::new()),
+                    path => std::fs::read_to_string(path)
+                        .map_err(|e| format!("can't read {}: {}", path, e))
+                        .and_then(|text| EvalParams::parse(&text).map_err(|e| e.to_string())),
+                };
+                match loaded {
+                    Ok(params) => {
+                        self.eval_params = params;
+                        self.apply_eval_params();
+                    }
+                    Err(e) => self.send(&format!("info string {}", e)),
+                }
+            }
+            "nnuefile" => {
+                let loaded = match value.as_str() {
+                    "" | "<empty>" => Ok(None),
+                    path => std::fs::read(path)
+                        .map_err(|e| format!("can't read {}: {}", path, e))
+                        .and_then(|bytes| Network::from_bytes(&bytes).map_err(|e| e.to_string()))
+                        .map(|network| Some(Arc::new(network))),
+                };
+                match loaded {
+                    Ok(network) => {
+                        if let Some(network) = &network {
+                            let fits = if network.fits(&self.game) { "" } else { ", not made for this variant" };
+                            self.send(&format!("info string network {} with {} inputs{}", value, network.inputs(), fits));
+                        }
+                        self.network = network.clone();
+                        self.controller.update_searcher(|searcher| searcher.with_network(network));
+                    }
+                    Err(e) => self.send(&format!("info string {}", e)),
+                }
+            }
+            "skill level" => match value.parse::<u32>() {
+                Ok(level) => self.skill_level = level.min(MAX_LEVEL),
+                Err(_) => self.send(&format!("info string {} is not a skill level", value)),
+            },
+            "uci_limitstrength" => self.limit_strength = value == "true",
+            "uci_elo" => match value.parse::<u32>() {
+                Ok(elo) => self.elo = elo.clamp(MIN_ELO, MAX_ELO),
+                Err(_) => self.send(&format!("info string {} is not an elo", value)),
+            },
+            "personality" => match Personality::from_name(&value) {
+                Some(personality) => {
+                    self.personality = personality;
+                    self.apply_eval_params();
+                }
+                None => self.send(&format!("info string unknown personality {}", value)),
+            },
+            "variantpath" => match self.load_variants(&value) {
+                Ok(()) => self.send(&format!("info string variants {}", self.variant_list())),
+                Err(e) => self.send(&format!("info string {}", e)),
+            },
+            _ => self.send(&format!("info string unknown option {}", name)),
+        }
+    }
+
+    fn skill(&self) -> Skill {
+        if self.limit_strength {
+            Skill::from_elo(self.elo)
+        } else {
+            Skill::new(self.skill_level)
+        }
+    }
+
+    // the eval file's weights, changed for the personality.
+    fn eval_params(&self) -> Result<EvalParams> {
+        self.personality.params(self.variant.catalog(), &self.eval_params)
+    }
+
+    fn apply_eval_params(&mut self) {
+        match self.eval_params() {
+            Ok(params) => self.controller.update_searcher(|searcher| searcher.with_eval_params(params)),
+            Err(e) => self.send(&format!("info string {}", e)),
+        }
+    }
+
+    // the variants in a file are added, or replace the ones with the same name.
+    fn load_variants(&mut self, path: &str) -> std::result::Result<(), String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path, e))?;
+        let loaded = parse_variants(&text).map_err(|e| e.to_string())?;
+        for variant in loaded {
+            match self.variants.iter_mut().find(|v| v.name() == variant.name()) {
+                Some(existing) => *existing = variant,
+                None => self.variants.push(variant),
+            }
+        }
+        Ok(())
+    }
+
+    // polyglot for standard chess, our own format for everything else.
+    fn load_book(&self, path: &str) -> std::result::Result<Book, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("can't read {}: {}", path, e))?;
+        let format = if self.variant.name() == "chess" { BookFormat::Polyglot } else { BookFormat::Variant };
+        Book::from_bytes(&bytes, format).map_err(|e| e.to_string())
+    }
+
+    fn book_move(&self) -> Option<Move> {
+        let book = self.book.as_ref()?;
+        // positions the polyglot key can't describe just aren't in the book.
+        book.pick(&self.game, random()).ok().flatten()
+    }
+
+    fn variant_list(&self) -> String {
+        let names: Vec<String> = self.variants.iter().map(|variant| format!("var {}", variant.name())).collect();
+        names.join(" ")
+    }
+
+    // the selected variant's board, for guis that draw it themselves.
+    fn send_variant(&self) {
+        let board = self.game.board();
+        self.send(&format!(
+            "info string variant {} files {} ranks {} pocket 0 template fairy startpos {}",
+            self.variant.name(),
+            board.width(),
+            board.height(),
+            self.variant.start_fen()
+        ));
+    }
+
+    // between searches.
+    fn searcher(&mut self) -> &mut SmpSearcher {
+        self.controller.wait();
+        self.controller.searcher().unwrap()
+    }
+
+    fn send(&self, line: &str) {
+        let mut out = self.out.lock().unwrap();
+        let _ = writeln!(out, "{}", line);
+        let _ = out.flush();
+    }
+}
+
+// good enough for picking book moves and weaker moves.
+fn random() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64)
+}
+
+// position (startpos | fen <fen>) [moves <move>...], in the variant's pieces.
+fn position(variant: &Variant, args: &[&str]) -> Result<Game> {
+    let moves_idx = args.iter().position(|arg| *arg == "moves").unwrap_or(args.len());
+    let mut game = match args.first() {
+        Some(&"fen") => variant.game(&args[1..moves_idx].join(" "))?,
+        _ => variant.start()?,
+    };
+
+    for uci in args.iter().skip(moves_idx + 1) {
+        let m = game.parse_uci(uci)?;
+        game.execute_move(&m)?;
+    }
+    Ok(game)
+}
+
+/// cp for centipoints, mate for the moves until a mate, negative when the team to move gets mated.
+pub fn score_to_uci(score: i32) -> String {
+    if is_mate_score(score) {
+        let plies = MATE - score.abs();
+        let moves = (plies + 1) / 2;
+        format!("mate {}", if score > 0 { moves } else { -moves })
+    } else {
+        format!("cp {}", score)
+    }
+}
+
+fn info_line(game: &mut Game, info: &SearchInfo) -> String {
+    let millis = info.elapsed.as_millis().max(1);
+    let pv: Vec<String> = resolve_pv(game, &info.pv).iter().map(|m| game.move_to_uci(m)).collect();
+    format!(
+        "info depth {} multipv {} score {} nodes {} nps {} time {} pv {}",
+        info.depth,
+        info.multipv,
+        score_to_uci(info.score),
+        info.nodes,
+        info.nodes as u128 * 1000 / millis,
+        info.elapsed.as_millis(),
+        pv.join(" ")
+    )
+}
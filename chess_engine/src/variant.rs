@@ -0,0 +1,235 @@
+// variant definitions: a starting position and the pieces it's written with, in an ini style
+// file like fairy-stockfish's variants.ini, ex.
+//
+// [capablanca]
+// startFen = rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1
+// piece = a 7 BN archbishop
+// piece = c 9 RN chancellor
+// promotions = p qrbnac
+//
+// every variant starts from the six standard pieces. a piece line is the symbol, the points, the
+// rules in betza notation, then any of royal, castles, en_passant, jump_immune and pierce_immune
+// and an optional name. a piece with a symbol that's already there replaces it.
+// promotions lists what a piece can promote to, after every piece is defined. promotions a piece
+// already has follow its targets when they're replaced.
+// the board size comes from the starting position, files and ranks can be given to check it.
+// positions are fen, so variants have two teams.
+
+use chess::error::{ChessError, Result};
+use chess::fen::{standard_catalog, STARTING_FEN};
+use chess::game::Game;
+use chess::piece::{Piece, PieceBuilder};
+
+const BUILTIN_VARIANTS: &str = "
+[chess]
+startFen = rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
+
+# the 10x10 board the app starts with.
+[original]
+startFen = 1rnbkqbnr1/1pppppppp1/10/10/10/10/10/10/1PPPPPPPP1/1RNBQKBNR1 w KQkq - 0 1
+
+[capablanca]
+startFen = rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1
+piece = a 7 BN archbishop
+piece = c 9 RN chancellor
+promotions = p qrbnac
+";
+
+#[derive(Clone)]
+pub struct Variant {
+    name: String,
+    start_fen: String,
+    catalog: Vec<Piece>,
+}
+
+fn variant_error(why: String) -> ChessError {
+    ChessError::NotationError { why }
+}
+
+impl Variant {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn start_fen(&self) -> &str {
+        &self.start_fen
+    }
+
+    pub fn catalog(&self) -> &Vec<Piece> {
+        &self.catalog
+    }
+
+    pub fn start(&self) -> Result<Game> {
+        Game::from_fen_with(&self.start_fen, &self.catalog)
+    }
+
+    /// a position of the variant.
+    pub fn game(&self, fen: &str) -> Result<Game> {
+        Game::from_fen_with(fen, &self.catalog)
+    }
+}
+
+/// chess, the app's 10x10 board and capablanca chess.
+pub fn builtin_variants() -> Vec<Variant> {
+    parse_variants(BUILTIN_VARIANTS).unwrap()
+}
+
+pub fn parse_variants(text: &str) -> Result<Vec<Variant>> {
+    let mut variants = Vec::new();
+    let mut section: Option<Section> = None;
+
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        let line_error = |why: String| variant_error(format!("Line {}: {}", idx + 1, why));
+
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            if let Some(section) = section.take() {
+                variants.push(section.finish()?);
+            }
+            section = Some(Section::new(name.trim()));
+            continue;
+        }
+
+        let Some(section) = section.as_mut() else {
+            return Err(line_error("Expected a [variant] before anything else.".to_string()));
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(line_error(format!("Expected key = value, got {}.", line)));
+        };
+        section.set(key.trim(), value.trim()).map_err(|e| line_error(e.to_string()))?;
+    }
+
+    if let Some(section) = section {
+        variants.push(section.finish()?);
+    }
+    Ok(variants)
+}
+
+// a variant while it's being read.
+struct Section {
+    name: String,
+    start_fen: String,
+    catalog: Vec<Piece>,
+    // (symbol, what it promotes to)
+    promotions: Vec<(char, String)>,
+    size: (Option<u32>, Option<u32>),
+}
+
+impl Section {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            start_fen: STARTING_FEN.to_string(),
+            catalog: standard_catalog(),
+            promotions: Vec::new(),
+            size: (None, None),
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let number = |value: &str| value.parse::<u32>().map_err(|_| variant_error(format!("{} is not a number.", value)));
+        match key {
+            "startFen" => self.start_fen = value.to_string(),
+            "files" => self.size.0 = Some(number(value)?),
+            "ranks" => self.size.1 = Some(number(value)?),
+            "piece" => {
+                let piece = parse_piece(value)?;
+                let symbol = piece.read()?.symbol();
+                self.catalog.retain(|p| p.read().unwrap().symbol() != symbol);
+                self.catalog.push(piece);
+            }
+            "promotions" => {
+                let (symbol, targets) = value
+                    .split_once(char::is_whitespace)
+                    .ok_or(variant_error(format!("Expected a piece and what it promotes to, got {}.", value)))?;
+                let symbol = single_char(symbol)?;
+                self.promotions.push((symbol.to_ascii_uppercase(), targets.trim().to_string()));
+            }
+            _ => return Err(variant_error(format!("Unknown key {}.", key))),
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Variant> {
+        // promotions the pieces came with point at the standard pieces, a redefined one has to be
+        // looked up again so the same letter is always the same piece.
+        for piece in &self.catalog {
+            let symbols: Vec<char> = piece.read()?.promotions().iter().map(|p| p.symbol()).collect();
+            if symbols.is_empty() {
+                continue;
+            }
+            let promotions = symbols.into_iter().map(|symbol| self.piece(symbol).cloned()).collect::<Result<Vec<_>>>()?;
+            piece.write()?.set_promotions(promotions);
+        }
+        for (symbol, targets) in std::mem::take(&mut self.promotions) {
+            let promotions = targets
+                .chars()
+                .map(|target| self.piece(target.to_ascii_uppercase()).cloned())
+                .collect::<Result<Vec<_>>>()?;
+            self.piece(symbol)?.write()?.set_promotions(promotions);
+        }
+
+        let variant = Variant {
+            name: self.name,
+            start_fen: self.start_fen,
+            catalog: self.catalog,
+        };
+
+        // the starting position has to load, and fit the size if there is one.
+        let game = variant.start()?;
+        let (width, height) = (game.board().width(), game.board().height());
+        if self.size.0.is_some_and(|files| files != width) || self.size.1.is_some_and(|ranks| ranks != height) {
+            return Err(variant_error(format!(
+                "The starting position of {} is {}x{}, not the given size.",
+                variant.name, width, height
+            )));
+        }
+
+        Ok(variant)
+    }
+
+    fn piece(&self, symbol: char) -> Result<&Piece> {
+        self.catalog
+            .iter()
+            .find(|p| p.read().unwrap().symbol() == symbol)
+            .ok_or(variant_error(format!("Unknown piece {}.", symbol)))
+    }
+}
+
+// <symbol> <points> <betza> [flags...] [name]
+fn parse_piece(value: &str) -> Result<Piece> {
+    let tokens: Vec<&str> = value.split_whitespace().collect();
+    let [symbol, points, betza, rest @ ..] = tokens.as_slice() else {
+        return Err(variant_error(format!("Expected a symbol, points and betza, got {}.", value)));
+    };
+    let points = points
+        .parse::<u16>()
+        .map_err(|_| variant_error(format!("{} is not a number of points.", points)))?;
+
+    let mut builder = PieceBuilder::new()
+        .symbol(single_char(symbol)?.to_ascii_uppercase())
+        .points(points)
+        .betza(betza)?;
+    for token in rest {
+        builder = match *token {
+            "royal" => builder.royal(true),
+            "castles" => builder.castles(true),
+            "en_passant" => builder.en_passant(true),
+            "jump_immune" => builder.jump_immune(true),
+            "pierce_immune" => builder.pierce_immune(true),
+            name => builder.name(name.to_string()),
+        };
+    }
+    builder.build()
+}
+
+fn single_char(value: &str) -> Result<char> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(variant_error(format!("{} is not a single letter.", value))),
+    }
+}
@@ -0,0 +1,110 @@
+use chess::fen::{standard_catalog, STARTING_FEN};
+use chess::game::Game;
+use chess::pgn::parse_pgn;
+use chess_engine::book::{Book, BookBuilder, BookFormat};
+use chess_engine::polyglot::polyglot_key;
+use chess_engine::variant::builtin_variants;
+
+#[test]
+pub fn polyglot_keys() {
+    // the examples from the polyglot format's description.
+    let expected = [
+        (vec![], 0x463b96181691fc9c),
+        (vec!["e2e4"], 0x823c9b50fd114196),
+        (vec!["e2e4", "d7d5"], 0x0756b94461c50fb0),
+        (vec!["e2e4", "d7d5", "e4e5"], 0x662fafb965db29d4),
+        (vec!["e2e4", "d7d5", "e4e5", "f7f5"], 0x22a48b5a8e47ff78),
+        (vec!["e2e4", "d7d5", "e4e5", "f7f5", "e1e2"], 0x652a607ca3f242c1),
+        (vec!["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"], 0x00fdd303c946bdd9),
+        (vec!["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"], 0x3c8123ea7b067637),
+        (vec!["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"], 0x5c3f9b829b279560),
+    ];
+
+    for (moves, key) in expected {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        for uci in &moves {
+            let m = game.parse_uci(uci).unwrap();
+            game.execute_move(&m).unwrap();
+        }
+        assert_eq!(polyglot_key(&game).unwrap(), key, "{:?}", moves);
+    }
+
+    assert!(polyglot_key(&Game::original().unwrap()).is_err());
+}
+
+#[test]
+pub fn reading_and_picking() {
+    let game = Game::from_fen(STARTING_FEN).unwrap();
+    let mut book = Book::new(BookFormat::Polyglot);
+    book.add(&game, &game.parse_uci("e2e4").unwrap(), 3).unwrap();
+    book.add(&game, &game.parse_uci("d2d4").unwrap(), 1).unwrap();
+    book.add(&game, &game.parse_uci("e2e4").unwrap(), 1).unwrap();
+
+    // e2e4 is stored as polyglot writes it.
+    let entries = book.entries(&game).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!((entries[0].key, entries[0].mv, entries[0].weight), (0x463b96181691fc9c, 0x031c, 4));
+
+    let bytes = book.to_bytes();
+    assert_eq!(bytes.len(), 32);
+    assert_eq!(&bytes[..12], &[0x46, 0x3b, 0x96, 0x18, 0x16, 0x91, 0xfc, 0x9c, 0x03, 0x1c, 0x00, 0x04]);
+    let book = Book::from_bytes(&bytes, BookFormat::Polyglot).unwrap();
+    assert!(Book::from_bytes(&bytes[1..], BookFormat::Polyglot).is_err());
+
+    // weights of 4 and 1, so 0 to 3 pick e2e4 and 4 picks d2d4.
+    let pick = |random: u64| game.move_to_uci(&book.pick(&game, random).unwrap().unwrap());
+    assert_eq!(pick(0), "e2e4");
+    assert_eq!(pick(3), "e2e4");
+    assert_eq!(pick(4), "d2d4");
+    assert_eq!(pick(5), "e2e4");
+
+    let other = Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(book.pick(&other, 0).unwrap().is_none());
+
+    // castling goes onto the rook.
+    let castles = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    let mut book = Book::new(BookFormat::Polyglot);
+    book.add(&castles, &castles.parse_uci("e1g1").unwrap(), 1).unwrap();
+    assert_eq!(book.entries(&castles).unwrap()[0].mv, 0x0107);
+    let picked = book.pick(&castles, 0).unwrap().unwrap();
+    assert_eq!(castles.move_to_uci(&picked), "e1g1");
+}
+
+#[test]
+pub fn building() {
+    let games = parse_pgn(
+        "[Result \"1-0\"]\n1. e4 e5 2. Nf3 1-0\n[Result \"0-1\"]\n1. e4 c5 0-1\n[Result \"1/2-1/2\"]\n1. d4 d5 1/2-1/2\n",
+    )
+    .unwrap();
+    let mut builder = BookBuilder::new(BookFormat::Polyglot, standard_catalog()).with_plies(2);
+    for game in &games {
+        builder.add_game(game).unwrap();
+    }
+    let book = builder.build();
+
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    let moves: Vec<(String, u16)> = book
+        .moves(&game)
+        .unwrap()
+        .iter()
+        .map(|(m, weight)| (game.move_to_uci(m), *weight))
+        .collect();
+    assert_eq!(moves, [("e2e4".to_string(), 2), ("d2d4".to_string(), 1)]);
+
+    // e5 lost, so only c5 is left after e4, and nothing past the second ply.
+    let m = game.parse_uci("e2e4").unwrap();
+    game.execute_move(&m).unwrap();
+    let replies: Vec<String> = book.moves(&game).unwrap().iter().map(|(m, _)| game.move_to_uci(m)).collect();
+    assert_eq!(replies, ["c7c5"]);
+    assert_eq!(book.len(), 4);
+
+    // a 10x10 book goes by our own hashes.
+    let original = &builtin_variants()[1];
+    let games = parse_pgn(&format!("[FEN \"{}\"]\n1. e4 e7 2. Nd3 1-0\n", original.start_fen())).unwrap();
+    let mut builder = BookBuilder::new(BookFormat::Variant, original.catalog().clone());
+    builder.add_game(&games[0]).unwrap();
+    let book = Book::from_bytes(&builder.build().to_bytes(), BookFormat::Variant).unwrap();
+    let game = original.start().unwrap();
+    assert_eq!(book.entries(&game).unwrap()[0].key, game.hash());
+    assert_eq!(game.move_to_uci(&book.pick(&game, 7).unwrap().unwrap()), "e2e4");
+}
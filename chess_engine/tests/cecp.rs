@@ -0,0 +1,99 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+
+// runs the cecp binary on a script, the output once the input ran out and the engine is done.
+fn run(script: &str) -> Vec<String> {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_cecp"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    engine.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+    let output = engine.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap().lines().map(|line| line.to_string()).collect()
+}
+
+fn engine_moves(output: &[String]) -> Vec<&str> {
+    output
+        .iter()
+        .filter_map(|line| line.strip_prefix("move "))
+        .collect()
+}
+
+#[test]
+pub fn handshake() {
+    let output = run("xboard\nprotover 2\nping 7\n");
+    assert!(output[0].starts_with("feature "));
+    for feature in ["ping=1", "setboard=1", "usermove=1", "variants=\"normal,original,capablanca\""] {
+        assert!(output[0].contains(feature), "{}", output[0]);
+    }
+    assert_eq!(output[1], "feature done=1");
+    assert_eq!(output[2], "pong 7");
+
+    let output = run("nonsense\n");
+    assert_eq!(output[0], "Error (unknown command): nonsense");
+}
+
+#[test]
+pub fn plays_black_after_new() {
+    let output = run("xboard\nnew\npost\nsd 2\nusermove e2e4\nping 1\nusermove e2e5\n");
+    let thinking: Vec<&String> = output.iter().filter(|line| line.starts_with("2 ")).collect();
+    assert_eq!(thinking.len(), 1);
+    // depth, score, time, nodes, then the pv.
+    assert!(thinking[0].split_whitespace().count() >= 5, "{}", thinking[0]);
+
+    let moves = engine_moves(&output);
+    assert_eq!(moves.len(), 1);
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    for uci in ["e2e4", moves[0]] {
+        let m = game.parse_uci(uci).unwrap();
+        game.execute_move(&m).unwrap();
+    }
+
+    // the reply comes before the pong, and an illegal move gets refused.
+    let pong = output.iter().position(|line| line == "pong 1").unwrap();
+    assert!(output[..pong].iter().any(|line| line.starts_with("move ")));
+    assert_eq!(output.last().unwrap(), "Illegal move: e2e5");
+}
+
+#[test]
+pub fn force_undo_and_setboard() {
+    // in force mode nothing gets played, and undo takes back the moves.
+    let output = run("new\nforce\nusermove e2e4\nusermove e7e5\nremove\nundo\nusermove e2e4\n");
+    assert!(engine_moves(&output).is_empty());
+    assert_eq!(output, ["Error (nothing to undo): undo"]);
+
+    let output = run("new\nsetboard 7k/8/6K1/8/8/8/8/R7 w - - 0 1\nsd 3\ngo\n");
+    assert_eq!(output, ["move a1a8", "1-0 {White mates}"]);
+
+    let output = run("new\nsetboard nonsense\n");
+    assert!(output[0].starts_with("tellusererror Illegal position"));
+}
+
+#[test]
+pub fn variants_and_clocks() {
+    let output = run("new\nvariant capablanca\nlevel 40 0:10 0\ntime 1000\notim 1000\ngo\n");
+    assert!(output[0].starts_with("setup (PNBRQ..AC............Kpnbrq..ac............k) 10x8+0_capablanca "));
+    let moves = engine_moves(&output);
+    assert_eq!(moves.len(), 1);
+    let variant = &chess_engine::variant::builtin_variants()[2];
+    assert!(variant.start().unwrap().parse_uci(moves[0]).is_ok());
+
+    let output = run("new\nvariant nonsense\nlevel 40\n");
+    assert_eq!(output, ["Error (unknown variant): nonsense", "Error (bad level): 40"]);
+}
+
+#[test]
+pub fn move_now_and_force() {
+    // ? plays what the search has so far, and force throws it away.
+    let start = std::time::Instant::now();
+    let output = run("new\nst 60\ngo\n?\nping 2\n");
+    let pong = output.iter().position(|line| line == "pong 2").unwrap();
+    assert_eq!(engine_moves(&output[..pong]).len(), 1);
+    let output = run("new\nst 60\ngo\nforce\nping 3\n");
+    assert_eq!(output, ["pong 3"]);
+    assert!(start.elapsed() < std::time::Duration::from_secs(30));
+}
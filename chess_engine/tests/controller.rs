@@ -0,0 +1,172 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use chess::game::Game;
+use chess_engine::controller::{Controller, SearchEvent};
+use chess_engine::search::{SearchLimits, MATE};
+use chess_engine::smp::SmpSearcher;
+
+const OPENING: &str = "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3";
+
+// what's left of the search, up to its best move.
+fn finish(controller: &mut Controller) -> (Vec<SearchEvent>, SearchEvent) {
+    let mut events = Vec::new();
+    loop {
+        match controller
+            .recv_timeout(Duration::from_secs(60))
+            .expect("the search answers")
+        {
+            event @ SearchEvent::BestMove { .. } => return (events, event),
+            event => events.push(event),
+        }
+    }
+}
+
+#[test]
+pub fn searching() {
+    let game = Game::from_fen("7k/8/5K2/8/8/8/8/1R6 w - - 0 1").unwrap();
+    let mut controller = Controller::new(SmpSearcher::new(SearchLimits::new(), 2));
+    controller.go(&game, SearchLimits::new().depth(4), None).unwrap();
+    assert!(controller.searcher().is_none());
+
+    let (events, best) = finish(&mut controller);
+    let SearchEvent::BestMove {
+        best_move,
+        ponder,
+        score,
+        depth,
+        ..
+    } = best
+    else {
+        unreachable!()
+    };
+    assert_eq!(score, MATE - 3);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, SearchEvent::Info(info) if info.depth == depth && info.score == score)));
+    assert!(ponder.is_some());
+    let best_move = best_move.unwrap();
+    assert!(game.legal_moves().unwrap().iter().any(|m| m.key() == best_move));
+
+    controller.wait();
+    assert!(!controller.is_searching());
+    assert!(controller.searcher().is_some());
+    assert!(controller.poll().is_empty());
+}
+
+#[test]
+pub fn new_search_drops_the_old_one() {
+    let game = Game::from_fen(OPENING).unwrap();
+    let mut controller = Controller::new(SmpSearcher::new(SearchLimits::new(), 1));
+    controller.go(&game, SearchLimits::new(), None).unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    // only the mate is reported, nothing from the search without limits.
+    let mate = Game::from_fen("7k/8/5K2/8/8/8/8/1R6 w - - 0 1").unwrap();
+    controller.go(&mate, SearchLimits::new().depth(4), None).unwrap();
+    let (events, best) = finish(&mut controller);
+    assert!(events
+        .iter()
+        .all(|event| matches!(event, SearchEvent::Info(info) if info.depth <= 4)));
+    assert!(matches!(best, SearchEvent::BestMove { score, .. } if score == MATE - 3));
+}
+
+#[test]
+pub fn pondering() {
+    let mut game = Game::from_fen(OPENING).unwrap();
+    let mut controller = Controller::new(SmpSearcher::new(SearchLimits::new(), 2));
+    let reply = game.parse_uci("g8f6").unwrap().key();
+    controller
+        .ponder(&game, reply, SearchLimits::new().depth(3), None)
+        .unwrap();
+    assert!(controller.is_pondering());
+
+    // the ponder search keeps going until it's told what happened.
+    thread::sleep(Duration::from_millis(200));
+    assert!(controller.is_searching());
+    assert!(!controller
+        .poll()
+        .iter()
+        .any(|event| matches!(event, SearchEvent::BestMove { .. })));
+
+    controller.ponderhit();
+    assert!(!controller.is_pondering());
+    let (_, best) = finish(&mut controller);
+    let SearchEvent::BestMove { best_move, depth, .. } = best else {
+        unreachable!()
+    };
+    assert_eq!(depth, 3);
+    game.play(reply).unwrap();
+    assert!(game.legal_moves().unwrap().iter().any(|m| Some(m.key()) == best_move));
+
+    // stopping a ponder search still gives a move.
+    controller
+        .ponder(&game, best_move.unwrap(), SearchLimits::new().depth(3), None)
+        .unwrap();
+    thread::sleep(Duration::from_millis(100));
+    controller.stop();
+    assert!(!controller.is_pondering());
+    let (_, best) = finish(&mut controller);
+    assert!(matches!(best, SearchEvent::BestMove { best_move: Some(_), .. }));
+
+    // a ponder search that's done right away still waits to be told.
+    let mate = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let reply = mate.parse_uci("a1a8").unwrap().key();
+    controller.ponder(&mate, reply, SearchLimits::new(), None).unwrap();
+    thread::sleep(Duration::from_millis(200));
+    assert!(controller.is_searching());
+    assert!(controller.poll().is_empty());
+    controller.stop();
+    let (_, best) = finish(&mut controller);
+    assert!(matches!(best, SearchEvent::BestMove { best_move: None, .. }));
+
+    controller.ponder(&mate, reply, SearchLimits::new(), None).unwrap();
+    controller.cancel();
+    assert!(!controller.is_searching());
+    assert!(controller.poll().is_empty());
+}
+
+#[test]
+pub fn callbacks_and_analysis() {
+    let mate = Game::from_fen("7k/8/5K2/8/8/8/8/1R6 w - - 0 1").unwrap();
+    let mut controller = Controller::new(SmpSearcher::new(SearchLimits::new(), 1));
+    let (sender, receiver) = mpsc::channel();
+    controller.set_on_event(Some(Box::new(move |event| sender.send(event).unwrap())));
+
+    controller.go(&mate, SearchLimits::new().depth(4), None).unwrap();
+    controller.wait();
+    let events: Vec<SearchEvent> = receiver.try_iter().collect();
+    assert!(matches!(events.last(), Some(SearchEvent::BestMove { score, .. }) if *score == MATE - 3));
+    assert!(controller.poll().is_empty());
+
+    // an analysis search only answers once it's stopped, and nothing comes from one that's
+    // thrown away, not even its move.
+    controller.analyze(&mate).unwrap();
+    thread::sleep(Duration::from_millis(200));
+    assert!(controller.is_searching());
+    assert!(!receiver.try_iter().any(|event| matches!(event, SearchEvent::BestMove { .. })));
+    controller.stop();
+    controller.wait();
+    assert!(receiver.try_iter().any(|event| matches!(event, SearchEvent::BestMove { .. })));
+
+    controller.analyze(&mate).unwrap();
+    controller.cancel();
+    assert!(!receiver.try_iter().any(|event| matches!(event, SearchEvent::BestMove { .. })));
+
+    // the reply already played, the real search takes over at the ponderhit with a new searcher.
+    controller.update_searcher(|searcher| SmpSearcher::new(SearchLimits::new(), 2).with_table(searcher.table().clone()));
+    assert_eq!(controller.searcher().unwrap().threads(), 2);
+    controller.ponder_after(&mate, SearchLimits::new().depth(2), None).unwrap();
+    assert!(controller.is_pondering());
+    controller.ponderhit();
+    controller.wait();
+    let best = receiver.try_iter().last();
+    assert!(matches!(best, Some(SearchEvent::BestMove { best_move: Some(_), .. })), "{:?}", best);
+
+    controller.set_on_event(None);
+    controller.go(&mate, SearchLimits::new().depth(1), None).unwrap();
+    let (_, best) = finish(&mut controller);
+    assert!(matches!(best, SearchEvent::BestMove { best_move: Some(_), .. }));
+    assert!(receiver.try_iter().next().is_none());
+}
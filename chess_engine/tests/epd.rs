@@ -0,0 +1,69 @@
+use std::process::Command;
+
+use chess_engine::epd::{parse_epd, EpdPosition};
+use chess_engine::search::{search, SearchLimits};
+
+#[test]
+pub fn parse_operations() {
+    let position = EpdPosition::parse(
+        r#"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 bm e5 c5; am f6; id "open; game"; c0 "a comment"; hmvc 0; fmvn 1;"#,
+    )
+    .unwrap();
+
+    assert_eq!(position.best_moves(), ["e5", "c5"]);
+    assert_eq!(position.avoid_moves(), ["f6"]);
+    assert_eq!(position.id(), Some("open; game"));
+    assert_eq!(position.comment(0), Some("a comment"));
+    assert_eq!(position.comment(1), None);
+    assert_eq!(position.fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert_eq!(position.operations().len(), 6);
+    assert!(position.game().is_ok());
+}
+
+#[test]
+pub fn parse_errors() {
+    assert!(EpdPosition::parse("8/8/8 w").is_err());
+    assert!(EpdPosition::parse("8/8/8/8/8/8/8/8 w - - bm e4").is_err());
+    assert!(EpdPosition::parse(r#"8/8/8/8/8/8/8/8 w - - id "open;"#).is_err());
+
+    let suite = "# comment\n\n8/8/8/8/8/8/8/8 w - - id \"a\";\nbad line\n";
+    assert!(parse_epd(suite).is_err());
+}
+
+#[test]
+pub fn solve_mate_in_one() {
+    let positions = parse_epd(
+        "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - bm Qxf7#; id \"scholar\";\n\
+         6k1/5ppp/8/8/8/8/8/R3K3 w Q - am O-O-O; id \"back rank\";\n",
+    )
+    .unwrap();
+
+    for position in positions {
+        let mut game = position.game().unwrap();
+        let result = search(&mut game, SearchLimits::new().depth(2)).unwrap();
+        let best_move = result.best_move.unwrap();
+        assert!(position.is_solved_by(&game, &best_move).unwrap(), "{:?}", position.id());
+    }
+}
+
+#[test]
+pub fn runner_limits() {
+    let path = std::env::temp_dir().join(format!("chess-rs-suite-{}.epd", std::process::id()));
+    std::fs::write(&path, "6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id \"back rank\";\n").unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_epd")).arg(&path).args(args).output().unwrap();
+        (output.status.success(), String::from_utf8(output.stdout).unwrap())
+    };
+
+    let (ok, output) = run(&["--movetime", "100"]);
+    assert!(ok);
+    assert!(output.starts_with("ok   back rank played Ra8#"), "{}", output);
+    assert!(output.contains("solved 1/1 at 100ms a move in"), "{}", output);
+
+    let (_, output) = run(&["--depth", "2", "--movetime", "100"]);
+    assert!(output.contains("solved 1/1 at depth 2, 100ms a move in"), "{}", output);
+    let (_, output) = run(&[]);
+    assert!(output.contains("solved 1/1 at depth 3 in"), "{}", output);
+    assert!(!run(&["--movetime", "soon"]).0);
+    std::fs::remove_file(&path).unwrap();
+}
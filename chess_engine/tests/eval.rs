@@ -0,0 +1,55 @@
+use chess::fen::{standard_catalog, STARTING_FEN};
+use chess::game::Game;
+use chess::notation::parse_square;
+use chess::piece::PieceBuilder;
+use chess_engine::eval::Evaluator;
+
+#[test]
+pub fn balanced_and_material() {
+    let mut evaluator = Evaluator::new();
+    assert_eq!(evaluator.evaluate(&Game::from_fen(STARTING_FEN).unwrap()), 0);
+
+    // white is a queen up, and it's black's turn in the second position.
+    let white_up = evaluator.evaluate(&Game::from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap());
+    assert!(white_up > 800, "{}", white_up);
+    let black_to_move = evaluator.evaluate(&Game::from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap());
+    assert_eq!(black_to_move, -white_up);
+}
+
+#[test]
+pub fn tables_follow_geometry() {
+    let game = Game::from_fen("4k3/8/8/8/8/8/P7/N3K3 w - - 0 1").unwrap();
+    let board = game.board();
+    let mut evaluator = Evaluator::new();
+    let idx = |square: &str| {
+        let coord = parse_square(square).unwrap();
+        (coord.y() * board.width() + coord.x()) as usize
+    };
+
+    let knight = board.tile(parse_square("a1").unwrap()).unwrap().read().unwrap().piece().unwrap();
+    let table = evaluator.table(board, 0, &knight.read().unwrap()).clone();
+    assert!(table[idx("d4")] > table[idx("a1")]);
+    assert!(table[idx("e5")] > table[idx("h8")]);
+
+    let pawn = board.tile(parse_square("a2").unwrap()).unwrap().read().unwrap().piece().unwrap();
+    let table = evaluator.table(board, 0, &pawn.read().unwrap()).clone();
+    assert!(table[idx("d7")] > table[idx("d5")]);
+    assert!(table[idx("d5")] > table[idx("d2")]);
+
+    let king = board.tile(parse_square("e1").unwrap()).unwrap().read().unwrap().piece().unwrap();
+    assert!(evaluator.table(board, 0, &king.read().unwrap()).iter().all(|bonus| *bonus == 0));
+}
+
+#[test]
+pub fn user_pieces() {
+    // an unnamed bishop-knight compound only white has.
+    let mut catalog = standard_catalog();
+    catalog.push(PieceBuilder::new().symbol('A').points(7).betza("BN").unwrap().build().unwrap());
+
+    let game = Game::from_fen_with("4k3/8/8/8/3A4/8/8/4K3 w - - 0 1", &catalog).unwrap();
+    let centered = Evaluator::new().evaluate(&game);
+    let game = Game::from_fen_with("4k3/8/8/8/8/8/8/A3K3 w - - 0 1", &catalog).unwrap();
+    let cornered = Evaluator::new().evaluate(&game);
+    assert!(centered > cornered);
+    assert!(cornered > 600);
+}
@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+use chess::game::Game;
+use chess_engine::mcts::{MctsSearcher, Playout};
+use chess_engine::search::{is_mate_score, SearchLimits};
+
+#[test]
+pub fn finds_mate_in_one() {
+    for playout in [Playout::Random, Playout::Light] {
+        let mut game = Game::from_fen("7k/8/6K1/8/8/8/8/R7 w - - 0 1").unwrap();
+        let mut searcher = MctsSearcher::new(SearchLimits::new().nodes(200))
+            .with_playout(playout)
+            .with_playout_depth(4)
+            .with_seed(7);
+        let result = searcher.search(&mut game).unwrap();
+        assert_eq!(game.move_to_uci(&result.best_move.unwrap()), "a1a8", "{:?}", playout);
+        assert!(is_mate_score(result.score) && result.score > 0);
+        assert_eq!(result.nodes, 200);
+
+        // the search leaves the game as it was.
+        assert_eq!(game.history().len(), 0);
+    }
+}
+
+#[test]
+pub fn takes_hanging_queen() {
+    let mut game = Game::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+    let mut searcher = MctsSearcher::new(SearchLimits::new().nodes(300))
+        .with_playout_depth(2)
+        .with_seed(1);
+    let result = searcher.search(&mut game).unwrap();
+    assert_eq!(game.move_to_uci(&result.best_move.unwrap()), "d2d5");
+    assert!(result.score > 0);
+
+    // every pv move can be played.
+    assert!(!result.pv.is_empty());
+    for m in result.pv {
+        game.play(m.key()).unwrap();
+    }
+}
+
+#[test]
+pub fn limits_and_tree_reuse() {
+    let mut game = Game::from_fen("4k3/pp6/8/8/8/8/PP6/4K3 w - - 0 1").unwrap();
+
+    let start = Instant::now();
+    let mut searcher = MctsSearcher::new(SearchLimits::new().movetime(Duration::from_millis(100))).with_playout_depth(4);
+    let result = searcher.search(&mut game).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(result.best_move.is_some());
+
+    let mut searcher = MctsSearcher::new(SearchLimits::new()).with_playouts(100).with_playout_depth(4).with_seed(3);
+    let result = searcher.search(&mut game).unwrap();
+    assert_eq!(result.nodes, 100);
+    assert_eq!(searcher.root_visits(), 100);
+
+    // the reply the search expected keeps the tree under it.
+    game.play(result.pv[0].key()).unwrap();
+    game.play(result.pv[1].key()).unwrap();
+    let result = searcher.search(&mut game).unwrap();
+    assert_eq!(result.nodes, 100);
+    assert!(searcher.root_visits() > 100);
+
+    let mut fresh = MctsSearcher::new(SearchLimits::new()).with_playouts(100).with_playout_depth(4).with_tree_reuse(false);
+    fresh.search(&mut game).unwrap();
+    fresh.search(&mut game).unwrap();
+    assert_eq!(fresh.root_visits(), 100);
+}
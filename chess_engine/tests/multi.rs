@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use chess::board::Board;
+use chess::game::Game;
+use chess::piece::{defaults, Piece};
+use chess::piece_set::PieceSet;
+use chess::r#move::Coord;
+use chess::team::{StartInfo, Team};
+use chess_engine::multi::{Alliances, MultiMode, MultiSearcher};
+use chess_engine::search::SearchLimits;
+
+// four teams on an 8x8 board, one per side, moving in the order given. pieces are placed by
+// absolute coordinates.
+fn four_teams(pieces: [Vec<(Piece, (u32, u32))>; 4]) -> Game {
+    let mut board = Board::new(8, 8);
+    let sides = [
+        ("Red", StartInfo::Bottom { offset: 0 }),
+        ("Blue", StartInfo::Left { offset: 0 }),
+        ("Yellow", StartInfo::Top { offset: 0 }),
+        ("Green", StartInfo::Right { offset: 0 }),
+    ];
+
+    for ((name, start_info), pieces) in sides.into_iter().zip(pieces) {
+        let mut set = PieceSet::new(Arc::new(Team::new(name.to_string(), start_info)), Vec::new());
+        for (piece, (x, y)) in pieces {
+            let rel = board.absolute_coord_to_rel(Coord::new(x, y), start_info);
+            set.add_piece(piece, rel).unwrap();
+        }
+        board.add_piece_set(set).unwrap();
+    }
+
+    Game::from_board(board)
+}
+
+#[test]
+pub fn hanging_pieces() {
+    // blue's rook on d7 hangs to red's rook on d4.
+    for mode in [MultiMode::MaxN, MultiMode::Paranoid, MultiMode::BestReply] {
+        let mut game = four_teams([
+            vec![(defaults::king(), (0, 0)), (defaults::rook(), (3, 3))],
+            vec![(defaults::king(), (7, 0)), (defaults::rook(), (3, 6))],
+            vec![(defaults::king(), (0, 7))],
+            vec![(defaults::king(), (7, 7))],
+        ]);
+        let hash = game.hash();
+
+        let alliances = Alliances::free_for_all(&game);
+        let mut searcher = MultiSearcher::new(mode, alliances, SearchLimits::new().depth(2));
+        let result = searcher.search(&mut game).unwrap();
+        let best = result.best_move.unwrap();
+        assert_eq!((best.from(), best.to()), (Coord::new(3, 3), Coord::new(3, 6)), "{:?}", mode);
+        assert_eq!(result.scores.len(), 4);
+        assert!(result.scores[0] > 0, "{:?} {:?}", mode, result.scores);
+        assert_eq!(result.depth, 2);
+
+        // the search leaves the game as it found it.
+        assert_eq!(game.hash(), hash);
+        assert_eq!(game.current_move(), 0);
+    }
+}
+
+#[test]
+pub fn alliances() {
+    // yellow's rook on d7 hangs to red's rook, but they play together.
+    let pieces = || {
+        [
+            vec![(defaults::king(), (0, 0)), (defaults::rook(), (3, 3))],
+            vec![(defaults::king(), (7, 0))],
+            vec![(defaults::king(), (0, 7)), (defaults::rook(), (3, 6))],
+            vec![(defaults::king(), (7, 7))],
+        ]
+    };
+    let kills_d7 = |game: &mut Game, alliances: Alliances, mode: MultiMode| {
+        let mut searcher = MultiSearcher::new(mode, alliances, SearchLimits::new().depth(2));
+        let best = searcher.search(game).unwrap().best_move.unwrap();
+        best.to() == Coord::new(3, 6)
+    };
+
+    for mode in [MultiMode::MaxN, MultiMode::Paranoid, MultiMode::BestReply] {
+        let mut game = four_teams(pieces());
+        let free_for_all = Alliances::free_for_all(&game);
+        assert!(kills_d7(&mut game, free_for_all, mode), "{:?}", mode);
+
+        let mut game = four_teams(pieces());
+        let teams = Alliances::new(&game, &[&["Red", "Yellow"], &["Blue", "Green"]]).unwrap();
+        assert!(teams.allied(0, 2) && !teams.allied(0, 1));
+        assert!(!kills_d7(&mut game, teams, mode), "{:?}", mode);
+    }
+
+    let game = four_teams(pieces());
+    assert!(Alliances::new(&game, &[&["Red", "Purple"]]).is_err());
+    // allies share their score, and everyone else's counts against it.
+    let teams = Alliances::new(&game, &[&["Red", "Yellow"]]).unwrap();
+    assert_eq!(teams.utilities(&[100, 0, 300, 100]), vec![150, -166, 150, -33]);
+}
+
+#[test]
+pub fn three_teams() {
+    let mut board = Board::new(8, 8);
+    let mut add = |name: &str, start_info: StartInfo, pieces: Vec<(Piece, (u32, u32))>| {
+        let mut set = PieceSet::new(Arc::new(Team::new(name.to_string(), start_info)), Vec::new());
+        for (piece, (x, y)) in pieces {
+            let rel = board.absolute_coord_to_rel(Coord::new(x, y), start_info);
+            set.add_piece(piece, rel).unwrap();
+        }
+        board.add_piece_set(set).unwrap();
+    };
+    // red can take blue's rook or green's queen, which is worth more.
+    add("Red", StartInfo::Bottom { offset: 0 }, vec![(defaults::king(), (0, 0)), (defaults::rook(), (3, 3))]);
+    add("Blue", StartInfo::Left { offset: 0 }, vec![(defaults::king(), (7, 0)), (defaults::rook(), (3, 6))]);
+    add("Green", StartInfo::Top { offset: 0 }, vec![(defaults::king(), (0, 7)), (defaults::queen(), (6, 3))]);
+    let mut game = Game::from_board(board);
+
+    for mode in [MultiMode::MaxN, MultiMode::Paranoid, MultiMode::BestReply] {
+        let alliances = Alliances::free_for_all(&game);
+        let mut searcher = MultiSearcher::new(mode, alliances, SearchLimits::new().depth(3));
+        let result = searcher.search(&mut game).unwrap();
+        assert_eq!(result.best_move.unwrap().to(), Coord::new(6, 3), "{:?}", mode);
+        assert_eq!(result.scores.len(), 3);
+    }
+}
@@ -0,0 +1,254 @@
+use std::sync::Arc;
+
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+use chess_engine::datagen::{parse_samples, DataGen, Sample};
+use chess_engine::eval::Evaluator;
+use chess_engine::nnue::{Accumulators, Network, Trainer};
+use chess_engine::search::SearchLimits;
+use chess_engine::variant::builtin_variants;
+
+fn random_network(hidden: usize) -> Network {
+    Trainer::new(&builtin_variants()[0], hidden).unwrap().with_seed(42).network()
+}
+
+#[test]
+pub fn file_format() {
+    let network = random_network(8);
+    assert_eq!(network.inputs(), 2 * 6 * 64);
+    assert_eq!(network.symbols().len(), 6);
+
+    let bytes = network.to_bytes();
+    let read = Network::from_bytes(&bytes).unwrap();
+    assert_eq!(read.to_bytes(), bytes);
+    let game = Game::from_fen(STARTING_FEN).unwrap();
+    assert_eq!(read.evaluate(&game), network.evaluate(&game));
+
+    assert!(Network::from_bytes(b"nope").is_err());
+    assert!(Network::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Network::from_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+
+    // sizes far past the end of the file.
+    let mut huge = b"CRNN".to_vec();
+    for value in [1, u32::MAX, u32::MAX, 2, u32::MAX, 1, 'K' as u32] {
+        huge.extend_from_slice(&value.to_le_bytes());
+    }
+    assert!(Network::from_bytes(&huge).is_err());
+    huge[24..28].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(Network::from_bytes(&huge).is_err());
+}
+
+// kings only, every tile weighted the same, so the sums don't depend on where they stand.
+// 11 hidden values are a full run of 8 and 3 left over.
+#[test]
+pub fn sums() {
+    let hidden = 11;
+    let own: Vec<i16> = (0..hidden).map(|i| 37 * i as i16).collect();
+    let other: Vec<i16> = (0..hidden).map(|i| 3 * i as i16 - 20).collect();
+    let bias: Vec<i16> = (0..hidden).map(|i| 100 * i as i16 - 300).collect();
+    let outputs: Vec<i16> = (0..hidden * 2).map(|i| 5 * i as i16 - 40).collect();
+    let output_bias: i32 = -1234;
+
+    let mut bytes = b"CRNN".to_vec();
+    for value in [1, 8, 8, 2, hidden as u32, 1, 'K' as u32] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    let rows = std::iter::repeat_n(&own, 64).chain(std::iter::repeat_n(&other, 64));
+    for weight in rows.flatten().chain(&bias).chain(&outputs) {
+        bytes.extend_from_slice(&weight.to_le_bytes());
+    }
+    bytes.extend_from_slice(&output_bias.to_le_bytes());
+    let network = Network::from_bytes(&bytes).unwrap();
+
+    let accumulator: Vec<i32> = (0..hidden).map(|i| (bias[i] + own[i] + other[i]) as i32).collect();
+    let sum: i32 = (0..hidden * 2)
+        .map(|i| accumulator[i % hidden].clamp(0, 255) * outputs[i] as i32)
+        .sum::<i32>()
+        + output_bias;
+    let expected = (sum as i64 * 400 / (255 * 64)) as i32;
+
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(network.evaluate(&game), expected);
+    let mut accumulators = Accumulators::new(Arc::new(network));
+    assert_eq!(accumulators.evaluate(&game), expected);
+}
+
+#[test]
+pub fn incremental_updates() {
+    let network = Arc::new(random_network(16));
+    let mut accumulators = Accumulators::new(network.clone());
+    // en passant, castling both ways, a promotion with a capture and a capture.
+    let mut game = Game::from_fen("r3k2r/1P3ppp/8/3pP3/8/8/5PPP/R3K2R w KQkq d6 0 1").unwrap();
+    let moves = ["e5d6", "e8g8", "b7a8q", "f8a8", "e1c1", "a8a2", "d6d7", "a2a1"];
+
+    let mut scores = vec![accumulators.evaluate(&game)];
+    assert_eq!(scores[0], network.evaluate(&game));
+    for uci in moves {
+        let m = game.parse_uci(uci).unwrap();
+        game.execute_move(&m).unwrap();
+        let score = accumulators.evaluate(&game);
+        assert_eq!(score, network.evaluate(&game), "{}", uci);
+        scores.push(score);
+    }
+
+    // going back finds the same scores, and so does a passed turn.
+    for expected in scores.iter().rev().skip(1) {
+        game.undo().unwrap();
+        assert_eq!(accumulators.evaluate(&game), *expected);
+    }
+    game.pass_turn();
+    assert_eq!(accumulators.evaluate(&game), network.evaluate(&game));
+    game.undo().unwrap();
+
+    // another line from the same position.
+    for uci in ["a1d1", "h7h6"] {
+        let m = game.parse_uci(uci).unwrap();
+        game.execute_move(&m).unwrap();
+        assert_eq!(accumulators.evaluate(&game), network.evaluate(&game));
+    }
+}
+
+#[test]
+pub fn make_and_unmake() {
+    let network = Arc::new(random_network(16));
+    let mut accumulators = Accumulators::new(network.clone());
+    let mut game = Game::from_fen("r3k2r/1P3ppp/8/3pP3/8/8/5PPP/R3K2R w KQkq d6 0 1").unwrap();
+    let moves = ["e5d6", "e8g8", "b7a8q", "f8a8", "e1c1", "a8a2", "d6d7", "a2a1"];
+
+    // only evaluated every other move, the ones in between are worked out on the way.
+    let mut scores = vec![accumulators.evaluate(&game)];
+    for (idx, uci) in moves.into_iter().enumerate() {
+        let m = game.parse_uci(uci).unwrap();
+        accumulators.make(&mut game, &m).unwrap();
+        if idx % 2 == 1 {
+            let score = accumulators.evaluate(&game);
+            assert_eq!(score, network.evaluate(&game), "{}", uci);
+            scores.push(score);
+        }
+    }
+    accumulators.pass(&mut game);
+    assert_eq!(accumulators.evaluate(&game), network.evaluate(&game));
+    accumulators.unmake(&mut game).unwrap();
+
+    for expected in scores.iter().rev().skip(1) {
+        accumulators.unmake(&mut game).unwrap();
+        accumulators.unmake(&mut game).unwrap();
+        assert_eq!(accumulators.evaluate(&game), *expected);
+    }
+
+    // a move made without it starts the accumulators over.
+    let m = game.parse_uci("e5d6").unwrap();
+    game.execute_move(&m).unwrap();
+    let m = game.parse_uci("e8g8").unwrap();
+    accumulators.make(&mut game, &m).unwrap();
+    assert_eq!(accumulators.evaluate(&game), network.evaluate(&game));
+}
+
+#[test]
+pub fn only_games_that_fit() {
+    let network = Arc::new(random_network(8));
+    let chess = Game::from_fen(STARTING_FEN).unwrap();
+    let mut evaluator = Evaluator::new().with_network(Some(network.clone()));
+    assert_eq!(evaluator.evaluate(&chess), network.evaluate(&chess));
+
+    let capablanca = builtin_variants()
+        .into_iter()
+        .find(|v| v.name() == "capablanca")
+        .unwrap()
+        .start()
+        .unwrap();
+    assert!(!network.fits(&capablanca));
+    assert_eq!(evaluator.evaluate(&capablanca), Evaluator::new().evaluate(&capablanca));
+}
+
+#[test]
+pub fn self_play_data() {
+    let chess = builtin_variants()[0].clone();
+    let datagen = DataGen::new(chess.clone())
+        .with_games(2)
+        .with_limits(SearchLimits::new().depth(1))
+        .with_random_plies(4)
+        .with_max_plies(12);
+    let mut games = Vec::new();
+    let total = datagen.run(|round, samples| {
+        games.push((round, samples.to_vec()));
+        true
+    });
+    assert_eq!(total.unwrap(), games.iter().map(|(_, samples)| samples.len()).sum::<usize>());
+    assert_eq!(games.iter().map(|(round, _)| *round).collect::<Vec<_>>(), [0, 1]);
+
+    let samples: Vec<Sample> = games.into_iter().flat_map(|(_, samples)| samples).collect();
+    assert!(!samples.is_empty());
+    let text: String = samples.iter().map(|sample| sample.to_line() + "\n").collect();
+    assert_eq!(parse_samples(&text).unwrap(), samples);
+    for sample in &samples {
+        assert!(chess.game(&sample.fen).is_ok());
+        assert!([0.0, 0.5, 1.0].contains(&sample.result));
+    }
+
+    // stopping early.
+    let mut rounds = 0;
+    datagen
+        .run(|_, _| {
+            rounds += 1;
+            false
+        })
+        .unwrap();
+    assert_eq!(rounds, 1);
+
+    assert!(Sample::parse("8/8/8/8/8/8/8/8 w - - 0 1 | 12").is_err());
+    assert!(Sample::parse("8/8/8/8/8/8/8/8 w - - 0 1 | 12 | 2").is_err());
+    assert!(parse_samples("\n8/8/8/8/8/8/8/8 w - - 0 1 | x | 1\n")
+        .unwrap_err()
+        .to_string()
+        .contains("Line 2"));
+}
+
+#[test]
+pub fn training() {
+    let chess = builtin_variants()[0].clone();
+    // the side with the queen wins.
+    let fens = [
+        "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR b KQkq - 0 1",
+        "4k3/8/8/8/8/8/8/3QK3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/3QK3 b - - 0 1",
+        "3qk3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "3qk3/8/8/8/8/8/8/4K3 b - - 0 1",
+    ];
+    let mut evaluator = Evaluator::new();
+    let mut trainer = Trainer::new(&chess, 8).unwrap();
+    for fen in fens {
+        let score = evaluator.evaluate(&chess.game(fen).unwrap());
+        let result = if score > 0 { 1.0 } else { 0.0 };
+        trainer
+            .add_sample(&Sample {
+                fen: fen.to_string(),
+                score,
+                result,
+            })
+            .unwrap();
+    }
+    assert_eq!(trainer.len(), fens.len());
+    assert!(trainer
+        .add_sample(&Sample {
+            fen: "bad".to_string(),
+            score: 0,
+            result: 0.5
+        })
+        .is_err());
+
+    let before = trainer.error();
+    let after = trainer.train(300);
+    assert!(after < before / 2.0, "{} {}", before, after);
+
+    // the rounded network agrees on who's winning.
+    let network = trainer.network();
+    for fen in fens {
+        let game = chess.game(fen).unwrap();
+        let expected = evaluator.evaluate(&game);
+        assert_eq!(network.evaluate(&game) > 0, expected > 0, "{}", fen);
+    }
+}
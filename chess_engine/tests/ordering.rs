@@ -0,0 +1,64 @@
+use chess::game::Game;
+use chess_engine::ordering::MoveOrdering;
+use chess_engine::search::{SearchLimits, Searcher};
+
+fn sorted(ordering: &mut MoveOrdering, game: &Game) -> Vec<String> {
+    let mut moves = game.legal_moves().unwrap();
+    ordering.sort(game, &mut moves, None, 0).unwrap();
+    moves.iter().map(|m| game.move_to_uci(m)).collect()
+}
+
+#[test]
+pub fn kills_killers_and_history() {
+    // the pawn can kill the queen, the queen can kill a pawn.
+    let game = Game::from_fen("4k3/8/8/3q4/4P3/8/8/3QK3 w - - 0 1").unwrap();
+    let mut ordering = MoveOrdering::new();
+    ordering.new_search(&game);
+    let order = sorted(&mut ordering, &game);
+    assert_eq!(order[..2], ["e4d5", "d1d5"]);
+
+    // a quiet move that cut off comes right after the kills.
+    let quiet = game.parse_uci("e1f2").unwrap();
+    ordering.cut_off(&game, &quiet, 0, 4).unwrap();
+    assert_eq!(sorted(&mut ordering, &game)[2], "e1f2");
+
+    // on another ply it's not a killer, but the king's history still puts it first among the quiet moves.
+    let mut moves = game.legal_moves().unwrap();
+    ordering.sort(&game, &mut moves, None, 1).unwrap();
+    assert_eq!(game.move_to_uci(&moves[2]), "e1f2");
+
+    // killers are forgotten between searches.
+    ordering.new_search(&game);
+    let mut moves = game.legal_moves().unwrap();
+    let tt_move = game.parse_uci("d1a4").unwrap().key();
+    ordering.sort(&game, &mut moves, Some(tt_move), 0).unwrap();
+    assert_eq!(moves[0].key(), tt_move);
+}
+
+#[test]
+pub fn without_new_search() {
+    // the tables fit themselves to the board, with or without new_search.
+    let game = Game::from_fen("4k3/8/8/3q4/4P3/8/8/3QK3 w - - 0 1").unwrap();
+    let mut ordering = MoveOrdering::new();
+    let quiet = game.parse_uci("e1f2").unwrap();
+    ordering.cut_off(&game, &quiet, 0, 4).unwrap();
+    assert_eq!(sorted(&mut ordering, &game)[..3], ["e4d5", "d1d5", "e1f2"]);
+
+    let mut fresh = MoveOrdering::new();
+    assert_eq!(sorted(&mut fresh, &game)[..2], ["e4d5", "d1d5"]);
+
+    // a bigger board starts over.
+    let big = Game::original().unwrap();
+    assert_eq!(sorted(&mut ordering, &big).len(), big.legal_moves().unwrap().len());
+}
+
+#[test]
+pub fn fewer_nodes() {
+    let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3";
+    let nodes = |order_moves: bool| {
+        let mut game = Game::from_fen(fen).unwrap();
+        let mut searcher = Searcher::new(SearchLimits::new().depth(3)).with_move_ordering(order_moves);
+        searcher.search(&mut game).unwrap().nodes
+    };
+    assert!(nodes(true) * 2 < nodes(false));
+}
@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+use std::sync::{Arc, Mutex};
+
+use chess_engine::search::{evaluate_moves, search, Iteration, SearchLimits, Searcher, MATE};
+
+#[test]
+pub fn finds_mates() {
+    let mut game = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 0 1").unwrap();
+    let result = search(&mut game, SearchLimits::new().depth(4)).unwrap();
+    assert_eq!(result.score, MATE - 1);
+    assert_eq!(game.move_to_uci(&result.best_move.unwrap()), "f3f7");
+    // quiescence search sees black has no way out of check, nothing deeper is needed after that.
+    assert_eq!(result.depth, 1);
+
+    let mut game = Game::from_fen("7k/8/5K2/8/8/8/8/1R6 w - - 0 1").unwrap();
+    let result = search(&mut game, SearchLimits::new().depth(4)).unwrap();
+    assert_eq!(result.score, MATE - 3);
+    let pv: Vec<String> = result.pv.iter().map(|m| game.move_to_uci(m)).collect();
+    assert_eq!(pv, ["f6f7", "h8h7", "b1h1"]);
+}
+
+#[test]
+pub fn pv_is_playable() {
+    let mut game = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3").unwrap();
+    let result = search(&mut game, SearchLimits::new().depth(3)).unwrap();
+    assert_eq!(result.depth, 3);
+    assert_eq!(result.pv.len(), 3);
+    assert_eq!(result.pv[0].key(), result.best_move.unwrap().key());
+
+    // the search leaves the game as it was.
+    assert_eq!(game.history().len(), 0);
+    for m in result.pv {
+        game.play(m.key()).unwrap();
+    }
+}
+
+#[test]
+pub fn limits() {
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    let result = search(&mut game, SearchLimits::new().nodes(500)).unwrap();
+    assert!(result.nodes <= 500);
+    assert!(result.best_move.is_some());
+
+    let start = Instant::now();
+    let result = search(&mut game, SearchLimits::new().movetime(Duration::from_millis(100))).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(result.depth >= 1);
+    assert!(result.best_move.is_some());
+
+    let mut game = Game::from_fen("7k/5K2/6Q1/8/8/8/8/8 b - - 0 1").unwrap();
+    let result = search(&mut game, SearchLimits::new().depth(3)).unwrap();
+    assert!(result.best_move.is_none());
+    assert_eq!(result.score, 0);
+}
+
+#[test]
+pub fn multi_pv() {
+    let mut game = Game::from_fen("4k3/8/8/3r4/8/8/8/3QK3 w - - 0 1").unwrap();
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let mut searcher = Searcher::new(SearchLimits::new().depth(3)).with_multi_pv(3);
+    let lines = reported.clone();
+    searcher.set_on_iteration(Some(Box::new(move |_: &Game, iteration: &Iteration| {
+        lines.lock().unwrap().push((iteration.depth, iteration.multipv));
+    })));
+    let result = searcher.search(&mut game).unwrap();
+
+    // every line as soon as it's found, depth by depth.
+    let expected: Vec<(u32, usize)> = (1..=3).flat_map(|depth| (1..=3).map(move |line| (depth, line))).collect();
+    assert_eq!(*reported.lock().unwrap(), expected);
+
+    assert_eq!(result.lines.len(), 3);
+    assert_eq!(game.move_to_uci(&result.best_move.unwrap()), "d1d5");
+    assert_eq!(result.lines[0].pv[0].key(), result.pv[0].key());
+    assert_eq!(result.lines[0].score, result.score);
+    for pair in result.lines.windows(2) {
+        assert!(pair[0].score >= pair[1].score);
+        assert_ne!(pair[0].pv[0].key(), pair[1].pv[0].key());
+    }
+
+    // no more lines than moves.
+    let mut game = Game::from_fen("7k/8/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    let result = Searcher::new(SearchLimits::new().depth(2)).with_multi_pv(10).search(&mut game).unwrap();
+    assert_eq!(result.lines.len(), 3);
+}
+
+#[test]
+pub fn move_evaluations() {
+    let mut game = Game::from_fen("4k3/8/8/3r4/8/8/8/3QK3 w - - 0 1").unwrap();
+    let mut streamed = 0;
+    let lines = Searcher::new(SearchLimits::new())
+        .evaluate_moves(&mut game, 2, |_, _| streamed += 1)
+        .unwrap();
+    assert_eq!(lines.len(), game.legal_moves().unwrap().len());
+    assert_eq!(streamed, lines.len());
+    assert_eq!(game.move_to_uci(&lines[0].pv[0]), "d1d5");
+    assert!(lines.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    // losing the queen for nothing is the worst there is.
+    assert!(lines.last().unwrap().score < -500);
+    assert_eq!(game.history().len(), 0);
+
+    let mut game = Game::from_fen("7k/5K2/6Q1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert!(evaluate_moves(&mut game, 2).unwrap().is_empty());
+}
@@ -0,0 +1,81 @@
+use chess::fen::standard_catalog;
+use chess::game::Game;
+use chess::piece::PieceBuilder;
+use chess::piece_rules::{Direction, Distance, MoveRules, MoveVec};
+use chess_engine::search::{search, SearchLimits};
+use chess_engine::see::see;
+
+fn see_of(game: &mut Game, uci: &str) -> i32 {
+    let m = game.parse_uci(uci).unwrap();
+    see(game, &m).unwrap()
+}
+
+fn lines(distance: Distance) -> Vec<MoveVec> {
+    [Direction::Up, Direction::Down, Direction::Left, Direction::Right]
+        .into_iter()
+        .map(|direction| MoveVec::new(distance, direction))
+        .collect()
+}
+
+#[test]
+pub fn exchanges() {
+    // the pawn is defended by a pawn, and the rook wins a free knight.
+    let mut game = Game::from_fen("n3k3/8/4p3/3p4/8/8/8/R2QK3 w - - 0 1").unwrap();
+    assert_eq!(see_of(&mut game, "d1d5"), 100 - 900);
+    assert_eq!(see_of(&mut game, "a1a8"), 300);
+    assert_eq!(game.history().len(), 0);
+
+    // two attackers against one defender.
+    let mut game = Game::from_fen("4k3/8/4p3/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+    assert_eq!(see_of(&mut game, "d2d5"), 100 - 500 + 100);
+}
+
+#[test]
+pub fn cannons_and_piercing() {
+    let mut catalog = standard_catalog();
+    // moves like a rook, kills by hopping over exactly one piece.
+    catalog.push(
+        PieceBuilder::new()
+            .name("Cannon".to_string())
+            .symbol('C')
+            .points(5)
+            .move_rules(vec![MoveRules::blunt(lines(Distance::infinite()))])
+            .kill_rules(vec![MoveRules::line_jump(lines(Distance::infinite()))])
+            .build()
+            .unwrap(),
+    );
+    // kills everything on its way up to 10 points.
+    catalog.push(
+        PieceBuilder::new()
+            .name("Lancer".to_string())
+            .symbol('L')
+            .points(4)
+            .move_rules(vec![MoveRules::pierce(vec![MoveVec::new(Distance::infinite(), Direction::Up)], 10)])
+            .use_moves_for_kills(true)
+            .build()
+            .unwrap(),
+    );
+
+    // the cannon only defends the knight through its pawn screen.
+    let mut game = Game::from_fen_with("c3k3/p7/8/n7/8/8/8/R3K3 w - - 0 1", &catalog).unwrap();
+    assert_eq!(see_of(&mut game, "a1a5"), 300 - 500);
+    let mut game = Game::from_fen_with("c3k3/8/8/n7/8/8/8/R3K3 w - - 0 1", &catalog).unwrap();
+    assert_eq!(see_of(&mut game, "a1a5"), 300);
+
+    // the rook is the cannon's screen, killing the knight takes the cannon's defence away with it.
+    let mut game = Game::from_fen_with("4k3/c7/R7/n7/8/8/8/4K3 w - - 0 1", &catalog).unwrap();
+    assert_eq!(see_of(&mut game, "a6a5"), 300);
+
+    // the lancer kills both knights, and the rook kills it back.
+    let mut game = Game::from_fen_with("r3k3/8/8/n7/8/n7/8/L3K3 w - - 0 1", &catalog).unwrap();
+    assert_eq!(see_of(&mut game, "a1a6"), 600 - 400);
+}
+
+#[test]
+pub fn quiescence() {
+    // taking the pawn with the queen looks good at depth 1, until the pawn on e6 kills back.
+    let mut game = Game::from_fen("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+    let result = search(&mut game, SearchLimits::new().depth(1)).unwrap();
+    assert_ne!(game.move_to_uci(&result.best_move.unwrap()), "d1d5");
+    assert!(result.score > 600);
+}
@@ -0,0 +1,168 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use chess::fen::{standard_catalog, STARTING_FEN};
+use chess::pgn::parse_pgn;
+use chess_engine::selfplay::{
+    parse_openings, run_match, Adjudication, EngineConfig, MatchConfig, MatchLimit, MatchStats, Opening, Sprt,
+    SprtVerdict,
+};
+use chess_engine::variant::builtin_variants;
+
+const OPENINGS: &str = "
+rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - bm e5; id \"e4\";
+rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq - 0 1
+";
+
+#[test]
+pub fn openings() {
+    let chess = builtin_variants().remove(0);
+    let openings = parse_openings(OPENINGS, &chess).unwrap();
+    assert_eq!(openings.len(), 2);
+    assert_eq!(openings[0].fen, "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    assert!(openings.iter().all(|opening| opening.moves.is_empty()));
+
+    let openings = parse_openings("[Event \"x\"]\n\n1. e4 c5 2. Nf3 *\n\n1. d4 d5 *", &chess).unwrap();
+    assert_eq!(
+        openings,
+        [
+            Opening {
+                fen: STARTING_FEN.to_string(),
+                moves: vec!["e2e4".to_string(), "c7c5".to_string(), "g1f3".to_string()],
+            },
+            Opening {
+                fen: STARTING_FEN.to_string(),
+                moves: vec!["d2d4".to_string(), "d7d5".to_string()],
+            },
+        ]
+    );
+
+    assert!(parse_openings("not a position", &chess).is_err());
+    assert!(parse_openings("1. e5 *", &chess).is_err());
+}
+
+#[test]
+pub fn elo_and_sprt() {
+    let even = MatchStats {
+        wins: 30,
+        losses: 30,
+        draws: 40,
+    };
+    let (elo, margin) = even.elo();
+    assert!(elo.abs() < 1e-9);
+    assert!(margin > 40.0 && margin < 60.0, "{}", margin);
+
+    // 75% is about 191 elo.
+    let better = MatchStats {
+        wins: 100,
+        losses: 0,
+        draws: 100,
+    };
+    assert!((better.elo().0 - 190.85).abs() < 0.1);
+    assert!(better.elo().1 < even.elo().1);
+
+    let sprt = Sprt::new(0.0, 10.0);
+    let (lower, upper) = sprt.bounds();
+    assert!((lower + 2.944).abs() < 0.001 && (upper - 2.944).abs() < 0.001);
+    assert_eq!(sprt.llr(&MatchStats::default()), 0.0);
+    assert_eq!(sprt.verdict(&better), Some(SprtVerdict::H1));
+    let worse = MatchStats {
+        wins: 0,
+        losses: 100,
+        draws: 100,
+    };
+    assert_eq!(sprt.verdict(&worse), Some(SprtVerdict::H0));
+    assert_eq!(sprt.verdict(&even), None);
+    assert!(sprt.llr(&even) < 0.0);
+}
+
+#[test]
+pub fn matches() {
+    let uci = env!("CARGO_BIN_EXE_uci");
+    let first = EngineConfig::new(uci).with_name("deeper").with_option("Hash", "1");
+    let second = EngineConfig::new(uci).with_name("shallow").with_option("Hash", "1");
+    let openings = parse_openings(OPENINGS, &builtin_variants()[0]).unwrap();
+    let adjudication = Adjudication {
+        max_moves: 15,
+        ..Adjudication::default()
+    };
+    let config = MatchConfig::new(first, second)
+        .with_openings(openings.clone())
+        .with_games(4)
+        .with_concurrency(2)
+        .with_limit(MatchLimit::Depth(1))
+        .with_adjudication(Some(adjudication));
+
+    let mut games = Vec::new();
+    let stats = run_match(&config, |game, stats| {
+        games.push(game.clone());
+        assert_eq!(stats.games(), games.len());
+        true
+    })
+    .unwrap();
+    assert_eq!(stats.games(), 4);
+    games.sort_by_key(|game| game.round);
+
+    for (round, game) in games.iter().enumerate() {
+        assert_eq!(game.round, round);
+        assert_eq!(game.first_is_white, round % 2 == 0);
+        let white = if game.first_is_white { "deeper" } else { "shallow" };
+        assert_eq!(game.pgn.tag("White"), Some(white));
+        assert!(game.pgn.moves.len() <= 30);
+
+        // the written game reads back and replays from its opening.
+        let pgn = parse_pgn(&game.pgn.to_pgn()).unwrap().remove(0);
+        assert_eq!(pgn, game.pgn);
+        assert_eq!(pgn.start().unwrap().to_fen().unwrap(), openings[round / 2].fen);
+        pgn.replay_with(&standard_catalog()).unwrap();
+    }
+
+    // stopping early still finishes and counts what's being played.
+    let mut finished = 0;
+    let stats = run_match(&config.clone().with_concurrency(1), |_, _| {
+        finished += 1;
+        false
+    })
+    .unwrap();
+    assert_eq!(stats.games(), finished);
+    assert!(stats.games() < config.games());
+
+    let missing = MatchConfig::new(EngineConfig::new("/nonexistent/engine"), EngineConfig::new(uci));
+    assert!(run_match(&missing, |_, _| true).is_err());
+}
+
+#[test]
+pub fn silent_engine() {
+    let uci = env!("CARGO_BIN_EXE_uci");
+    // sets up like an engine, then never answers a go.
+    let silent = EngineConfig::new("sh")
+        .with_name("silent")
+        .with_arg("-c")
+        .with_arg("while read line; do case $line in uci) echo uciok;; isready) echo readyok;; esac; done");
+
+    // on a clock it loses on time, and gets started again for the next game.
+    let config = MatchConfig::new(EngineConfig::new(uci), silent.clone())
+        .with_games(2)
+        .with_limit(MatchLimit::Clock {
+            base: Duration::from_millis(100),
+            increment: Duration::ZERO,
+        });
+    let mut games = Vec::new();
+    let stats = run_match(&config, |game, _| {
+        games.push(game.clone());
+        true
+    })
+    .unwrap();
+    assert_eq!(stats.score(), 1.0);
+    for game in &games {
+        assert_eq!(game.reason, "Time forfeit");
+        assert_eq!(game.pgn.tag("Termination"), Some("time forfeit"));
+    }
+
+    // with a fixed move time it ends the match.
+    let started = Instant::now();
+    let config = config.with_limit(MatchLimit::MoveTime(Duration::from_millis(50)));
+    let e = run_match(&config, |_, _| true).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+
+use chess::fen::{standard_catalog, STARTING_FEN};
+use chess::game::Game;
+use chess_engine::eval::{EvalParams, Evaluator};
+use chess_engine::search::{evaluate_moves, SearchLimits};
+use chess_engine::skill::{Personality, Skill, MAX_ELO, MAX_LEVEL, MIN_ELO};
+
+#[test]
+pub fn levels() {
+    assert_eq!(Skill::new(25).level(), MAX_LEVEL);
+    assert!(Skill::default().is_full_strength());
+    assert_eq!(Skill::from_elo(0).level(), 0);
+    assert_eq!(Skill::from_elo(MAX_ELO + 500).level(), MAX_LEVEL);
+    assert_eq!(Skill::from_elo(1449).level(), 6);
+    assert_eq!(Skill::from_elo(1451).elo(), 1500);
+    assert_eq!(Skill::new(0).elo(), MIN_ELO);
+
+    // full strength leaves everything alone, the rest is capped.
+    let limits = SearchLimits::new().depth(10);
+    assert_eq!(Skill::default().limits(limits), limits);
+    assert_eq!(Skill::default().multi_pv(1), 1);
+    assert_eq!(Skill::default().noise(), 0);
+    let weakest = Skill::new(0).limits(limits);
+    assert_eq!(weakest.depth, Some(1));
+    assert!(weakest.nodes.is_some());
+    assert_eq!(Skill::new(12).limits(SearchLimits::new().depth(2)).depth, Some(2));
+    assert_eq!(Skill::new(0).multi_pv(1), 4);
+    assert!(Skill::new(0).noise() > Skill::new(10).noise());
+}
+
+#[test]
+pub fn picking() {
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    let lines = evaluate_moves(&mut game, 2).unwrap();
+    assert!(Skill::new(0).pick(&[], 1).is_none());
+
+    let key = |skill: Skill, random: u64| skill.pick(&lines, random).unwrap().pv[0].key();
+    let best = lines[0].pv[0].key();
+    assert!((0..50).all(|random| key(Skill::default(), random) == best));
+
+    // the weakest level plays other moves too, but only out of the best few.
+    let picked: HashSet<_> = (0..50).map(|random| key(Skill::new(0), random * 7919)).collect();
+    assert!(picked.len() > 1);
+    assert!(picked.iter().all(|key| lines[..4].iter().any(|line| line.pv[0].key() == *key)));
+}
+
+#[test]
+pub fn noise() {
+    let positions = [
+        STARTING_FEN,
+        "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+        "7k/8/5K2/8/8/8/8/1R6 w - - 0 1",
+    ];
+    let mut exact = Evaluator::new();
+    let mut noisy = Evaluator::new();
+    noisy.set_noise(50, 42);
+
+    let mut differ = 0;
+    for fen in positions {
+        let game = Game::from_fen(fen).unwrap();
+        let score = noisy.evaluate(&game);
+        assert_eq!(score, noisy.evaluate(&game));
+        assert!((score - exact.evaluate(&game)).abs() <= 50);
+        differ += (score != exact.evaluate(&game)) as usize;
+    }
+    assert!(differ > 0);
+}
+
+#[test]
+pub fn personalities() {
+    assert_eq!(Personality::from_name("Aggressive"), Some(Personality::Aggressive));
+    assert_eq!(Personality::from_name("reckless"), None);
+    for personality in Personality::ALL {
+        assert_eq!(Personality::from_name(personality.name()), Some(personality));
+    }
+
+    let catalog = standard_catalog();
+    let base = EvalParams::seeded(&catalog);
+    assert_eq!(Personality::Normal.params(&catalog, &base).unwrap(), base);
+
+    let queen = base.get('Q').unwrap();
+    let greedy = Personality::Materialistic.params(&catalog, &base).unwrap().get('Q').unwrap();
+    assert!(greedy.material > queen.material);
+    assert!(greedy.mobility < queen.mobility);
+    let aggressive = Personality::Aggressive.params(&catalog, &EvalParams::new()).unwrap().get('P').unwrap();
+    assert!(aggressive.advance > base.get('P').unwrap().advance);
+
+    // the evaluation follows, a queen up counts for more with a materialistic engine.
+    let game = Game::from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    let normal = Evaluator::new().evaluate(&game);
+    let greedy = Evaluator::new().with_params(Personality::Materialistic.params(&catalog, &base).unwrap()).evaluate(&game);
+    assert!(greedy > normal, "{} {}", greedy, normal);
+}
@@ -0,0 +1,46 @@
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chess::game::Game;
+use chess_engine::search::{search, SearchLimits, MATE};
+use chess_engine::smp::SmpSearcher;
+
+#[test]
+pub fn same_answers_as_one_thread() {
+    let mut game = Game::from_fen("7k/8/5K2/8/8/8/8/1R6 w - - 0 1").unwrap();
+    let mut searcher = SmpSearcher::new(SearchLimits::new().depth(4), 4);
+    let result = searcher.search(&mut game).unwrap();
+    assert_eq!(result.score, MATE - 3);
+    assert_eq!(result.pv.len(), 3);
+
+    let single = search(&mut game, SearchLimits::new().depth(4)).unwrap();
+    assert_eq!(result.score, single.score);
+
+    // the moves belong to the caller's game, not to a thread's copy.
+    assert_eq!(game.history().len(), 0);
+    for m in &result.pv {
+        game.execute_move(m).unwrap();
+    }
+    assert!(game.legal_moves().unwrap().is_empty());
+}
+
+#[test]
+pub fn stop_flag() {
+    let mut game = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3").unwrap();
+    let mut searcher = SmpSearcher::new(SearchLimits::new(), 2);
+    assert_eq!(searcher.threads(), 2);
+
+    let stop = searcher.stop_flag();
+    let stopper = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        stop.store(true, Ordering::Relaxed);
+    });
+    let start = Instant::now();
+    let result = searcher.search(&mut game).unwrap();
+    stopper.join().unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!(result.best_move.is_some());
+    game.play(result.best_move.unwrap().key()).unwrap();
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use chess::fen::standard_catalog;
+use chess::game::Game;
+use chess_engine::search::{SearchLimits, Searcher, MATE};
+use chess_engine::tablebase::{Outcome, Tablebases};
+
+#[test]
+pub fn king_and_queen() {
+    let mut tablebases = Tablebases::new();
+    tablebases.generate("KQvK", 4, 4, &standard_catalog()).unwrap();
+    let table = tablebases.get("QKvK", 4, 4).unwrap();
+    assert_eq!(table.len(), 16 * 16 * 16 * 2);
+    assert!(tablebases.get("KvK", 4, 4).is_some());
+
+    let probe = |fen: &str| tablebases.probe(&Game::from_fen(fen).unwrap());
+    // mated, stalemated, and a king that can take the queen.
+    assert_eq!(probe("k3/1Q2/2K1/4 b - - 0 1"), Some(Outcome::Loss(0)));
+    assert_eq!(probe("k3/2Q1/1K2/4 b - - 0 1"), Some(Outcome::Draw));
+    assert_eq!(probe("kQ2/4/4/3K b - - 0 1"), Some(Outcome::Draw));
+    assert_eq!(probe("k3/4/4/3K b - - 0 1"), Some(Outcome::Draw));
+    // not in the tables.
+    assert_eq!(probe("k3/4/4/2RK b - - 0 1"), None);
+
+    // the search agrees on the short mates.
+    for (fen, plies) in [("4/4/1K1k/1Q2 w - - 0 1", 1), ("K3/4/Q3/2k1 w - - 0 1", 3), ("k3/4/3Q/1K2 w - - 0 1", 5)] {
+        let game = Game::from_fen(fen).unwrap();
+        assert_eq!(tablebases.probe(&game), Some(Outcome::Win(plies)), "{}", fen);
+        let result = Searcher::new(SearchLimits::new().depth(plies)).search(&mut game.deep_clone().unwrap()).unwrap();
+        assert_eq!(result.score, MATE - plies as i32, "{}", fen);
+    }
+
+    // with the queen's side to move, every position is won within 7 plies.
+    let longest = (0..16 * 16 * 16)
+        .filter_map(|idx| {
+            let squares = [idx % 16, idx / 16 % 16, idx / 256].map(|square| square as u32);
+            table.outcome(&squares, 0)
+        })
+        .map(|outcome| match outcome {
+            Outcome::Win(plies) => plies,
+            _ => panic!("{:?}", outcome),
+        })
+        .max();
+    assert_eq!(longest, Some(7));
+}
+
+#[test]
+pub fn search_and_storage() {
+    let mut tablebases = Tablebases::new();
+    tablebases.generate("KRvK", 4, 4, &standard_catalog()).unwrap();
+    let tablebases = Tablebases::from_bytes(&tablebases.to_bytes()).unwrap();
+    assert!(tablebases.get("KRvK", 4, 4).is_some());
+    assert!(Tablebases::from_bytes(b"nonsense").is_err());
+
+    // one ply of search is enough to know the mate, and to play towards it.
+    let mut game = Game::from_fen("3k/4/4/R2K w - - 0 1").unwrap();
+    let Some(Outcome::Win(plies)) = tablebases.probe(&game) else {
+        panic!("expected a win");
+    };
+    let mut searcher = Searcher::new(SearchLimits::new().depth(1)).with_tablebases(Arc::new(tablebases));
+    let result = searcher.search(&mut game).unwrap();
+    assert_eq!(result.score, MATE - plies as i32);
+
+    assert!(Tablebases::new().generate("KPvK", 4, 4, &standard_catalog()).is_err());
+    assert!(Tablebases::new().generate("KXvK", 4, 4, &standard_catalog()).is_err());
+    assert!(Tablebases::new().generate("KQ", 4, 4, &standard_catalog()).is_err());
+}
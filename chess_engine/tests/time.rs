@@ -0,0 +1,96 @@
+use std::time::{Duration, Instant};
+
+use chess::game::Game;
+use chess::r#move::{Coord, MoveKey};
+use chess_engine::search::{SearchLimits, Searcher};
+use chess_engine::time::{TimeControl, TimeManager};
+
+fn key(from: (u32, u32), to: (u32, u32)) -> MoveKey {
+    MoveKey {
+        from: Coord::new(from.0, from.1),
+        to: Coord::new(to.0, to.1),
+        promotion: None,
+    }
+}
+
+#[test]
+pub fn budgets() {
+    let clock = |remaining: u64, increment: u64, moves_to_go: Option<u32>| {
+        TimeManager::new(TimeControl::Clock {
+            remaining: Duration::from_millis(remaining),
+            increment: Duration::from_millis(increment),
+            moves_to_go,
+        })
+    };
+
+    // sudden death splits the time over the moves still to come.
+    let time = clock(60_000, 0, None);
+    let soft = time.soft_limit().unwrap();
+    let hard = time.hard_limit().unwrap();
+    assert!(soft > Duration::from_millis(1500) && soft < Duration::from_millis(2500), "{:?}", soft);
+    assert!(hard > soft && hard < Duration::from_secs(60));
+
+    // the increment and fewer moves to go both give more.
+    assert!(clock(60_000, 1000, None).soft_limit().unwrap() > soft);
+    assert!(clock(60_000, 0, Some(10)).soft_limit().unwrap() > soft);
+
+    // even the last move before the time control keeps some time back.
+    let last = clock(10_000, 0, Some(1));
+    assert!(last.hard_limit().unwrap() < Duration::from_secs(10));
+    let flagging = clock(5, 0, None);
+    assert!(flagging.hard_limit().unwrap() <= Duration::from_millis(5));
+
+    let movetime = TimeManager::new(TimeControl::MoveTime(Duration::from_millis(300)));
+    assert_eq!(movetime.soft_limit(), Some(Duration::from_millis(300)));
+    assert_eq!(movetime.hard_limit(), Some(Duration::from_millis(300)));
+    let infinite = TimeManager::new(TimeControl::Infinite);
+    assert_eq!(infinite.hard_limit(), None);
+    assert!(infinite.keep_going() && !infinite.out_of_time());
+}
+
+#[test]
+pub fn stretches_when_unsettled() {
+    let mut time = TimeManager::new(TimeControl::Clock {
+        remaining: Duration::from_secs(60),
+        increment: Duration::ZERO,
+        moves_to_go: None,
+    });
+    let base = time.soft_limit().unwrap();
+
+    time.iteration(key((4, 1), (4, 3)), 20);
+    time.iteration(key((4, 1), (4, 3)), 25);
+    assert_eq!(time.soft_limit().unwrap(), base);
+
+    // the best move changed.
+    time.iteration(key((3, 1), (3, 3)), 25);
+    let unstable = time.soft_limit().unwrap();
+    assert!(unstable > base);
+
+    // the score dropped too.
+    time.iteration(key((6, 0), (5, 2)), -40);
+    assert!(time.soft_limit().unwrap() > unstable);
+    assert!(time.soft_limit().unwrap() <= time.hard_limit().unwrap());
+
+    // and it settles back down.
+    for _ in 0..10 {
+        time.iteration(key((6, 0), (5, 2)), -40);
+    }
+    assert!(time.soft_limit().unwrap() < unstable);
+}
+
+#[test]
+pub fn search_on_a_clock() {
+    let mut game = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3").unwrap();
+    let mut searcher = Searcher::new(SearchLimits::new());
+
+    let start = Instant::now();
+    searcher.set_time_manager(Some(TimeManager::new(TimeControl::Clock {
+        remaining: Duration::from_millis(3000),
+        increment: Duration::ZERO,
+        moves_to_go: Some(10),
+    })));
+    let result = searcher.search(&mut game).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(3));
+    assert!(result.best_move.is_some());
+    assert!(result.depth >= 1);
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use std::thread;
+
+use chess::game::Game;
+use chess::r#move::{Coord, MoveKey};
+use chess_engine::search::{SearchLimits, Searcher, MATE};
+use chess_engine::tt::{Bound, TranspositionTable, TtEntry};
+
+fn entry(depth: u32, score: i32) -> TtEntry {
+    TtEntry {
+        depth,
+        bound: Bound::Exact,
+        score,
+        best_move: Some(MoveKey {
+            from: Coord::new(4, 1),
+            to: Coord::new(4, 3),
+            promotion: Some(2),
+        }),
+    }
+}
+
+#[test]
+pub fn store_and_probe() {
+    let tt = TranspositionTable::new(1);
+    assert!(tt.capacity() > 10_000);
+    assert_eq!(tt.probe(42, 0), None);
+
+    tt.store(42, 0, entry(5, -120));
+    assert_eq!(tt.probe(42, 0), Some(entry(5, -120)));
+    assert_eq!(tt.probe(43, 0), None);
+
+    // a mate 3 plies past a node 2 plies deep is 3 plies past the same node 6 plies deep.
+    tt.store(7, 2, entry(3, MATE - 5));
+    assert_eq!(tt.probe(7, 6).unwrap().score, MATE - 9);
+    tt.store(8, 2, entry(3, -MATE + 5));
+    assert_eq!(tt.probe(8, 0).unwrap().score, -MATE + 3);
+
+    // a much shallower inexact result doesn't replace a deep one.
+    tt.store(42, 0, TtEntry { bound: Bound::Lower, ..entry(1, 300) });
+    assert_eq!(tt.probe(42, 0).unwrap().depth, 5);
+
+    tt.clear();
+    assert_eq!(tt.probe(42, 0), None);
+}
+
+#[test]
+pub fn shared_between_threads() {
+    let tt = Arc::new(TranspositionTable::new(1));
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let tt = tt.clone();
+            thread::spawn(move || {
+                for i in 0..20_000u64 {
+                    let hash = i.wrapping_mul(0x9e3779b97f4a7c15) ^ t;
+                    tt.store(hash, 0, entry((i % 20) as u32, i as i32));
+                    // whatever is read back belongs to the hash it was read with.
+                    if let Some(found) = tt.probe(hash, 0) {
+                        assert_eq!(found.score as u64 % 20, found.depth as u64);
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let mut game = Game::from_fen("7k/8/5K2/8/8/8/8/1R6 w - - 0 1").unwrap();
+    let mut searcher = Searcher::new(SearchLimits::new().depth(4)).with_table(tt.clone());
+    let result = searcher.search(&mut game).unwrap();
+    assert_eq!(result.score, MATE - 3);
+    assert_eq!(tt.probe(game.hash(), 0).unwrap().best_move, Some(result.pv[0].key()));
+}
@@ -0,0 +1,99 @@
+use chess::fen::standard_catalog;
+use chess::game::Game;
+use chess::pgn::parse_pgn;
+use chess_engine::eval::{EvalParams, Evaluator, PieceWeights};
+use chess_engine::tune::Tuner;
+
+const GAMES: &str = r#"[FEN "4k3/8/8/8/8/8/8/3QK3 w - - 0 1"]
+
+1. Qd2 Kf7 2. Qd3 Kg7 1-0
+
+[FEN "3qk3/8/8/8/8/8/8/4K3 w - - 0 1"]
+
+1. Kf2 Qd7 2. Kg2 Qd8 0-1
+
+[FEN "4k3/8/8/8/8/8/8/4K3 w - - 0 1"]
+
+1. Kd2 Kd7 1/2-1/2
+
+[FEN "4k3/8/8/8/8/8/8/3QK3 w - - 0 1"]
+
+1. Qd2 Kf7 1/2-1/2
+
+1. e4 e5 *
+"#;
+
+#[test]
+pub fn params() {
+    let params = EvalParams::parse("# a comment\nn 350 5 20 0\n\nQ 900 2 10 0\n").unwrap();
+    let knight = PieceWeights {
+        material: 350,
+        mobility: 5,
+        center: 20,
+        advance: 0,
+    };
+    assert_eq!(params.get('N'), Some(knight));
+    assert_eq!(params.get('n'), Some(knight));
+    assert_eq!(params.get('R'), None);
+    assert_eq!(EvalParams::parse(&params.to_text()).unwrap(), params);
+
+    assert!(EvalParams::parse("N 350 5 20").is_err());
+    assert!(EvalParams::parse("NB 350 5 20 0").is_err());
+    assert!(EvalParams::parse("N a 5 20 0").is_err());
+
+    // the seeded weights are the defaults, and change the score once they're changed.
+    let game = Game::from_fen("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+    let score = Evaluator::new().evaluate(&game);
+    let mut seeded = EvalParams::seeded(&standard_catalog());
+    assert_eq!(Evaluator::new().with_params(seeded.clone()).evaluate(&game), score);
+    seeded.set('N', PieceWeights { material: 400, ..seeded.get('N').unwrap() });
+    assert_eq!(Evaluator::new().with_params(seeded).evaluate(&game), score + 100);
+}
+
+#[test]
+pub fn terms_add_up() {
+    let params = EvalParams::seeded(&standard_catalog());
+    for fen in [
+        "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 2 3",
+        "4k3/1P6/8/3n4/8/2B5/8/4K3 w - - 0 1",
+    ] {
+        let game = Game::from_fen(fen).unwrap();
+        let mut evaluator = Evaluator::new();
+        let weighted: f64 = evaluator
+            .terms(&game)
+            .iter()
+            .map(|(symbol, terms)| {
+                let weights = params.get(*symbol).unwrap().to_array();
+                terms.iter().zip(weights).map(|(term, weight)| term * weight as f64).sum::<f64>()
+            })
+            .sum();
+        // the tables round each tile down.
+        let pieces = fen.split(' ').next().unwrap().chars().filter(|c| c.is_alphabetic()).count();
+        let score = evaluator.evaluate(&game) as f64;
+        assert!((weighted - score).abs() <= 2.0 * pieces as f64, "{}: {} {}", fen, weighted, score);
+    }
+}
+
+#[test]
+pub fn tuning() {
+    let mut tuner = Tuner::new(standard_catalog()).with_skip_plies(0);
+    let games = parse_pgn(GAMES).unwrap();
+    let added: Vec<usize> = games.iter().map(|game| tuner.add_game(game).unwrap()).collect();
+    // unfinished games don't count.
+    assert_eq!(added, [4, 4, 2, 2, 0]);
+    assert_eq!(tuner.len(), 12);
+
+    let scale = tuner.fit_scale();
+    assert!(scale > 0.0);
+    let before = tuner.error();
+    let after = tuner.tune(200);
+    assert!(after < before, "{} {}", after, before);
+    assert!(tuner.params().get('Q').unwrap().material > 0);
+    // pawns never showed up, so nothing moved them.
+    assert_eq!(tuner.params().get('P'), EvalParams::seeded(&standard_catalog()).get('P'));
+
+    // starting from other weights.
+    let start = EvalParams::parse("Q 500 0 0 0").unwrap();
+    let tuner = Tuner::new(standard_catalog()).with_params(&start);
+    assert_eq!(tuner.params().get('Q'), start.get('Q'));
+}
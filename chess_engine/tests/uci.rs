@@ -0,0 +1,305 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+use chess_engine::book::{Book, BookFormat};
+use chess_engine::nnue::Trainer;
+use chess_engine::variant::builtin_variants;
+
+// runs the uci binary on a script, the output once the input ran out and the engine is done.
+fn run(script: &str) -> Vec<String> {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    engine.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+    let output = engine.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap().lines().map(|line| line.to_string()).collect()
+}
+
+fn best_move(output: &[String]) -> &str {
+    let line = output.iter().find(|line| line.starts_with("bestmove")).unwrap();
+    line.split_whitespace().nth(1).unwrap()
+}
+
+#[test]
+pub fn handshake() {
+    let output = run("uci\nisready\nquit\n");
+    assert_eq!(output.first().unwrap(), "id name chess-rs");
+    assert!(output.iter().any(|line| line.starts_with("option name Hash type spin")));
+    assert!(output.iter().any(|line| line.starts_with("option name Threads type spin")));
+    assert!(output.iter().any(|line| line == "option name Ponder type check default false"));
+    let uciok = output.iter().position(|line| line == "uciok").unwrap();
+    assert_eq!(output[uciok + 1], "readyok");
+}
+
+#[test]
+pub fn position_and_go() {
+    let output = run("position startpos moves e2e4 e7e5\ngo depth 2\n");
+    let info: Vec<&String> = output.iter().filter(|line| line.starts_with("info depth")).collect();
+    assert_eq!(info.len(), 2);
+    for field in ["depth 2", "score cp", "nodes", "nps", "time", "pv"] {
+        assert!(info[1].contains(field), "{}", info[1]);
+    }
+
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    for uci in ["e2e4", "e7e5", best_move(&output)] {
+        let m = game.parse_uci(uci).unwrap();
+        game.execute_move(&m).unwrap();
+    }
+
+    let output = run("position fen 7k/8/5K2/8/8/8/8/1R6 w - - 0 1\ngo mate 2\n");
+    assert!(output.iter().any(|line| line.contains("score mate 2")));
+    assert!(output.last().unwrap().starts_with("bestmove"));
+    assert!(output.last().unwrap().contains("ponder"));
+
+    // a move that isn't legal keeps the last position.
+    let output = run("position startpos moves e2e5\ngo depth 1\n");
+    assert!(output[0].starts_with("info string"));
+    assert!(Game::from_fen(STARTING_FEN).unwrap().parse_uci(best_move(&output)).is_ok());
+}
+
+#[test]
+pub fn options_and_clocks() {
+    let output = run("setoption name Threads value 2\nsetoption name Hash value 1\nsetoption name Clear Hash\nucinewgame\nposition startpos\ngo wtime 2000 btime 2000 winc 0 binc 0\n");
+    assert!(!output.iter().any(|line| line.starts_with("info string")), "{:?}", output);
+    assert!(Game::from_fen(STARTING_FEN).unwrap().parse_uci(best_move(&output)).is_ok());
+
+    let output = run("setoption name Nonsense value 3\n");
+    assert!(output[0].starts_with("info string unknown option"));
+}
+
+#[test]
+pub fn infinite_until_stop() {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = engine.stdin.take().unwrap();
+    let mut stdout = BufReader::new(engine.stdout.take().unwrap());
+
+    // a mate in one is found right away, but the answer still waits for stop.
+    writeln!(stdin, "position fen 7k/8/6K1/8/8/8/8/R7 w - - 0 1\ngo infinite").unwrap();
+    thread::sleep(Duration::from_millis(300));
+    writeln!(stdin, "isready").unwrap();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        stdout.read_line(&mut line).unwrap();
+        assert!(!line.starts_with("bestmove"));
+        if line.trim() == "readyok" {
+            break;
+        }
+    }
+
+    let start = Instant::now();
+    writeln!(stdin, "stop").unwrap();
+    loop {
+        line.clear();
+        stdout.read_line(&mut line).unwrap();
+        if line.starts_with("bestmove") {
+            break;
+        }
+    }
+    assert_eq!(line.trim(), "bestmove a1a8");
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    writeln!(stdin, "quit").unwrap();
+    assert!(engine.wait().unwrap().success());
+}
+
+#[test]
+pub fn search_moves() {
+    let output = run("position startpos\ngo depth 3 searchmoves a2a3 h2h4\n");
+    assert!(["a2a3", "h2h4"].contains(&best_move(&output)), "{:?}", output);
+    for line in output.iter().filter(|line| line.starts_with("info depth")) {
+        let pv = line.split(" pv ").nth(1).unwrap();
+        assert!(pv.starts_with("a2a3") || pv.starts_with("h2h4"), "{}", line);
+    }
+
+    // the mate is left out.
+    let output = run("position fen 7k/8/6K1/8/8/8/8/R7 w - - 0 1\ngo depth 2 searchmoves a1a2 a1b1\n");
+    assert!(["a1a2", "a1b1"].contains(&best_move(&output)), "{:?}", output);
+
+    let output = run("position startpos\ngo depth 1 searchmoves e2e5\n");
+    assert!(output[0].starts_with("info string"));
+    assert_eq!(best_move(&output), "0000");
+}
+
+#[test]
+pub fn ponder() {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = engine.stdin.take().unwrap();
+    let mut stdout = BufReader::new(engine.stdout.take().unwrap());
+    let mut line = String::new();
+    // the first line starting with prefix after the command, failing on a bestmove before it.
+    let mut answer = |stdin: &mut dyn Write, command: &str, prefix: &str| {
+        writeln!(stdin, "{}", command).unwrap();
+        loop {
+            line.clear();
+            stdout.read_line(&mut line).unwrap();
+            if line.starts_with(prefix) {
+                return line.trim().to_string();
+            }
+            assert!(!line.starts_with("bestmove"), "{}", line);
+        }
+    };
+
+    // pondering goes on until the ponderhit, even once it's found the mate, and then the real
+    // search answers.
+    writeln!(stdin, "position fen 7k/8/6K1/8/8/8/8/R7 w - - 0 1\ngo ponder depth 2").unwrap();
+    thread::sleep(Duration::from_millis(300));
+    answer(&mut stdin, "isready", "readyok");
+    assert!(answer(&mut stdin, "ponderhit", "bestmove").starts_with("bestmove a1a8"));
+
+    // a stop answers with the ponder search's move.
+    writeln!(stdin, "position fen 7k/8/6K1/8/8/8/8/R7 w - - 0 1\ngo ponder wtime 1000 btime 1000").unwrap();
+    thread::sleep(Duration::from_millis(300));
+    answer(&mut stdin, "isready", "readyok");
+    assert!(answer(&mut stdin, "stop", "bestmove").starts_with("bestmove a1a8"));
+    answer(&mut stdin, "isready", "readyok");
+
+    // the clock only starts at the ponderhit, and the ponder move is the gui's reply.
+    writeln!(stdin, "setoption name Ponder value true").unwrap();
+    writeln!(stdin, "position startpos moves e2e4 e7e5\ngo ponder movetime 200").unwrap();
+    thread::sleep(Duration::from_millis(500));
+    answer(&mut stdin, "isready", "readyok");
+    let start = Instant::now();
+    let best = answer(&mut stdin, "ponderhit", "bestmove");
+    assert!(start.elapsed() < Duration::from_secs(5));
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    for uci in ["e2e4", "e7e5", best.split_whitespace().nth(1).unwrap()] {
+        let m = game.parse_uci(uci).unwrap();
+        game.execute_move(&m).unwrap();
+    }
+    assert!(best.contains(" ponder "), "{}", best);
+
+    writeln!(stdin, "quit").unwrap();
+    assert!(engine.wait().unwrap().success());
+}
+
+#[test]
+pub fn variants() {
+    let output = run("uci\nsetoption name UCI_Variant value capablanca\nposition startpos moves c1d3\ngo depth 1\n");
+    assert!(output.iter().any(|line| line.starts_with("option name UCI_Variant type combo default chess var chess var original var capablanca")));
+    assert!(output.iter().any(|line| line.starts_with("info string variant capablanca files 10 ranks 8")));
+    let variant = &builtin_variants()[2];
+    let mut game = variant.start().unwrap();
+    let m = game.parse_uci("c1d3").unwrap();
+    game.execute_move(&m).unwrap();
+    assert!(game.parse_uci(best_move(&output)).is_ok());
+
+    let output = run("setoption name UCI_Variant value nonsense\n");
+    assert!(output[0].starts_with("info string unknown variant"));
+
+    // a file of variants adds to the list.
+    let path = std::env::temp_dir().join(format!("chess-rs-variants-{}.ini", std::process::id()));
+    std::fs::write(&path, "[kings]\nstartFen = 4k3/8/8/8/8/8/8/4K3 w - - 0 1\n").unwrap();
+    let output = run(&format!(
+        "setoption name VariantPath value {}\nsetoption name UCI_Variant value kings\nposition startpos\ngo depth 1\n",
+        path.display()
+    ));
+    std::fs::remove_file(&path).unwrap();
+    assert!(output[0].ends_with("var kings"), "{:?}", output);
+    assert!(output[1].starts_with("info string variant kings files 8 ranks 8"));
+    assert!(best_move(&output).starts_with("e1"));
+
+    let output = run("setoption name VariantPath value /nonexistent/variants.ini\n");
+    assert!(output[0].starts_with("info string can't read"));
+}
+
+#[test]
+pub fn book_moves() {
+    let game = Game::from_fen(STARTING_FEN).unwrap();
+    let mut book = Book::new(BookFormat::Polyglot);
+    book.add(&game, &game.parse_uci("b1a3").unwrap(), 1).unwrap();
+    let path = std::env::temp_dir().join(format!("chess-rs-book-{}.bin", std::process::id()));
+    std::fs::write(&path, book.to_bytes()).unwrap();
+
+    // the book move comes without a search, and out of the book it searches again.
+    let output = run(&format!(
+        "setoption name BookFile value {}\nposition startpos\ngo depth 3\nposition startpos moves e2e4\ngo depth 1\n",
+        path.display()
+    ));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(output[0], format!("info string book {} with 1 entries", path.display()));
+    assert_eq!(output[1], "bestmove b1a3");
+    assert!(output[2].starts_with("info depth 1"));
+
+    let output = run("setoption name BookFile value /nonexistent/book.bin\n");
+    assert!(output[0].starts_with("info string can't read"));
+}
+
+#[test]
+pub fn eval_file() {
+    // knights are worth less than nothing, so the free one isn't taken.
+    let path = std::env::temp_dir().join(format!("chess-rs-params-{}.txt", std::process::id()));
+    std::fs::write(&path, "N -1000 4 30 60\n").unwrap();
+    let fen = "4k3/8/8/3n4/8/8/8/3QK3 w - - 0 1";
+    assert_eq!(best_move(&run(&format!("position fen {}\ngo depth 2\n", fen))), "d1d5");
+    let output = run(&format!("setoption name EvalFile value {}\nposition fen {}\ngo depth 2\n", path.display(), fen));
+    std::fs::remove_file(&path).unwrap();
+    assert_ne!(best_move(&output), "d1d5");
+
+    let output = run("setoption name EvalFile value /nonexistent/params.txt\n");
+    assert!(output[0].starts_with("info string can't read"));
+}
+
+#[test]
+pub fn multi_pv() {
+    let output = run("setoption name MultiPV value 3\nposition startpos\ngo depth 2\n");
+    for depth in 1..=2 {
+        for line in 1..=3 {
+            let prefix = format!("info depth {} multipv {} score", depth, line);
+            assert!(output.iter().any(|info| info.starts_with(&prefix)), "{}", prefix);
+        }
+    }
+    let first = output.iter().rev().find(|line| line.starts_with("info depth 2 multipv 1 ")).unwrap();
+    let pv = first.split(" pv ").nth(1).unwrap();
+    assert!(pv.starts_with(best_move(&output)));
+}
+
+#[test]
+pub fn skill_and_personality() {
+    let output = run("uci\nquit\n");
+    for option in ["Skill Level type spin default 20", "UCI_LimitStrength", "UCI_Elo", "Personality type combo"] {
+        assert!(output.iter().any(|line| line.starts_with(&format!("option name {}", option))), "{}", option);
+    }
+
+    // the weakest level only looks a ply ahead, but still from 4 lines.
+    let output = run("setoption name Skill Level value 0\nsetoption name Personality value aggressive\nposition startpos\ngo depth 5\n");
+    assert!(output.iter().any(|line| line.starts_with("info depth 1 multipv 4 ")));
+    assert!(!output.iter().any(|line| line.starts_with("info depth 2 ")));
+    let game = Game::from_fen(STARTING_FEN).unwrap();
+    assert!(game.parse_uci(best_move(&output)).is_ok());
+
+    let output = run("setoption name UCI_LimitStrength value true\nsetoption name UCI_Elo value 2800\nposition startpos\ngo depth 2\n");
+    assert!(!output.iter().any(|line| line.contains("multipv 2")));
+    let output = run("setoption name Personality value reckless\n");
+    assert_eq!(output, ["info string unknown personality reckless"]);
+}
+
+#[test]
+pub fn nnue_file() {
+    let chess = &builtin_variants()[0];
+    let network = Trainer::new(chess, 4).unwrap().with_seed(7).network();
+    let path = std::env::temp_dir().join(format!("chess-rs-net-{}.nnue", std::process::id()));
+    std::fs::write(&path, network.to_bytes()).unwrap();
+    let output = run(&format!("setoption name NNUEFile value {}\nposition startpos\ngo depth 2\n", path.display()));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(output[0], format!("info string network {} with {} inputs", path.display(), 2 * 6 * 64));
+    let game = Game::from_fen(STARTING_FEN).unwrap();
+    assert!(game.parse_uci(best_move(&output)).is_ok());
+
+    let output = run("setoption name NNUEFile value /nonexistent/net.nnue\n");
+    assert!(output[0].starts_with("info string can't read"));
+}
@@ -0,0 +1,76 @@
+use chess::fen::STARTING_FEN;
+use chess::game::Game;
+use chess_engine::variant::{builtin_variants, parse_variants};
+
+#[test]
+pub fn builtins() {
+    let variants = builtin_variants();
+    let names: Vec<&str> = variants.iter().map(|variant| variant.name()).collect();
+    assert_eq!(names, ["chess", "original", "capablanca"]);
+    assert_eq!(variants[0].start_fen(), STARTING_FEN);
+    assert_eq!(variants[0].start().unwrap().legal_moves().unwrap().len(), 20);
+
+    // notation goes past the 8th rank and the 9th file.
+    let mut original = variants[1].start().unwrap();
+    assert_eq!(original.board().width(), 10);
+    for uci in ["b2b4", "b9b7", "c1d3", "c10d8", "b4b5", "d8c6"] {
+        let m = original.parse_uci(uci).unwrap();
+        assert_eq!(original.move_to_uci(&m), uci);
+        original.execute_move(&m).unwrap();
+    }
+
+    let capablanca = variants[2].start().unwrap();
+    assert_eq!(capablanca.to_fen().unwrap(), variants[2].start_fen());
+    // the archbishop jumps like a knight and the chancellor moves like a rook, knights aside.
+    for uci in ["c1b3", "c1d3", "h1g3", "h1i3"] {
+        assert!(capablanca.parse_uci(uci).is_ok(), "{}", uci);
+    }
+    assert_eq!(capablanca.legal_moves().unwrap().len(), 28);
+
+    // pawns promote to the new pieces too.
+    let mut promoting = variants[2].game("5k4/1P8/10/10/10/10/10/10/10/5K4 w - - 0 1").unwrap();
+    for uci in ["b9b10q", "b9b10a", "b9b10c"] {
+        assert!(promoting.parse_uci(uci).is_ok(), "{}", uci);
+    }
+    let m = promoting.parse_uci("b9b10c").unwrap();
+    promoting.execute_move(&m).unwrap();
+    assert_eq!(promoting.to_fen().unwrap(), "1C3k4/10/10/10/10/10/10/10/10/5K4 b - - 0 1");
+}
+
+#[test]
+pub fn parsing() {
+    let text = "
+# a king that moves like a queen, and a pawnless board.
+[queenking]
+startFen = 3k4/8/8/8/8/8/8/3K4 w - - 0 1
+files = 8
+ranks = 8
+piece = k 0 Q royal
+";
+    let variants = parse_variants(text).unwrap();
+    assert_eq!(variants.len(), 1);
+    let game = variants[0].start().unwrap();
+    assert!(game.parse_uci("d1a4").is_ok());
+
+    let wrong_size = "[small]\nstartFen = 3k4/8/8/8/8/8/8/3K4 w - - 0 1\nfiles = 10\n";
+    assert!(parse_variants(wrong_size).is_err());
+    assert!(parse_variants("startFen = 8/8/8/8/8/8/8/8 w - - 0 1\n").is_err());
+    assert!(parse_variants("[x]\npiece = z 3 NotBetza\n").is_err());
+    assert!(parse_variants("[x]\npromotions = p qz\n").is_err());
+    assert!(parse_variants("[x]\nnonsense = 1\n").is_err());
+}
+
+#[test]
+pub fn redefined_promotion() {
+    let variants = parse_variants("[nr]\npiece = n 3 NN nightrider\n").unwrap();
+    let a8 = chess::notation::parse_square("a8").unwrap();
+    let moves_from_a8 = |game: &Game| game.legal_moves().unwrap().into_iter().filter(|m| m.from() == a8).count();
+    assert_eq!(moves_from_a8(&variants[0].game("N6k/8/8/8/8/8/8/7K w - - 0 1").unwrap()), 6);
+
+    // a pawn promoting to n becomes the nightrider, not the standard knight.
+    let mut game = variants[0].game("7k/P7/8/8/8/8/8/7K w - - 0 1").unwrap();
+    let m = game.parse_uci("a7a8n").unwrap();
+    game.execute_move(&m).unwrap();
+    game.pass_turn();
+    assert_eq!(moves_from_a8(&game), 6);
+}